@@ -1,3 +1,11 @@
+use crate::io::output::OutputTemplate;
+use crate::io::time::TimeFormat;
+use crate::path::environment::Environment;
+use crate::path::health::Severity;
+use crate::path::pattern::PatternOptions;
+use crate::path::searcher::CleanOptions;
+use crate::search::semver::VersionReq;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ColorWhen {
     #[default]
@@ -6,6 +14,14 @@ pub enum ColorWhen {
     Always,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+    Ndjson,
+}
+
 #[derive(Debug, Clone)]
 pub enum PreferTarget {
     /// Traditional index-based preference (backward compatible)
@@ -14,6 +30,11 @@ pub enum PreferTarget {
     PathBased { name: String, path: String },
     /// Path-only preference (like `fish_add_path`)
     PathOnly { path: String },
+    /// Prefer the highest version satisfying a constraint like `>=3.11,<3.13`
+    VersionConstraint {
+        name: String,
+        constraint: VersionReq,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -24,12 +45,55 @@ pub enum DeleteTarget {
     Path(String),
 }
 
+/// Where the hidden `__add` command places new entries
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum AddPosition {
+    /// In front of `PATH` (the default)
+    #[default]
+    Prepend,
+    /// At the end of `PATH`
+    Append,
+    /// At a 1-based position
+    At(usize),
+    Before(PathAnchor),
+    After(PathAnchor),
+}
+
+/// An existing `PATH` entry that another one is placed relative to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathAnchor {
+    /// 1-based `PATH` index
+    Index(usize),
+    /// Exact directory, matched like `PATH` entries (tilde and symlinks resolved)
+    Path(String),
+    /// Fuzzy pattern; the best-ranked entry is used
+    Pattern(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum PinAction {
+    /// Keep an entry at its position, moving it to `position` first if given
+    Pin {
+        target: PathAnchor,
+        position: Option<usize>,
+        save: bool,
+    },
+    /// Drop the pin on an entry; `save` also drops it from the config
+    Unpin { target: PathAnchor, save: bool },
+}
+
 #[derive(Debug, Clone)]
 pub enum ApplyTarget {
     CurrentShell,
     Shell(String),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAction {
+    Rebuild,
+    Clear,
+}
+
 #[derive(Debug, Clone)]
 pub enum PathEdit {
     Move { from: usize, to: usize },
@@ -40,6 +104,14 @@ pub enum PathEdit {
 pub enum HistoryAction {
     Undo(usize),
     Redo(usize),
+    List,
+}
+
+/// One operation of `whi batch` and the text it was parsed from
+#[derive(Debug, Clone)]
+pub struct BatchStep {
+    pub operation: String,
+    pub args: Args,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -49,20 +121,41 @@ pub struct Args {
     pub all: bool,
     pub full: bool,
     pub follow_symlinks: bool,
+    pub chain: bool,
     pub print0: bool,
     pub quiet: bool,
     pub silent: bool,
     pub one: bool,
     pub show_nonexec: bool,
     pub path_override: Option<String>,
+    pub env_context: Option<Environment>,
     pub color: ColorWhen,
+    pub format: OutputFormat,
     pub stat: bool,
     pub no_index: bool,
     pub swap_fuzzy: bool,
+    pub pattern: PatternOptions,
+    pub explain_score: bool,
     pub path_edit: Option<PathEdit>,
     pub prefer_target: Option<PreferTarget>,
     pub clean: bool,
+    pub clean_options: CleanOptions,
     pub delete_targets: Vec<DeleteTarget>,
+    pub add_paths: Vec<String>,
+    pub add_position: AddPosition,
+    pub move_if_present: bool,
+    pub batch: Vec<BatchStep>,
+    /// Leave the history snapshot to the caller, as `batch` does for its steps
+    pub defer_snapshot: bool,
+    /// Preview a `PATH` edit instead of emitting the new `PATH`
+    pub dry_run: bool,
+    /// Apply a `PATH` edit even if it displaces a pinned entry
+    pub force: bool,
+    pub pin_action: Option<PinAction>,
+    /// Reorder `PATH` to satisfy `~/.whi/order_rules`
+    pub enforce_order: bool,
+    /// Report entries breaking `~/.whi/order_rules` without changing `PATH`
+    pub order_check: bool,
     pub apply_target: Option<ApplyTarget>,
     pub no_protect: bool,
     pub diff: bool,
@@ -73,6 +166,20 @@ pub struct Args {
     pub save_profile: Option<String>,
     pub load_profile: Option<String>,
     pub remove_profile: Option<String>,
+    pub shadows: bool,
+    pub shadow_index: Option<usize>,
+    pub shadow_pattern: Option<String>,
+    pub matrix_names: Vec<String>,
+    pub no_cache: bool,
+    pub versions: bool,
+    pub resolve_shims: bool,
+    pub format_template: Option<OutputTemplate>,
+    pub time_format: Option<TimeFormat>,
+    pub cache_action: Option<CacheAction>,
+    pub doctor: bool,
+    pub explain: Option<String>,
+    pub not_found: Option<String>,
+    pub doctor_fail_on: Option<Severity>,
 }
 
 impl Args {
@@ -104,6 +211,11 @@ pub fn parse_prefer_arguments(tokens: Vec<String>) -> Result<PreferTarget, Strin
     let remaining: Vec<String> = iter.collect();
     let target_raw = remaining.join(" ");
 
+    if VersionReq::looks_like_constraint(&target_raw) {
+        let constraint = VersionReq::parse(&target_raw)?;
+        return Ok(PreferTarget::VersionConstraint { name, constraint });
+    }
+
     if remaining.len() == 1 {
         let candidate = &remaining[0];
         if let Ok(index) = candidate.parse::<usize>()
@@ -119,6 +231,24 @@ pub fn parse_prefer_arguments(tokens: Vec<String>) -> Result<PreferTarget, Strin
     })
 }
 
+/// Parse `__prefer` arguments when the target is a `--regex` or `--glob` pattern.
+///
+/// Patterns are never read as indices or version constraints, so `^/opt` is
+/// not taken for a caret requirement.
+pub fn parse_prefer_pattern_arguments(tokens: Vec<String>) -> Result<PreferTarget, String> {
+    let mut iter = tokens.into_iter();
+    let name = iter.next();
+    let pattern = iter.collect::<Vec<_>>().join(" ");
+
+    match name {
+        Some(name) if !pattern.is_empty() => Ok(PreferTarget::PathBased {
+            name,
+            path: pattern,
+        }),
+        _ => Err("prefer with --regex or --glob requires NAME and PATTERN".to_string()),
+    }
+}
+
 /// Parse arguments for the hidden `__delete` command.
 pub fn parse_delete_arguments(tokens: Vec<String>) -> Result<Vec<DeleteTarget>, String> {
     if tokens.is_empty() {
@@ -156,6 +286,36 @@ fn looks_like_path(s: &str) -> bool {
     s.contains('/') || s.starts_with('~') || s.starts_with('.') || s.contains('\\')
 }
 
+/// Parse an `--before`/`--after` anchor: an index, a path, or a fuzzy pattern
+#[must_use]
+pub fn parse_path_anchor(token: &str) -> PathAnchor {
+    if looks_like_path(token) {
+        PathAnchor::Path(token.to_string())
+    } else if let Ok(index) = token.parse::<usize>() {
+        PathAnchor::Index(index)
+    } else {
+        PathAnchor::Pattern(token.to_string())
+    }
+}
+
+/// Parse the placement options of the hidden `__add` command.
+pub fn parse_add_position(
+    append: bool,
+    at: Option<usize>,
+    before: Option<&str>,
+    after: Option<&str>,
+) -> Result<AddPosition, String> {
+    match (append, at, before, after) {
+        (false, None, None, None) => Ok(AddPosition::Prepend),
+        (true, None, None, None) => Ok(AddPosition::Append),
+        (false, Some(0), None, None) => Err("Position must be >= 1".to_string()),
+        (false, Some(position), None, None) => Ok(AddPosition::At(position)),
+        (false, None, Some(anchor), None) => Ok(AddPosition::Before(parse_path_anchor(anchor))),
+        (false, None, None, Some(anchor)) => Ok(AddPosition::After(parse_path_anchor(anchor))),
+        _ => Err("Use only one of --append, --at, --before and --after".to_string()),
+    }
+}
+
 /// Parse arguments for the hidden `__add` command.
 pub fn parse_add_arguments(tokens: Vec<String>) -> Result<Vec<String>, String> {
     if tokens.is_empty() {
@@ -165,3 +325,91 @@ pub fn parse_add_arguments(tokens: Vec<String>) -> Result<Vec<String>, String> {
     // Return paths as-is, preserving all provided paths
     Ok(tokens)
 }
+
+/// Split `whi batch` input into operations at `;` and newlines outside quotes
+#[must_use]
+pub fn split_batch_operations(input: &str) -> Vec<&str> {
+    let mut operations = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, ch) in input.char_indices() {
+        match (quote, ch) {
+            _ if escaped => escaped = false,
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('"') | None, '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, ';' | '\n') => {
+                operations.push(&input[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    operations.push(&input[start..]);
+    operations
+}
+
+/// Split one batch operation into words as a POSIX shell would: single
+/// quotes keep everything literally, double quotes and bare backslashes
+/// escape the next character
+pub fn split_operation_words(operation: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = operation.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        // Inside double quotes a backslash only escapes these
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                word.push(chars.next().unwrap_or('\\'));
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
@@ -8,11 +8,10 @@ mod handlers;
 mod internal;
 mod spec;
 
-#[must_use]
-pub fn run() -> i32 {
-    let cli_result = spec::Cli::try_parse();
-
-    let spec::Cli { query, command } = match cli_result {
+/// Parse the command line, showing the public names of hidden commands in
+/// usage errors
+fn parse_cli() -> spec::Cli {
+    match spec::Cli::try_parse() {
         Ok(cli) => cli,
         Err(err) => {
             let err_msg = err.to_string();
@@ -26,7 +25,10 @@ pub fn run() -> i32 {
                 .replace("whi __undo", "whi undo")
                 .replace("whi __redo", "whi redo")
                 .replace("whi __load", "whi load")
-                .replace("whi __init", "whi init");
+                .replace("whi __init", "whi init")
+                .replace("whi __batch", "whi batch")
+                .replace("whi __pin", "whi pin")
+                .replace("whi __enforce", "whi enforce");
 
             if rewritten != err_msg {
                 eprint!("{rewritten}");
@@ -35,7 +37,12 @@ pub fn run() -> i32 {
 
             err.exit();
         }
-    };
+    }
+}
+
+#[must_use]
+pub fn run() -> i32 {
+    let spec::Cli { query, command } = parse_cli();
 
     if let Err(e) = runtime::ensure_config_exists() {
         eprintln!("Error: {e}");
@@ -56,6 +63,8 @@ pub fn run() -> i32 {
         Some(spec::Command::Diff(diff)) => handlers::run_diff(diff),
         Some(spec::Command::Apply(apply)) => handlers::run_apply(apply),
         Some(spec::Command::Help) => handlers::run_help(),
+        // Checking leaves PATH alone, so it needs no shell integration
+        Some(spec::Command::Enforce(enforce)) if enforce.check => handlers::run_enforce_check(),
         // Public PATH-manipulation commands are intentionally shell-facing only.
         // The shell integration wrappers translate them into the hidden __* protocol,
         // which is what actually mutates PATH and returns the new value to export.
@@ -63,24 +72,28 @@ pub fn run() -> i32 {
             spec::Command::Prefer
             | spec::Command::Move
             | spec::Command::Switch
-            | spec::Command::Clean
+            | spec::Command::Clean(_)
             | spec::Command::Delete
             | spec::Command::Reset
             | spec::Command::Undo(_)
             | spec::Command::Redo(_)
             | spec::Command::Load(_)
-            | spec::Command::Add,
+            | spec::Command::Add
+            | spec::Command::Batch
+            | spec::Command::Pin
+            | spec::Command::Enforce(_),
         ) => check_shell_integration().unwrap_or(0),
         Some(spec::Command::Save(save)) => handlers::run_save_profile(save),
         Some(spec::Command::List) => handlers::run_list_profiles(),
         Some(spec::Command::RemoveProfile(remove)) => handlers::run_remove_profile(remove),
+        Some(spec::Command::Unpin(unpin)) => handlers::run_unpin(&unpin),
         Some(spec::Command::Init(init)) => handlers::run_init(init),
         Some(spec::Command::HiddenMove(move_args)) => internal::run_hidden_move(&move_args),
         Some(spec::Command::HiddenSwap(swap_args)) => internal::run_hidden_swap(&swap_args),
-        Some(spec::Command::HiddenClean) => internal::run_hidden_clean(),
+        Some(spec::Command::HiddenClean(clean_args)) => internal::run_hidden_clean(&clean_args),
         Some(spec::Command::HiddenDelete(delete_args)) => internal::run_hidden_delete(delete_args),
         Some(spec::Command::HiddenPrefer(prefer_args)) => internal::run_hidden_prefer(prefer_args),
-        Some(spec::Command::HiddenReset) => internal::run_hidden_reset(),
+        Some(spec::Command::HiddenReset(preview)) => internal::run_hidden_reset(preview),
         Some(spec::Command::HiddenUndo(undo_args)) => internal::run_hidden_undo(&undo_args),
         Some(spec::Command::HiddenRedo(redo_args)) => internal::run_hidden_redo(&redo_args),
         Some(spec::Command::HiddenLoad(load_args)) => internal::run_hidden_load(&load_args),
@@ -89,7 +102,17 @@ pub fn run() -> i32 {
             internal::run_hidden_load_saved_path(&args)
         }
         Some(spec::Command::HiddenAdd(add_args)) => internal::run_hidden_add(&add_args),
+        Some(spec::Command::HiddenBatch(batch_args)) => internal::run_hidden_batch(batch_args),
+        Some(spec::Command::HiddenPin(pin_args)) => internal::run_hidden_pin(&pin_args),
+        Some(spec::Command::HiddenEnforce(edit)) => internal::run_hidden_enforce(edit),
+        Some(spec::Command::HiddenNotFound(args)) => internal::run_hidden_not_found(args),
         Some(spec::Command::Shorthands) => internal::run_shorthands(),
+        Some(spec::Command::Shadows(shadows)) => handlers::run_shadows(shadows),
+        Some(spec::Command::Matrix(matrix)) => handlers::run_matrix(matrix),
+        Some(spec::Command::Cache(cache)) => handlers::run_cache(&cache),
+        Some(spec::Command::Doctor(doctor)) => handlers::run_doctor(doctor),
+        Some(spec::Command::Explain(explain)) => handlers::run_explain(explain),
+        Some(spec::Command::History(history)) => handlers::run_history(history),
         None => handlers::run_query(query),
     }
 }
@@ -1,30 +1,168 @@
 use clap::CommandFactory;
 
-use crate::cli::{ApplyTarget, Args as AppArgs};
+use crate::cli::{self, ApplyTarget, Args as AppArgs, HistoryAction, PinAction};
 use crate::commands;
+use crate::commands::support::path_support::history_for_current_scope;
 use crate::config::shell_paths;
+use crate::io::output::{OutputTemplate, TemplateField};
+use crate::path::pattern::PatternOptions;
 
 use super::check_shell_integration;
 use super::spec::{
-    ApplyArgs, Cli, ColorChoice, DiffArgs, InitArgs, RemoveProfileArgs, SaveProfileArgs,
+    ApplyArgs, CacheArgs, Cli, ColorChoice, DiffArgs, DoctorArgs, ExplainArgs, FormatChoice,
+    HistoryArgs, InitArgs, MatrixArgs, PathSourceArgs, RemoveProfileArgs, SaveProfileArgs,
+    ShadowsArgs, UnpinArgs,
 };
 
 pub(super) fn run_query(opts: super::spec::QueryArgs) -> i32 {
+    let template = match opts
+        .output
+        .format_string
+        .as_deref()
+        .map(OutputTemplate::parse)
+    {
+        Some(Ok(template)) => Some(template),
+        Some(Err(e)) => {
+            eprintln!("Error: {e}");
+            return 2;
+        }
+        None => None,
+    };
+
+    let path_override = match read_path_override(opts.source) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    // Fields a template refers to are fetched even without their flags
+    let needs = |fields: &[TemplateField]| template.as_ref().is_some_and(|t| t.uses(fields));
+
     let args = AppArgs {
         names: opts.names,
         all: opts.listing.all,
         full: opts.listing.full,
-        follow_symlinks: opts.listing.follow_symlinks,
+        follow_symlinks: opts.listing.follow_symlinks || needs(&[TemplateField::Canonical]),
+        chain: opts.listing_details.chain || needs(&[TemplateField::Chain]),
         print0: opts.output.print0,
         quiet: opts.output.quiet,
         silent: opts.output.silent,
         one: opts.listing_details.one,
         show_nonexec: opts.listing_details.show_nonexec,
-        path_override: opts.path_override,
+        path_override,
+        env_context: opts.env.map(Into::into),
         color: opts.color.unwrap_or(ColorChoice::Auto).into(),
-        stat: opts.output_details.stat,
+        format: opts.output.format.unwrap_or(FormatChoice::Human).into(),
+        stat: opts.output_details.stat || template.as_ref().is_some_and(OutputTemplate::needs_stat),
         no_index: opts.output_details.no_index,
+        versions: opts.output_details.versions || needs(&[TemplateField::Version]),
+        time_format: opts.output_details.time_format,
         swap_fuzzy: opts.mode.swap_fuzzy,
+        pattern: PatternOptions::from(&opts.pattern),
+        explain_score: opts.pattern.explain_score,
+        no_cache: opts.mode.no_cache,
+        resolve_shims: opts.mode.resolve_shims || needs(&[TemplateField::Shim]),
+        format_template: template.clone(),
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
+/// `--path` as given, or the `PATH` read from a `--path-from` source; a
+/// source that cannot be read is reported and its exit code returned
+fn read_path_override(opts: PathSourceArgs) -> Result<Option<String>, i32> {
+    let Some(source) = opts.path_from else {
+        return Ok(opts.path_override);
+    };
+
+    let current_path = std::env::var("PATH").unwrap_or_default();
+    source
+        .read(&current_path, history_for_current_scope)
+        .map(Some)
+        .map_err(|e| {
+            eprintln!("Error: {e}");
+            2
+        })
+}
+
+pub(super) fn run_shadows(opts: ShadowsArgs) -> i32 {
+    let path_override = match read_path_override(opts.source) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    let args = AppArgs {
+        shadows: true,
+        shadow_index: opts.index,
+        shadow_pattern: opts.pattern,
+        all: opts.all,
+        path_override,
+        color: opts.color.unwrap_or(ColorChoice::Auto).into(),
+        format: opts.format.unwrap_or(FormatChoice::Human).into(),
+        no_cache: opts.no_cache,
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
+pub(super) fn run_matrix(opts: MatrixArgs) -> i32 {
+    let path_override = match read_path_override(opts.source) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    let args = AppArgs {
+        matrix_names: opts.names,
+        path_override,
+        color: opts.color.unwrap_or(ColorChoice::Auto).into(),
+        format: opts.format.unwrap_or(FormatChoice::Human).into(),
+        no_cache: opts.no_cache,
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
+pub(super) fn run_explain(opts: ExplainArgs) -> i32 {
+    let path_override = match read_path_override(opts.source) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    let args = AppArgs {
+        explain: Some(opts.name),
+        path_override,
+        color: opts.color.unwrap_or(ColorChoice::Auto).into(),
+        format: opts.format.unwrap_or(FormatChoice::Human).into(),
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
+pub(super) fn run_doctor(opts: DoctorArgs) -> i32 {
+    let path_override = match read_path_override(opts.source) {
+        Ok(path) => path,
+        Err(code) => return code,
+    };
+
+    let args = AppArgs {
+        doctor: true,
+        doctor_fail_on: opts.fail_on.map(Into::into),
+        path_override,
+        color: opts.color.unwrap_or(ColorChoice::Auto).into(),
+        format: opts.format.unwrap_or(FormatChoice::Human).into(),
+        no_cache: opts.no_cache,
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
+pub(super) fn run_cache(opts: &CacheArgs) -> i32 {
+    let args = AppArgs {
+        cache_action: Some(opts.action.into()),
         ..Default::default()
     };
 
@@ -46,6 +184,16 @@ pub(super) fn run_diff(opts: DiffArgs) -> i32 {
     commands::run(&args)
 }
 
+pub(super) fn run_history(opts: HistoryArgs) -> i32 {
+    let args = AppArgs {
+        history_action: Some(HistoryAction::List),
+        time_format: opts.time_format,
+        ..Default::default()
+    };
+
+    commands::run(&args)
+}
+
 pub(super) fn run_apply(opts: ApplyArgs) -> i32 {
     if let Some(code) = check_shell_integration() {
         return code;
@@ -86,6 +234,25 @@ pub(super) fn run_remove_profile(opts: RemoveProfileArgs) -> i32 {
     commands::run(&args)
 }
 
+pub(super) fn run_unpin(opts: &UnpinArgs) -> i32 {
+    let args = AppArgs {
+        pin_action: Some(PinAction::Unpin {
+            target: cli::parse_path_anchor(&opts.target),
+            save: opts.save,
+        }),
+        ..Default::default()
+    };
+    commands::run(&args)
+}
+
+pub(super) fn run_enforce_check() -> i32 {
+    let args = AppArgs {
+        order_check: true,
+        ..Default::default()
+    };
+    commands::run(&args)
+}
+
 pub(super) fn run_list_profiles() -> i32 {
     if let Some(code) = check_shell_integration() {
         return code;
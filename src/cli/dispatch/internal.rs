@@ -1,80 +1,119 @@
-use std::path::PathBuf;
+use std::io;
+use std::os::unix::io::AsRawFd;
 
-use crate::cli::{self, Args as AppArgs, HistoryAction, PathEdit};
+use clap::Parser;
+
+use crate::cli::{self, Args as AppArgs, BatchStep, HistoryAction, PathEdit, PinAction};
 use crate::commands;
+use crate::commands::support::path_support::{active_pins, auto_order, keep_pins};
 use crate::config::shell_paths;
 use crate::path::file::{apply_path_sections, expand_shell_vars};
 use crate::path::guard::PathGuard;
-use crate::path::resolve::resolve_path;
-use crate::path::searcher::PathSearcher;
+use crate::path::pattern::PatternOptions;
+use crate::path::searcher::CleanOptions;
+use crate::platform;
 use crate::session::history::HistoryContext;
 use crate::session::store;
 use crate::shell::detect::Shell;
 
 use super::spec::{
-    HiddenAddArgs, HiddenDeleteArgs, HiddenInitArgs, HiddenLoadArgs, HiddenLoadSavedPathArgs,
-    HiddenMoveArgs, HiddenPreferArgs, HiddenRedoArgs, HiddenSwapArgs, HiddenUndoArgs,
+    CleanArgs, Cli, Command, EditArgs, HiddenAddArgs, HiddenBatchArgs, HiddenDeleteArgs,
+    HiddenInitArgs, HiddenLoadArgs, HiddenLoadSavedPathArgs, HiddenMoveArgs, HiddenNotFoundArgs,
+    HiddenPinArgs, HiddenPreferArgs, HiddenRedoArgs, HiddenSwapArgs, HiddenUndoArgs, PreviewArgs,
 };
 
 pub(super) fn run_hidden_move(opts: &HiddenMoveArgs) -> i32 {
-    let args = AppArgs {
+    run_or_report(Ok(move_args(opts)), opts.edit)
+}
+
+fn move_args(opts: &HiddenMoveArgs) -> AppArgs {
+    AppArgs {
         path_edit: Some(PathEdit::Move {
             from: opts.from,
             to: opts.to,
         }),
         ..Default::default()
-    };
-    commands::run(&args)
+    }
 }
 
 pub(super) fn run_hidden_swap(opts: &HiddenSwapArgs) -> i32 {
-    let args = AppArgs {
+    run_or_report(Ok(swap_args(opts)), opts.edit)
+}
+
+fn swap_args(opts: &HiddenSwapArgs) -> AppArgs {
+    AppArgs {
         path_edit: Some(PathEdit::Swap {
             first: opts.first,
             second: opts.second,
         }),
         ..Default::default()
-    };
-    commands::run(&args)
+    }
 }
 
-pub(super) fn run_hidden_clean() -> i32 {
-    let args = AppArgs {
+pub(super) fn run_hidden_clean(opts: &CleanArgs) -> i32 {
+    run_or_report(Ok(clean_args(opts)), opts.edit)
+}
+
+fn clean_args(opts: &CleanArgs) -> AppArgs {
+    AppArgs {
         clean: true,
+        clean_options: CleanOptions {
+            canonical: opts.canonical,
+            normalize: opts.normalize,
+            missing: opts.missing,
+        },
         ..Default::default()
-    };
-    commands::run(&args)
+    }
 }
 
 pub(super) fn run_hidden_delete(opts: HiddenDeleteArgs) -> i32 {
-    match cli::parse_delete_arguments(opts.targets) {
-        Ok(targets) => {
-            let args = AppArgs {
-                delete_targets: targets,
-                ..Default::default()
-            };
-            commands::run(&args)
-        }
-        Err(err) => {
-            eprintln!("Error: {err}");
-            2
-        }
-    }
+    let edit = opts.edit;
+    run_or_report(delete_args(opts), edit)
+}
+
+fn delete_args(opts: HiddenDeleteArgs) -> Result<AppArgs, String> {
+    Ok(AppArgs {
+        pattern: PatternOptions::from(&opts.pattern),
+        explain_score: opts.pattern.explain_score,
+        delete_targets: cli::parse_delete_arguments(opts.targets)?,
+        ..Default::default()
+    })
 }
 
 pub(super) fn run_hidden_prefer(opts: HiddenPreferArgs) -> i32 {
-    run_prefer_tokens(opts.tokens)
+    let pattern = PatternOptions::from(&opts.pattern);
+    run_or_report(
+        prefer_args(opts.tokens, pattern, opts.pattern.explain_score),
+        opts.edit,
+    )
 }
 
-pub(super) fn run_prefer_tokens(tokens: Vec<String>) -> i32 {
-    match cli::parse_prefer_arguments(tokens) {
-        Ok(target) => {
-            let args = AppArgs {
-                prefer_target: Some(target),
-                ..Default::default()
-            };
-            commands::run(&args)
-        }
+fn prefer_args(
+    tokens: Vec<String>,
+    pattern: PatternOptions,
+    explain_score: bool,
+) -> Result<AppArgs, String> {
+    let target = if pattern.is_explicit() {
+        cli::parse_prefer_pattern_arguments(tokens)?
+    } else {
+        cli::parse_prefer_arguments(tokens)?
+    };
+
+    Ok(AppArgs {
+        prefer_target: Some(target),
+        pattern,
+        explain_score,
+        ..Default::default()
+    })
+}
+
+fn run_or_report(args: Result<AppArgs, String>, edit: EditArgs) -> i32 {
+    match args {
+        Ok(args) => commands::run(&AppArgs {
+            dry_run: edit.dry_run,
+            force: edit.force,
+            ..args
+        }),
         Err(err) => {
             eprintln!("Error: {err}");
             2
@@ -82,9 +121,10 @@ pub(super) fn run_prefer_tokens(tokens: Vec<String>) -> i32 {
     }
 }
 
-pub(super) fn run_hidden_reset() -> i32 {
+pub(super) fn run_hidden_reset(preview: PreviewArgs) -> i32 {
     let args = AppArgs {
         reset: true,
+        dry_run: preview.dry_run,
         ..Default::default()
     };
     commands::run(&args)
@@ -93,6 +133,7 @@ pub(super) fn run_hidden_reset() -> i32 {
 pub(super) fn run_hidden_undo(opts: &HiddenUndoArgs) -> i32 {
     let args = AppArgs {
         history_action: Some(HistoryAction::Undo(opts.count)),
+        dry_run: opts.preview.dry_run,
         ..Default::default()
     };
     commands::run(&args)
@@ -101,6 +142,7 @@ pub(super) fn run_hidden_undo(opts: &HiddenUndoArgs) -> i32 {
 pub(super) fn run_hidden_redo(opts: &HiddenRedoArgs) -> i32 {
     let args = AppArgs {
         history_action: Some(HistoryAction::Redo(opts.count)),
+        dry_run: opts.preview.dry_run,
         ..Default::default()
     };
     commands::run(&args)
@@ -108,6 +150,12 @@ pub(super) fn run_hidden_redo(opts: &HiddenRedoArgs) -> i32 {
 
 pub(super) fn run_hidden_load(opts: &HiddenLoadArgs) -> i32 {
     let session_pid = current_session_pid();
+    let edit = opts.edit;
+    let args = AppArgs {
+        dry_run: edit.dry_run,
+        force: edit.force,
+        ..Default::default()
+    };
 
     match shell_paths::load_profile(&opts.name) {
         Ok(parsed) => {
@@ -128,6 +176,24 @@ pub(super) fn run_hidden_load(opts: &HiddenLoadArgs) -> i32 {
 
             let guarded_path =
                 PathGuard::default().ensure_protected_paths(&current_path, expanded_path);
+            // --force only lets the profile displace pins; order rules still apply
+            let guarded_path = match auto_order(&guarded_path).and_then(|ordered| {
+                if edit.force {
+                    Ok(ordered)
+                } else {
+                    active_pins().and_then(|pins| keep_pins(&current_path, &ordered, &pins))
+                }
+            }) {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    return 2;
+                }
+            };
+
+            if edit.dry_run {
+                return commands::preview(&guarded_path, &args);
+            }
 
             if let Ok(history) = HistoryContext::global(session_pid)
                 && let Err(err) = history.write_snapshot(&guarded_path)
@@ -195,51 +261,126 @@ pub(super) fn run_hidden_load_saved_path(args: &HiddenLoadSavedPathArgs) -> i32
     }
 }
 
-pub(super) fn run_hidden_add(args: &HiddenAddArgs) -> i32 {
-    let session_pid = current_session_pid();
+pub(super) fn run_hidden_add(opts: &HiddenAddArgs) -> i32 {
+    run_or_report(add_args(opts), opts.edit)
+}
 
-    let paths = match cli::parse_add_arguments(args.paths.clone()) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Error: {e}");
+fn add_args(opts: &HiddenAddArgs) -> Result<AppArgs, String> {
+    let placement = &opts.placement;
+    Ok(AppArgs {
+        add_paths: cli::parse_add_arguments(opts.paths.clone())?,
+        add_position: cli::parse_add_position(
+            placement.append,
+            placement.at,
+            placement.before.as_deref(),
+            placement.after.as_deref(),
+        )?,
+        move_if_present: opts.move_if_present,
+        ..Default::default()
+    })
+}
+
+pub(super) fn run_hidden_batch(opts: HiddenBatchArgs) -> i32 {
+    let edit = opts.edit;
+    let operations = if opts.operations.is_empty() {
+        if platform::is_tty(io::stdin().as_raw_fd()) {
+            eprintln!("Error: Give batch operations as arguments or on stdin");
             return 2;
         }
-    };
-
-    let current_path = std::env::var("PATH").unwrap_or_default();
-    let mut searcher = PathSearcher::new(&current_path);
-
-    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    for path_str in paths {
-        let resolved = match resolve_path(&path_str, &cwd) {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("Warning: Could not resolve path '{path_str}': {e}");
-                PathBuf::from(path_str)
+        match io::read_to_string(io::stdin()) {
+            Ok(input) => vec![input],
+            Err(err) => {
+                eprintln!("Error: Failed to read operations from stdin: {err}");
+                return 2;
             }
-        };
+        }
+    } else {
+        opts.operations
+    };
 
-        if searcher.contains(&resolved) {
+    let mut steps = Vec::new();
+    for operation in operations
+        .iter()
+        .flat_map(|ops| cli::split_batch_operations(ops))
+    {
+        let operation = operation.trim();
+        if operation.is_empty() || operation.starts_with('#') {
             continue;
         }
-
-        if let Err(e) = searcher.insert_at(&resolved, 1) {
-            eprintln!("Warning: Could not add '{}': {}", resolved.display(), e);
+        match batch_step_args(operation) {
+            Ok(args) => steps.push(BatchStep {
+                operation: operation.to_string(),
+                args,
+            }),
+            Err(err) => {
+                eprintln!("Error: {operation}: {err}");
+                return 2;
+            }
         }
     }
 
-    let new_path = searcher.to_path_string();
-    let guarded_path = PathGuard::default().ensure_protected_paths(&current_path, new_path);
+    if steps.is_empty() {
+        eprintln!("Error: No batch operations given");
+        return 2;
+    }
 
-    if let Ok(history) = HistoryContext::global(session_pid)
-        && let Err(err) = history.write_snapshot(&guarded_path)
-    {
-        eprintln!("Warning: Failed to write PATH snapshot: {err}");
+    let args = AppArgs {
+        batch: steps,
+        dry_run: edit.dry_run,
+        force: edit.force,
+        ..Default::default()
+    };
+    commands::run(&args)
+}
+
+/// Parse one batch operation with the same rules as its hidden command
+fn batch_step_args(operation: &str) -> Result<AppArgs, String> {
+    let mut tokens = cli::split_operation_words(operation)?.into_iter();
+    let name = tokens.next().unwrap_or_default();
+    if !matches!(
+        name.as_str(),
+        "add" | "move" | "switch" | "delete" | "prefer" | "clean"
+    ) {
+        return Err(format!(
+            "Unknown operation '{name}' (expected add, move, switch, delete, prefer or clean)"
+        ));
     }
 
-    println!("{guarded_path}");
-    0
+    let hidden = format!("__{name}");
+    let cli = Cli::try_parse_from(["whi".to_string(), hidden].into_iter().chain(tokens)).map_err(
+        |err| {
+            let message = err.to_string();
+            let first = message.lines().next().unwrap_or_default();
+            first.strip_prefix("error: ").unwrap_or(first).to_string()
+        },
+    )?;
+
+    let (args, edit) = match cli.command {
+        Some(Command::HiddenAdd(opts)) => (add_args(&opts), opts.edit),
+        Some(Command::HiddenMove(opts)) => (Ok(move_args(&opts)), opts.edit),
+        Some(Command::HiddenSwap(opts)) => (Ok(swap_args(&opts)), opts.edit),
+        Some(Command::HiddenDelete(opts)) => {
+            let edit = opts.edit;
+            (delete_args(opts), edit)
+        }
+        Some(Command::HiddenClean(opts)) => (Ok(clean_args(&opts)), opts.edit),
+        Some(Command::HiddenPrefer(opts)) => {
+            let pattern = PatternOptions::from(&opts.pattern);
+            (
+                prefer_args(opts.tokens, pattern, opts.pattern.explain_score),
+                opts.edit,
+            )
+        }
+        _ => return Err(format!("Unsupported operation '{name}'")),
+    };
+    if edit.dry_run {
+        return Err("--dry-run applies to the whole batch, not one operation".to_string());
+    }
+
+    Ok(AppArgs {
+        force: edit.force,
+        ..args?
+    })
 }
 
 struct Shorthand {
@@ -325,6 +466,42 @@ pub(super) fn run_shorthands() -> i32 {
     0
 }
 
+pub(super) fn run_hidden_pin(opts: &HiddenPinArgs) -> i32 {
+    if opts.position == Some(0) {
+        eprintln!("Error: Position must be >= 1");
+        return 2;
+    }
+
+    let args = AppArgs {
+        pin_action: Some(PinAction::Pin {
+            target: cli::parse_path_anchor(&opts.target),
+            position: opts.position,
+            save: opts.save,
+        }),
+        dry_run: opts.preview.dry_run,
+        ..Default::default()
+    };
+    commands::run(&args)
+}
+
+pub(super) fn run_hidden_enforce(edit: EditArgs) -> i32 {
+    run_or_report(
+        Ok(AppArgs {
+            enforce_order: true,
+            ..Default::default()
+        }),
+        edit,
+    )
+}
+
+pub(super) fn run_hidden_not_found(opts: HiddenNotFoundArgs) -> i32 {
+    let args = AppArgs {
+        not_found: Some(opts.name),
+        ..Default::default()
+    };
+    commands::run(&args)
+}
+
 fn current_session_pid() -> u32 {
     std::env::var("WHI_SESSION_PID")
         .ok()
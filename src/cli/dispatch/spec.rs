@@ -1,6 +1,11 @@
 use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
 
-use crate::cli::ColorWhen;
+use crate::cli::{CacheAction, ColorWhen, OutputFormat};
+use crate::io::time::TimeFormat;
+use crate::path::environment::Environment;
+use crate::path::health::Severity;
+use crate::path::pattern::{CaseMode, PatternKind, PatternOptions};
+use crate::path::source::PathSource;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -19,6 +24,44 @@ pub(crate) struct Cli {
     pub(crate) command: Option<Command>,
 }
 
+/// Flags shared by every `PATH` edit, flattened into each edit command
+#[derive(ClapArgs, Debug, Default, Clone, Copy)]
+#[command(about = None, long_about = None)]
+pub(crate) struct EditArgs {
+    /// Show what a `PATH` edit would change without applying it
+    #[arg(long = "dry-run")]
+    pub(crate) dry_run: bool,
+
+    /// Apply a `PATH` edit even if it displaces a pinned entry
+    #[arg(long = "force")]
+    pub(crate) force: bool,
+}
+
+/// `--dry-run` for edits that restore a recorded `PATH` or place a pin,
+/// which pins never hold back
+#[derive(ClapArgs, Debug, Default, Clone, Copy)]
+#[command(about = None, long_about = None)]
+pub(crate) struct PreviewArgs {
+    /// Show what a `PATH` edit would change without applying it
+    #[arg(long = "dry-run")]
+    pub(crate) dry_run: bool,
+}
+
+/// Where a read-only command takes `PATH` from instead of the environment
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct PathSourceArgs {
+    #[arg(long = "path")]
+    pub(crate) path_override: Option<String>,
+
+    /// Read `PATH` from `profile:NAME`, `saved:SHELL`, `snapshot:N`, `pid:PID` or `file:FILE`
+    #[arg(
+        long = "path-from",
+        value_name = "SOURCE",
+        conflicts_with = "path_override"
+    )]
+    pub(crate) path_from: Option<PathSource>,
+}
+
 #[derive(ClapArgs, Debug, Default)]
 pub(crate) struct QueryArgs {
     #[command(flatten)]
@@ -36,8 +79,19 @@ pub(crate) struct QueryArgs {
     #[command(flatten)]
     pub(crate) mode: QueryModeArgs,
 
-    #[arg(long = "path")]
-    pub(crate) path_override: Option<String>,
+    #[command(flatten)]
+    pub(crate) pattern: PatternArgs,
+
+    #[command(flatten)]
+    pub(crate) source: PathSourceArgs,
+
+    /// Resolve as cron, systemd, sudo or a login shell would, noting differences
+    #[arg(
+        long = "env",
+        value_name = "CONTEXT",
+        conflicts_with_all = ["path_override", "path_from"]
+    )]
+    pub(crate) env: Option<EnvChoice>,
 
     #[arg(long = "color")]
     pub(crate) color: Option<ColorChoice>,
@@ -65,6 +119,10 @@ pub(crate) struct QueryListingDetailsArgs {
 
     #[arg(long = "show-nonexec", alias = "nonexec")]
     pub(crate) show_nonexec: bool,
+
+    /// Show every symlink hop from each match to its final target
+    #[arg(long = "chain")]
+    pub(crate) chain: bool,
 }
 
 #[derive(ClapArgs, Debug, Default)]
@@ -77,6 +135,18 @@ pub(crate) struct QueryOutputArgs {
 
     #[arg(long = "silent")]
     pub(crate) silent: bool,
+
+    /// Output format for query results
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub(crate) format: Option<FormatChoice>,
+
+    /// Print each match with a template, e.g. '{index}\t{path}\t{mtime:%Y-%m-%d}'
+    #[arg(
+        long = "format-string",
+        value_name = "TEMPLATE",
+        conflicts_with = "format"
+    )]
+    pub(crate) format_string: Option<String>,
 }
 
 #[derive(ClapArgs, Debug, Default)]
@@ -86,12 +156,58 @@ pub(crate) struct QueryOutputDetailsArgs {
 
     #[arg(short = 'n', long = "no-index")]
     pub(crate) no_index: bool,
+
+    /// Run each executable match to show its version (see `[versions]` in config)
+    #[arg(long = "versions")]
+    pub(crate) versions: bool,
+
+    /// How `--stat` times are shown: local, iso8601, rfc3339, relative, epoch or a strftime format
+    #[arg(long = "time-format", value_name = "FORMAT", value_parser = TimeFormat::parse)]
+    pub(crate) time_format: Option<TimeFormat>,
 }
 
 #[derive(ClapArgs, Debug, Default)]
 pub(crate) struct QueryModeArgs {
     #[arg(short = 'x', long = "swap-fuzzy-exact")]
     pub(crate) swap_fuzzy: bool,
+
+    /// Scan `PATH` directories directly instead of using the executable index
+    #[arg(long = "no-cache")]
+    pub(crate) no_cache: bool,
+
+    /// Show what version-manager shims (rustup, pyenv, asdf, mise, nvm) dispatch to
+    #[arg(long = "resolve-shims")]
+    pub(crate) resolve_shims: bool,
+}
+
+/// How NAME queries and `prefer`/`delete` targets are matched
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct PatternArgs {
+    /// Treat the query as a regular expression, e.g. '^clang(-\d+)?$'
+    #[arg(long = "regex", conflicts_with = "glob")]
+    pub(crate) regex: bool,
+
+    /// Treat the query as a shell glob matching the whole name, e.g. 'python3.*'
+    #[arg(long = "glob")]
+    pub(crate) glob: bool,
+
+    /// Show how each fuzzy match was scored and ranked
+    #[arg(long = "explain-score")]
+    pub(crate) explain_score: bool,
+
+    #[command(flatten)]
+    pub(crate) case: CaseArgs,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct CaseArgs {
+    /// Match case-insensitively
+    #[arg(short = 'i', long = "ignore-case", conflicts_with = "case")]
+    pub(crate) ignore_case: bool,
+
+    /// Case sensitivity: smart (sensitive only if a regex or glob has uppercase), sensitive or insensitive
+    #[arg(long = "case", value_name = "CASE")]
+    pub(crate) case: Option<CaseChoice>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -110,7 +226,7 @@ pub(crate) enum Command {
     /// Swap two `PATH` entries
     Switch,
     /// Remove duplicate `PATH` entries
-    Clean,
+    Clean(CleanArgs),
     /// Delete `PATH` entries by index, path, or pattern
     Delete,
     /// Reset `PATH` to initial session state
@@ -119,6 +235,8 @@ pub(crate) enum Command {
     Undo(UndoArgs),
     /// Redo next `PATH` operation(s)
     Redo(UndoArgs),
+    /// List this session's `PATH` snapshots with their timestamps
+    History(HistoryArgs),
     /// Save current `PATH` as a named profile
     Save(SaveProfileArgs),
     /// Load a saved `PATH` profile
@@ -130,8 +248,26 @@ pub(crate) enum Command {
     RemoveProfile(RemoveProfileArgs),
     /// Add paths to `PATH` (prepends by default)
     Add,
+    /// Apply several `PATH` edits at once, as a single history entry
+    Batch,
+    /// Keep a `PATH` entry at its position through later edits
+    Pin,
+    /// Stop keeping a `PATH` entry at a fixed position
+    Unpin(UnpinArgs),
+    /// Reorder `PATH` to satisfy `~/.whi/order_rules`
+    Enforce(EnforceArgs),
     /// Show all whi shorthand commands
     Shorthands,
+    /// List executables shadowed by an earlier `PATH` entry
+    Shadows(ShadowsArgs),
+    /// Show which `PATH` entries provide each of several names
+    Matrix(MatrixArgs),
+    /// Rebuild or clear the executable index cache
+    Cache(CacheArgs),
+    /// Check `PATH` for problems and suggest fixes
+    Doctor(DoctorArgs),
+    /// Show what each `PATH` entry contributes when resolving NAME
+    Explain(ExplainArgs),
     // Hidden commands are the shell-integration protocol: the shell templates
     // invoke these __* subcommands and apply the emitted PATH value themselves.
     #[command(hide = true)]
@@ -141,13 +277,13 @@ pub(crate) enum Command {
     #[command(name = "__switch", hide = true)]
     HiddenSwap(HiddenSwapArgs),
     #[command(name = "__clean", hide = true)]
-    HiddenClean,
+    HiddenClean(CleanArgs),
     #[command(name = "__delete", hide = true)]
     HiddenDelete(HiddenDeleteArgs),
     #[command(name = "__prefer", hide = true)]
     HiddenPrefer(HiddenPreferArgs),
     #[command(name = "__reset", hide = true)]
-    HiddenReset,
+    HiddenReset(PreviewArgs),
     #[command(name = "__undo", hide = true)]
     HiddenUndo(HiddenUndoArgs),
     #[command(name = "__redo", hide = true)]
@@ -160,6 +296,21 @@ pub(crate) enum Command {
     HiddenLoadSavedPath(HiddenLoadSavedPathArgs),
     #[command(name = "__add", hide = true)]
     HiddenAdd(HiddenAddArgs),
+    #[command(name = "__batch", hide = true)]
+    HiddenBatch(HiddenBatchArgs),
+    #[command(name = "__pin", hide = true)]
+    HiddenPin(HiddenPinArgs),
+    #[command(name = "__enforce", hide = true)]
+    HiddenEnforce(EditArgs),
+    #[command(name = "__not_found", hide = true)]
+    HiddenNotFound(HiddenNotFoundArgs),
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct HistoryArgs {
+    /// How snapshot times are shown: local, iso8601, rfc3339, relative, epoch or a strftime format
+    #[arg(long = "time-format", value_name = "FORMAT", value_parser = TimeFormat::parse)]
+    pub(crate) time_format: Option<TimeFormat>,
 }
 
 #[derive(ClapArgs, Debug, Default)]
@@ -182,12 +333,123 @@ pub(crate) struct ApplyArgs {
     pub(crate) no_protect: bool,
 }
 
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct CleanArgs {
+    /// Also remove entries that resolve to the same directory (symlinks, merged /usr)
+    #[arg(long = "canonical")]
+    pub(crate) canonical: bool,
+
+    /// Strip trailing slashes and `.`/`..` segments before comparing
+    #[arg(long = "normalize")]
+    pub(crate) normalize: bool,
+
+    /// Also remove entries that do not exist on disk
+    #[arg(long = "missing")]
+    pub(crate) missing: bool,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
+}
+
 #[derive(ClapArgs, Debug, Default)]
 pub(crate) struct UndoArgs {
     #[arg(value_name = "COUNT", default_value = "1")]
     pub(crate) count: usize,
 }
 
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct ShadowsArgs {
+    /// Only show executables whose name fuzzy-matches PATTERN
+    #[arg(value_name = "PATTERN")]
+    pub(crate) pattern: Option<String>,
+
+    /// Only show executables with a copy in `PATH` entry INDEX
+    #[arg(long = "index", value_name = "INDEX")]
+    pub(crate) index: Option<usize>,
+
+    /// Also show names whose copies are all the same file (e.g. merged /usr)
+    #[arg(short = 'a', long = "all")]
+    pub(crate) all: bool,
+
+    #[command(flatten)]
+    pub(crate) source: PathSourceArgs,
+
+    #[arg(long = "color")]
+    pub(crate) color: Option<ColorChoice>,
+
+    /// Output format
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub(crate) format: Option<FormatChoice>,
+
+    /// Scan `PATH` directories directly instead of using the executable index
+    #[arg(long = "no-cache")]
+    pub(crate) no_cache: bool,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct MatrixArgs {
+    /// Names to resolve, e.g. a toolchain such as `cc c++ ld ar`
+    #[arg(value_name = "NAME", required = true)]
+    pub(crate) names: Vec<String>,
+
+    #[command(flatten)]
+    pub(crate) source: PathSourceArgs,
+
+    #[arg(long = "color")]
+    pub(crate) color: Option<ColorChoice>,
+
+    /// Output format
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub(crate) format: Option<FormatChoice>,
+
+    /// Scan `PATH` directories directly instead of using the executable index
+    #[arg(long = "no-cache")]
+    pub(crate) no_cache: bool,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct ExplainArgs {
+    #[arg(value_name = "NAME", required = true)]
+    pub(crate) name: String,
+
+    #[command(flatten)]
+    pub(crate) source: PathSourceArgs,
+
+    #[arg(long = "color")]
+    pub(crate) color: Option<ColorChoice>,
+
+    /// Output format
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub(crate) format: Option<FormatChoice>,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct DoctorArgs {
+    /// Exit non-zero when a finding has at least this severity
+    #[arg(long = "fail-on", value_name = "SEVERITY")]
+    pub(crate) fail_on: Option<SeverityChoice>,
+
+    #[command(flatten)]
+    pub(crate) source: PathSourceArgs,
+
+    #[arg(long = "color")]
+    pub(crate) color: Option<ColorChoice>,
+
+    /// Output format
+    #[arg(long = "format", value_name = "FORMAT")]
+    pub(crate) format: Option<FormatChoice>,
+
+    /// Scan `PATH` directories directly instead of using the executable index
+    #[arg(long = "no-cache")]
+    pub(crate) no_cache: bool,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct CacheArgs {
+    #[arg(value_name = "ACTION", required = true)]
+    pub(crate) action: CacheChoice,
+}
+
 #[derive(ClapArgs, Debug)]
 pub(crate) struct SaveProfileArgs {
     #[arg(value_name = "NAME", required = true)]
@@ -210,18 +472,33 @@ pub(crate) struct RemoveProfileArgs {
 pub(crate) struct HiddenUndoArgs {
     #[arg(value_name = "COUNT", default_value = "1")]
     pub(crate) count: usize,
+
+    #[command(flatten)]
+    pub(crate) preview: PreviewArgs,
 }
 
 #[derive(ClapArgs, Debug)]
 pub(crate) struct HiddenRedoArgs {
     #[arg(value_name = "COUNT", default_value = "1")]
     pub(crate) count: usize,
+
+    #[command(flatten)]
+    pub(crate) preview: PreviewArgs,
 }
 
 #[derive(ClapArgs, Debug)]
 pub(crate) struct HiddenLoadArgs {
     #[arg(value_name = "NAME", required = true)]
     pub(crate) name: String,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct HiddenNotFoundArgs {
+    #[arg(value_name = "NAME")]
+    pub(crate) name: String,
 }
 
 #[derive(ClapArgs, Debug)]
@@ -237,6 +514,9 @@ pub(crate) struct HiddenMoveArgs {
 
     #[arg(value_name = "TO")]
     pub(crate) to: usize,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
 }
 
 #[derive(ClapArgs, Debug)]
@@ -246,18 +526,33 @@ pub(crate) struct HiddenSwapArgs {
 
     #[arg(value_name = "SECOND")]
     pub(crate) second: usize,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
 }
 
 #[derive(ClapArgs, Debug)]
 pub(crate) struct HiddenDeleteArgs {
     #[arg(value_name = "TARGET", required = true)]
     pub(crate) targets: Vec<String>,
+
+    #[command(flatten)]
+    pub(crate) pattern: PatternArgs,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
 }
 
 #[derive(ClapArgs, Debug)]
 pub(crate) struct HiddenPreferArgs {
     #[arg(value_name = "ARGS", required = true)]
     pub(crate) tokens: Vec<String>,
+
+    #[command(flatten)]
+    pub(crate) pattern: PatternArgs,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
 }
 
 #[derive(ClapArgs, Debug)]
@@ -277,6 +572,82 @@ pub(crate) struct HiddenAddArgs {
     /// Paths to add to `PATH`
     #[arg(value_name = "PATH", required = true)]
     pub(crate) paths: Vec<String>,
+
+    #[command(flatten)]
+    pub(crate) placement: AddPlacementArgs,
+
+    /// Move paths that are already on `PATH` instead of skipping them
+    #[arg(long = "move-if-present")]
+    pub(crate) move_if_present: bool,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct HiddenBatchArgs {
+    /// Operations such as `move 5 2`, several per argument when separated by
+    /// `;`; read from stdin when none are given
+    #[arg(value_name = "OPERATION")]
+    pub(crate) operations: Vec<String>,
+
+    #[command(flatten)]
+    pub(crate) edit: EditArgs,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct HiddenPinArgs {
+    /// Entry to pin: an index, a path or a fuzzy pattern
+    #[arg(value_name = "TARGET")]
+    pub(crate) target: String,
+
+    /// Move the entry to 1-based position N and pin it there
+    #[arg(long = "position", value_name = "N")]
+    pub(crate) position: Option<usize>,
+
+    /// Also pin it in `~/.whi/config.toml`, for every session
+    #[arg(long = "save")]
+    pub(crate) save: bool,
+
+    #[command(flatten)]
+    pub(crate) preview: PreviewArgs,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct UnpinArgs {
+    /// Pinned entry: an index, a path or a fuzzy pattern
+    #[arg(value_name = "TARGET", required = true)]
+    pub(crate) target: String,
+
+    /// Also remove the pin from `~/.whi/config.toml`
+    #[arg(long = "save")]
+    pub(crate) save: bool,
+}
+
+#[derive(ClapArgs, Debug, Default)]
+pub(crate) struct EnforceArgs {
+    /// Only report entries that break the rules; exits 1 if any do
+    #[arg(long = "check")]
+    pub(crate) check: bool,
+}
+
+#[derive(ClapArgs, Debug)]
+pub(crate) struct AddPlacementArgs {
+    /// Append instead of prepending
+    #[arg(long = "append")]
+    pub(crate) append: bool,
+
+    /// Insert at 1-based position N
+    #[arg(long = "at", value_name = "N")]
+    pub(crate) at: Option<usize>,
+
+    /// Insert before ANCHOR: an index, a path or a fuzzy pattern
+    #[arg(long = "before", value_name = "ANCHOR")]
+    pub(crate) before: Option<String>,
+
+    /// Insert after ANCHOR: an index, a path or a fuzzy pattern
+    #[arg(long = "after", value_name = "ANCHOR")]
+    pub(crate) after: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -295,3 +666,107 @@ impl From<ColorChoice> for ColorWhen {
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum FormatChoice {
+    Human,
+    Json,
+    Ndjson,
+}
+
+impl From<FormatChoice> for OutputFormat {
+    fn from(value: FormatChoice) -> OutputFormat {
+        match value {
+            FormatChoice::Human => OutputFormat::Human,
+            FormatChoice::Json => OutputFormat::Json,
+            FormatChoice::Ndjson => OutputFormat::Ndjson,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum EnvChoice {
+    Cron,
+    Systemd,
+    Sudo,
+    Login,
+}
+
+impl From<EnvChoice> for Environment {
+    fn from(value: EnvChoice) -> Environment {
+        match value {
+            EnvChoice::Cron => Environment::Cron,
+            EnvChoice::Systemd => Environment::Systemd,
+            EnvChoice::Sudo => Environment::Sudo,
+            EnvChoice::Login => Environment::Login,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum CaseChoice {
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+impl From<CaseChoice> for CaseMode {
+    fn from(value: CaseChoice) -> CaseMode {
+        match value {
+            CaseChoice::Smart => CaseMode::Smart,
+            CaseChoice::Sensitive => CaseMode::Sensitive,
+            CaseChoice::Insensitive => CaseMode::Insensitive,
+        }
+    }
+}
+
+impl From<&PatternArgs> for PatternOptions {
+    fn from(value: &PatternArgs) -> PatternOptions {
+        let kind = if value.regex {
+            PatternKind::Regex
+        } else if value.glob {
+            PatternKind::Glob
+        } else {
+            PatternKind::Fuzzy
+        };
+        let case = if value.case.ignore_case {
+            CaseMode::Insensitive
+        } else {
+            value.case.case.map_or(CaseMode::Smart, Into::into)
+        };
+
+        PatternOptions { kind, case }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum CacheChoice {
+    Rebuild,
+    Clear,
+}
+
+impl From<CacheChoice> for CacheAction {
+    fn from(value: CacheChoice) -> CacheAction {
+        match value {
+            CacheChoice::Rebuild => CacheAction::Rebuild,
+            CacheChoice::Clear => CacheAction::Clear,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum SeverityChoice {
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<SeverityChoice> for Severity {
+    fn from(value: SeverityChoice) -> Severity {
+        match value {
+            SeverityChoice::Info => Severity::Info,
+            SeverityChoice::Warning => Severity::Warning,
+            SeverityChoice::Error => Severity::Error,
+        }
+    }
+}
@@ -2,6 +2,8 @@ pub mod args;
 pub mod dispatch;
 
 pub use args::{
-    ApplyTarget, Args, ColorWhen, DeleteTarget, HistoryAction, PathEdit, PreferTarget,
-    parse_add_arguments, parse_delete_arguments, parse_prefer_arguments,
+    AddPosition, ApplyTarget, Args, BatchStep, CacheAction, ColorWhen, DeleteTarget, HistoryAction,
+    OutputFormat, PathAnchor, PathEdit, PinAction, PreferTarget, parse_add_arguments,
+    parse_add_position, parse_delete_arguments, parse_path_anchor, parse_prefer_arguments,
+    parse_prefer_pattern_arguments, split_batch_operations, split_operation_words,
 };
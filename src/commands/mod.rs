@@ -1,4 +1,4 @@
 pub mod runner;
 pub mod support;
 
-pub use runner::run;
+pub use runner::{preview, run};
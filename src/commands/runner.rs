@@ -1,18 +1,31 @@
 use std::env;
-use std::io::{self, BufWriter, StdoutLock};
+use std::io::{self, BufWriter};
 use std::path::PathBuf;
 
-use crate::cli::args::Args;
+use crate::cli::args::{Args, PathEdit};
+use crate::commands::support::path_support::{active_pins, auto_order, keep_pins};
 use crate::config;
+use crate::path::pins::{pin_conflict, pin_for};
+use crate::path::searcher::PathSearcher;
 
+mod batch;
+mod cache;
+mod doctor;
+mod environment;
+mod explain;
+mod matrix;
+mod order;
 mod path_ops;
+mod pins;
+mod preview;
 mod query;
 mod session;
+mod shadows;
 
 fn handle_path_result(
     result: Result<String, String>,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl io::Write,
 ) -> i32 {
     match result {
         Ok(new_path) => {
@@ -34,6 +47,106 @@ fn get_current_exe_dir() -> Option<PathBuf> {
         .and_then(|exe_path| exe_path.parent().map(std::path::Path::to_path_buf))
 }
 
+/// Run the `PATH` edit `args` asks for, if any, writing the new `PATH` to `out`
+fn run_path_edit(searcher: &PathSearcher, args: &Args, out: &mut impl io::Write) -> Option<i32> {
+    let code = if !args.batch.is_empty() {
+        batch::handle_batch(searcher, args, out)
+    } else if args.clean {
+        path_ops::handle_clean(searcher, args, out)
+    } else if !args.delete_targets.is_empty() {
+        path_ops::handle_delete(searcher, &args.delete_targets, args, out)
+    } else if !args.add_paths.is_empty() {
+        path_ops::handle_add(searcher, args, out)
+    } else if let Some(path_edit) = &args.path_edit {
+        path_ops::handle_move_or_swap(searcher, path_edit, args, out)
+    } else if let Some(target) = &args.prefer_target {
+        path_ops::handle_prefer(searcher, target, args, out)
+    } else if args.enforce_order {
+        order::handle_enforce(searcher, args, out)
+    } else {
+        return None;
+    };
+    Some(code)
+}
+
+/// Keep pinned entries in place across the edit `args` made from `searcher`
+/// to `new_path`; move and switch may not pick up a pinned entry themselves
+fn enforce_pins(searcher: &PathSearcher, new_path: &str, args: &Args) -> Result<String, String> {
+    if args.force {
+        return Ok(new_path.to_string());
+    }
+
+    let pins = active_pins()?;
+    let current_path = searcher.to_path_string();
+    let targets = match args.path_edit {
+        Some(PathEdit::Move { from, .. }) => vec![from],
+        Some(PathEdit::Swap { first, second }) => vec![first, second],
+        None => Vec::new(),
+    };
+    if new_path != current_path
+        && let Some(pin) = targets
+            .iter()
+            .filter_map(|index| searcher.dirs().get(index.wrapping_sub(1)))
+            .find_map(|dir| pin_for(&pins, dir))
+    {
+        return Err(pin_conflict(pin, "would be moved"));
+    }
+
+    let kept = keep_pins(&current_path, new_path, &pins)?;
+    if kept != new_path && !args.quiet && !args.silent {
+        eprintln!("Note: Kept pinned entries at their positions");
+    }
+    Ok(kept)
+}
+
+/// Apply order rules and pins to an edit's result, then preview it or
+/// record and emit it
+fn finish_path_edit(
+    searcher: &PathSearcher,
+    new_path: &str,
+    args: &Args,
+    out: &mut BufWriter<io::StdoutLock<'_>>,
+) -> i32 {
+    let mut reordered = false;
+    let result = auto_order(new_path).and_then(|ordered| {
+        reordered = ordered != new_path;
+        // Batch checks pins after each step
+        if args.batch.is_empty() || reordered {
+            enforce_pins(searcher, &ordered, args)
+        } else {
+            Ok(ordered)
+        }
+    });
+    let new_path = match result {
+        Ok(path) => path,
+        Err(e) => {
+            if !args.silent {
+                eprintln!("Error: {e}");
+            }
+            return 2;
+        }
+    };
+    // Pins win over order rules, so the rules are judged on the final PATH
+    if args.enforce_order || reordered {
+        order::report_outcome(searcher, &new_path, args);
+    }
+
+    if args.dry_run {
+        return preview::handle_preview(&new_path, args, out);
+    }
+    query::write_snapshot_safe(&new_path, args);
+    query::output_path(out, &new_path)
+}
+
+/// Show what replacing the current `PATH` with `new_path` would change,
+/// for `--dry-run` of edits computed outside `run`
+#[must_use]
+pub fn preview(new_path: &str, args: &Args) -> i32 {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    preview::handle_preview(new_path, args, &mut out)
+}
+
 #[must_use]
 pub fn run(args: &Args) -> i32 {
     if let Err(e) = config::runtime::ensure_config_exists() {
@@ -64,13 +177,14 @@ pub fn run(args: &Args) -> i32 {
     }
 
     if args.reset {
-        return session::handle_reset();
+        return session::handle_reset(args);
     }
 
     if let Some(history_action) = &args.history_action {
         return match history_action {
-            crate::cli::args::HistoryAction::Undo(count) => session::handle_undo(*count),
-            crate::cli::args::HistoryAction::Redo(count) => session::handle_redo(*count),
+            crate::cli::args::HistoryAction::Undo(count) => session::handle_undo(*count, args),
+            crate::cli::args::HistoryAction::Redo(count) => session::handle_redo(*count, args),
+            crate::cli::args::HistoryAction::List => session::handle_history(args),
         };
     }
 
@@ -78,29 +192,66 @@ pub fn run(args: &Args) -> i32 {
         return session::handle_diff(args.diff_full);
     }
 
+    if let Some(name) = &args.not_found {
+        return session::handle_not_found(name);
+    }
+
+    if let Some(context) = args.env_context {
+        let stdout = io::stdout();
+        let mut out = BufWriter::new(stdout.lock());
+        return environment::handle_environment(context, &config, args, &mut out);
+    }
+
     let path_var = match &args.path_override {
         Some(p) => p.clone(),
         None => env::var("PATH").unwrap_or_default(),
     };
 
-    let searcher = crate::path::searcher::PathSearcher::new(&path_var);
     let stdout = io::stdout();
     let mut out = BufWriter::new(stdout.lock());
 
-    if args.clean {
-        return path_ops::handle_clean(&searcher, args, &mut out);
+    // Doctor reports PATH problems itself instead of PathSearcher's stderr warnings
+    if args.doctor {
+        return doctor::handle_doctor(&path_var, args, &mut out);
     }
 
-    if !args.delete_targets.is_empty() {
-        return path_ops::handle_delete(&searcher, &args.delete_targets, args, &mut out);
+    let searcher = PathSearcher::new(&path_var);
+
+    if let Some(action) = args.cache_action {
+        return cache::handle_cache(&searcher, action);
+    }
+
+    if let Some(name) = &args.explain {
+        return explain::handle_explain(&searcher, name, &path_var, args, &mut out);
     }
 
-    if let Some(path_edit) = &args.path_edit {
-        return path_ops::handle_move_or_swap(&searcher, path_edit, args, &mut out);
+    if args.shadows {
+        return shadows::handle_shadows(&searcher, args, &mut out);
     }
 
-    if let Some(ref target) = args.prefer_target {
-        return path_ops::handle_prefer(&searcher, target, args, &mut out);
+    if !args.matrix_names.is_empty() {
+        return matrix::handle_matrix(&searcher, args, &mut out);
+    }
+
+    if args.order_check {
+        return order::handle_check(&searcher, args, &mut out);
+    }
+
+    if let Some(action) = &args.pin_action {
+        return pins::handle_pin_action(&searcher, action, args, &mut out);
+    }
+
+    let edit_args = Args {
+        defer_snapshot: true,
+        ..args.clone()
+    };
+    let mut new_path = Vec::new();
+    if let Some(code) = run_path_edit(&searcher, &edit_args, &mut new_path) {
+        if code != 0 {
+            return code;
+        }
+        let new_path = String::from_utf8_lossy(&new_path);
+        return finish_path_edit(&searcher, new_path.trim_end_matches('\n'), args, &mut out);
     }
 
     query::run_query(&searcher, args, &config, &mut out)
@@ -114,6 +265,7 @@ mod atty {
         let fd = match stream {
             Stream::Stdout => std::io::stdout().as_raw_fd(),
             Stream::Stdin => std::io::stdin().as_raw_fd(),
+            Stream::Stderr => std::io::stderr().as_raw_fd(),
         };
 
         platform::is_tty(fd)
@@ -123,5 +275,6 @@ mod atty {
     pub enum Stream {
         Stdout,
         Stdin,
+        Stderr,
     }
 }
@@ -0,0 +1,54 @@
+use std::io::Write;
+
+use crate::cli::args::Args;
+use crate::commands::support::path_support::{output_path, write_snapshot_safe};
+use crate::path::searcher::PathSearcher;
+
+/// Apply every step to one in-memory `PATH`, stopping at the first failure
+/// so the shell's `PATH` and history only ever see the finished result; pins
+/// are checked after each step
+pub(super) fn handle_batch(searcher: &PathSearcher, args: &Args, out: &mut impl Write) -> i32 {
+    let mut current = searcher.clone();
+
+    for (number, step) in args.batch.iter().enumerate() {
+        let mut step_args = step.args.clone();
+        step_args.defer_snapshot = true;
+        step_args.quiet = args.quiet;
+        step_args.silent = args.silent;
+        step_args.force |= args.force;
+
+        let mut step_out = Vec::new();
+        let code = match super::run_path_edit(&current, &step_args, &mut step_out) {
+            Some(0) => {
+                let new_path = String::from_utf8_lossy(&step_out);
+                match super::enforce_pins(&current, new_path.trim_end_matches('\n'), &step_args) {
+                    Ok(new_path) => {
+                        current = PathSearcher::new(&new_path);
+                        0
+                    }
+                    Err(e) => {
+                        if !args.silent {
+                            eprintln!("Error: {e}");
+                        }
+                        2
+                    }
+                }
+            }
+            code => code.unwrap_or(2),
+        };
+        if code != 0 {
+            if !args.silent {
+                eprintln!(
+                    "Error: Batch stopped at step {} ({}); PATH left unchanged",
+                    number + 1,
+                    step.operation
+                );
+            }
+            return code;
+        }
+    }
+
+    let new_path = current.to_path_string();
+    write_snapshot_safe(&new_path, args);
+    output_path(out, &new_path)
+}
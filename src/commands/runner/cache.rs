@@ -0,0 +1,35 @@
+use crate::cli::args::CacheAction;
+use crate::path::environment::clear_login_path;
+use crate::path::searcher::PathSearcher;
+use crate::search::index::ExecutableIndex;
+use crate::search::version::VersionProbe;
+
+pub(super) fn handle_cache(searcher: &PathSearcher, action: CacheAction) -> i32 {
+    match action {
+        CacheAction::Rebuild => {
+            let mut index = ExecutableIndex::load();
+            let (dirs, entries) = index.rebuild(searcher.dirs());
+
+            if let Err(e) = index.save() {
+                eprintln!("Error: {e}");
+                return 2;
+            }
+
+            println!("Rebuilt executable index ({dirs} directories, {entries} entries)");
+            0
+        }
+        CacheAction::Clear => match ExecutableIndex::clear()
+            .and_then(|()| VersionProbe::clear())
+            .and_then(|()| clear_login_path())
+        {
+            Ok(()) => {
+                println!("Cleared executable index, version cache and login PATH");
+                0
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                2
+            }
+        },
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::io::{BufWriter, StdoutLock, Write};
+use std::path::Path;
+
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::should_use_color;
+use crate::config::{protected_paths, shell_paths};
+use crate::io::json::JsonValue;
+use crate::io::output::StructuredWriter;
+use crate::path::health::{Finding, Severity, check_path_entries};
+use crate::shell::detect::{detect_current_shell, get_saved_path_file};
+
+use super::query::{open_index, save_index};
+
+pub(super) fn handle_doctor(
+    path_var: &str,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let mut index = open_index(args);
+    let mut findings = check_path_entries(path_var, &mut index);
+    save_index(&mut index, args);
+
+    findings.extend(check_protected_paths(path_var));
+
+    // The saved PATH only describes the live shell, not an arbitrary --path value
+    if args.path_override.is_none() {
+        findings.extend(check_saved_path(path_var));
+    }
+
+    let written = match args.format {
+        OutputFormat::Human => write_human(&findings, args, out),
+        OutputFormat::Json => write_structured(&findings, false, out),
+        OutputFormat::Ndjson => write_structured(&findings, true, out),
+    };
+    if let Err(err) = written {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+
+    let threshold = args.doctor_fail_on.unwrap_or(Severity::Warning);
+    i32::from(findings.iter().any(|f| f.severity >= threshold))
+}
+
+fn normalized_entries(path_var: &str) -> HashSet<&str> {
+    path_var
+        .split(':')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.trim_end_matches('/'))
+        .collect()
+}
+
+fn check_protected_paths(path_var: &str) -> Vec<Finding> {
+    let Ok(protected) = protected_paths::load_protected_paths() else {
+        return vec![
+            Finding::new(
+                Severity::Warning,
+                "protected-paths-unreadable",
+                "Could not read ~/.whi/protected_paths",
+            )
+            .fix("whi apply"),
+        ];
+    };
+
+    let current = normalized_entries(path_var);
+
+    protected
+        .iter()
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            let path_str = path.to_string_lossy();
+            if current.contains(path_str.trim_end_matches('/')) {
+                return None;
+            }
+
+            Some(
+                Finding::new(
+                    Severity::Warning,
+                    "protected-path-missing",
+                    "Protected path exists but is not on PATH",
+                )
+                .entry(&path_str)
+                .fix(format!("whi add --append {path_str}")),
+            )
+        })
+        .collect()
+}
+
+fn check_saved_path(path_var: &str) -> Vec<Finding> {
+    let Ok(shell) = detect_current_shell() else {
+        return Vec::new();
+    };
+
+    let saved_file_exists = get_saved_path_file(&shell).is_ok_and(|file| file.exists());
+    if !saved_file_exists {
+        return Vec::new();
+    }
+
+    let saved = match shell_paths::load_saved_path_for_shell(&shell) {
+        Ok(saved) => saved,
+        Err(e) => {
+            return vec![
+                Finding::new(
+                    Severity::Error,
+                    "saved-path-unreadable",
+                    format!("Saved PATH for {} cannot be loaded: {e}", shell.as_str()),
+                )
+                .fix(format!("whi apply {}", shell.as_str())),
+            ];
+        }
+    };
+
+    let mut findings: Vec<Finding> = saved
+        .split(':')
+        .filter(|entry| !entry.is_empty() && !Path::new(entry).is_dir())
+        .map(|entry| {
+            Finding::new(
+                Severity::Warning,
+                "saved-path-stale",
+                format!(
+                    "Saved PATH for {} references a missing directory",
+                    shell.as_str()
+                ),
+            )
+            .entry(entry)
+            .fix(format!("whi apply {}", shell.as_str()))
+        })
+        .collect();
+
+    let current: Vec<&str> = path_var.split(':').filter(|s| !s.is_empty()).collect();
+    let saved_entries: Vec<&str> = saved.split(':').filter(|s| !s.is_empty()).collect();
+    if findings.is_empty() && current != saved_entries {
+        findings.push(
+            Finding::new(
+                Severity::Info,
+                "saved-path-differs",
+                format!(
+                    "Current PATH differs from the saved PATH for {}",
+                    shell.as_str()
+                ),
+            )
+            .fix(format!("whi apply {}", shell.as_str())),
+        );
+    }
+
+    findings
+}
+
+fn write_human(
+    findings: &[Finding],
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> std::io::Result<()> {
+    if findings.is_empty() {
+        writeln!(out, "No problems found")?;
+        return out.flush();
+    }
+
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let reset = if use_color { "\x1b[0m" } else { "" };
+
+    for finding in findings {
+        let color = match (use_color, finding.severity) {
+            (false, _) => "",
+            (true, Severity::Error) => "\x1b[31m",
+            (true, Severity::Warning) => "\x1b[33m",
+            (true, Severity::Info) => "\x1b[36m",
+        };
+
+        let location = match (finding.index, &finding.entry) {
+            (Some(index), Some(entry)) => format!("[{index}] {entry}: "),
+            (None, Some(entry)) => format!("{entry}: "),
+            _ => String::new(),
+        };
+
+        writeln!(
+            out,
+            "{color}{:<8}{reset} {:<24} {location}{}",
+            finding.severity.as_str(),
+            finding.code,
+            finding.message
+        )?;
+
+        if let Some(fix) = &finding.fix {
+            writeln!(out, "{:<8} {:<24} fix: {fix}", "", "")?;
+        }
+    }
+
+    let count = |severity| findings.iter().filter(|f| f.severity == severity).count();
+    writeln!(
+        out,
+        "\n{} finding(s): {} error(s), {} warning(s), {} info",
+        findings.len(),
+        count(Severity::Error),
+        count(Severity::Warning),
+        count(Severity::Info)
+    )?;
+
+    out.flush()
+}
+
+fn write_structured(
+    findings: &[Finding],
+    newline_delimited: bool,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> std::io::Result<()> {
+    let mut writer = StructuredWriter::new(newline_delimited);
+
+    for finding in findings {
+        let record = JsonValue::object([
+            ("type", JsonValue::from("finding")),
+            ("severity", JsonValue::from(finding.severity.as_str())),
+            ("code", JsonValue::from(finding.code)),
+            ("index", JsonValue::from(finding.index)),
+            ("entry", JsonValue::from(finding.entry.clone())),
+            ("message", JsonValue::from(finding.message.as_str())),
+            ("fix", JsonValue::from(finding.fix.clone())),
+        ]);
+        writer.write_record(out, record)?;
+    }
+
+    writer.finish(out)
+}
@@ -0,0 +1,153 @@
+use std::env;
+use std::io::{BufWriter, StdoutLock, Write};
+
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::should_use_color;
+use crate::config::runtime::Config;
+use crate::io::json::JsonValue;
+use crate::io::output::StructuredWriter;
+use crate::path::environment::{EnvResolution, Environment, compare_resolution};
+
+pub(super) fn handle_environment(
+    environment: Environment,
+    config: &Config,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let current_path = env::var("PATH").unwrap_or_default();
+    let env_path = match environment.path(&config.environments, &current_path) {
+        Ok(path) => path,
+        Err(e) => {
+            if !args.silent {
+                eprintln!("Error: {e}");
+            }
+            return 2;
+        }
+    };
+
+    let resolutions: Vec<EnvResolution> = args
+        .names
+        .iter()
+        .map(|name| compare_resolution(name, &env_path, &current_path))
+        .collect();
+
+    let code = if args.silent {
+        0
+    } else {
+        match args.format {
+            OutputFormat::Human => write_human(environment, &env_path, &resolutions, args, out),
+            OutputFormat::Json => {
+                write_structured(environment, &env_path, &resolutions, false, out)
+            }
+            OutputFormat::Ndjson => {
+                write_structured(environment, &env_path, &resolutions, true, out)
+            }
+        }
+    };
+
+    if code == 0 && resolutions.iter().any(|r| r.path.is_none()) {
+        1
+    } else {
+        code
+    }
+}
+
+fn write_human(
+    environment: Environment,
+    env_path: &str,
+    resolutions: &[EnvResolution],
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let (yellow, gray, reset) = if use_color {
+        ("\x1b[33m", "\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+
+    if !args.quiet {
+        writeln!(
+            out,
+            "{gray}{} PATH: {env_path}{reset}",
+            environment.as_str()
+        )
+        .ok();
+    }
+
+    let width = resolutions
+        .iter()
+        .map(|r| r.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    for resolution in resolutions {
+        let found = resolution
+            .path
+            .as_ref()
+            .map_or_else(|| "not found".to_string(), |p| p.display().to_string());
+        write!(out, "{:<width$}  {found}", resolution.name).ok();
+
+        if resolution.differs() {
+            let current = resolution
+                .current
+                .as_ref()
+                .map_or_else(|| "not found".to_string(), |p| p.display().to_string());
+            write!(out, "  {yellow}(current shell: {current}){reset}").ok();
+        }
+        writeln!(out).ok();
+    }
+
+    if let Err(err) = out.flush() {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+    0
+}
+
+fn write_structured(
+    environment: Environment,
+    env_path: &str,
+    resolutions: &[EnvResolution],
+    newline_delimited: bool,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let mut writer = StructuredWriter::new(newline_delimited);
+
+    let header = JsonValue::object([
+        ("type", JsonValue::from("environment")),
+        ("env", JsonValue::from(environment.as_str())),
+        ("path", JsonValue::from(env_path)),
+    ]);
+    writer.write_record(out, header).ok();
+
+    for resolution in resolutions {
+        let record = JsonValue::object([
+            ("type", JsonValue::from("env_resolution")),
+            ("env", JsonValue::from(environment.as_str())),
+            ("name", JsonValue::from(resolution.name.as_str())),
+            (
+                "path",
+                resolution
+                    .path
+                    .as_deref()
+                    .map_or(JsonValue::Null, JsonValue::path),
+            ),
+            (
+                "current",
+                resolution
+                    .current
+                    .as_deref()
+                    .map_or(JsonValue::Null, JsonValue::path),
+            ),
+            ("differs", JsonValue::from(resolution.differs())),
+        ]);
+        writer.write_record(out, record).ok();
+    }
+
+    if let Err(err) = writer.finish(out) {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+    0
+}
@@ -0,0 +1,181 @@
+use std::io::{BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::{history_for_current_scope, should_use_color};
+use crate::io::json::JsonValue;
+use crate::io::output::StructuredWriter;
+use crate::path::guard::PathGuard;
+use crate::path::searcher::PathSearcher;
+use crate::search::explain::{CandidateStatus, ExplainStep, explain_name};
+
+/// An explain step plus what `PathGuard` has to say about its directory
+struct ExplainRow {
+    step: ExplainStep,
+    protected_binaries: Vec<String>,
+    reappended: bool,
+}
+
+pub(super) fn handle_explain(
+    searcher: &PathSearcher,
+    name: &str,
+    path_var: &str,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    if name.contains('/') {
+        if !args.silent {
+            eprintln!("Error: explain expects a command name, not a path: {name}");
+        }
+        return 2;
+    }
+
+    let guard = PathGuard::default();
+    let current_tail = guarded_tail(&guard, path_var);
+    let initial_tail = initial_path(args).map(|initial| guarded_tail(&guard, &initial));
+
+    let rows: Vec<ExplainRow> = explain_name(searcher.dirs(), name)
+        .into_iter()
+        .map(|step| {
+            let protected_binaries: Vec<String> = guard
+                .binaries_protected_by(path_var, &step.dir)
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            let reappended = !protected_binaries.is_empty()
+                && current_tail.contains(&step.dir)
+                && initial_tail
+                    .as_ref()
+                    .is_some_and(|tail| !tail.contains(&step.dir));
+
+            ExplainRow {
+                step,
+                protected_binaries,
+                reappended,
+            }
+        })
+        .collect();
+
+    let result = match args.format {
+        OutputFormat::Human => write_human(name, &rows, args, out),
+        OutputFormat::Json => write_structured(name, &rows, false, out),
+        OutputFormat::Ndjson => write_structured(name, &rows, true, out),
+    };
+    if let Err(err) = result {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+
+    i32::from(!rows.iter().any(|row| row.step.winner))
+}
+
+/// The session's initial `PATH`, if a history exists and `PATH` was not overridden
+fn initial_path(args: &Args) -> Option<String> {
+    if args.path_override.is_some() {
+        return None;
+    }
+    history_for_current_scope().ok()?.initial_snapshot().ok()?
+}
+
+/// The trailing run of `PATH` entries that hold protected binaries
+///
+/// `PathGuard` appends the directories it restores to the end of `PATH`, so an
+/// entry that sits in this run now but did not in the session's initial `PATH`
+/// was most likely put back by the guard.
+fn guarded_tail(guard: &PathGuard, path_var: &str) -> Vec<PathBuf> {
+    path_var
+        .split(':')
+        .filter(|entry| !entry.is_empty())
+        .rev()
+        .map(PathBuf::from)
+        .take_while(|dir| !guard.binaries_protected_by(path_var, dir).is_empty())
+        .collect()
+}
+
+fn write_human(
+    name: &str,
+    rows: &[ExplainRow],
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> std::io::Result<()> {
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let (green, yellow, gray, reset) = if use_color {
+        ("\x1b[1;32m", "\x1b[33m", "\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "", "", "")
+    };
+
+    let width = rows
+        .iter()
+        .map(|row| row.step.dir.as_os_str().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut winner_index = None;
+    for row in rows {
+        let step = &row.step;
+        let (color, status) = match step.status {
+            CandidateStatus::Executable if step.winner => {
+                winner_index = Some(step.path_index);
+                (green, format!("winner: {}", step.candidate.display()))
+            }
+            CandidateStatus::Executable => {
+                (yellow, format!("shadowed: {}", step.candidate.display()))
+            }
+            CandidateStatus::NoSuchFile => (gray, step.status.describe().to_string()),
+            _ => (yellow, step.status.describe().to_string()),
+        };
+
+        writeln!(
+            out,
+            "{:>4} {:<width$}  {color}{status}{reset}",
+            format!("[{}]", step.path_index),
+            step.dir.display()
+        )?;
+
+        if row.reappended {
+            writeln!(
+                out,
+                "     {yellow}re-appended by PathGuard (provides {}){reset}",
+                row.protected_binaries.join(", ")
+            )?;
+        }
+    }
+
+    match winner_index {
+        Some(index) => writeln!(out, "{name}: resolved by PATH entry [{index}]")?,
+        None => writeln!(out, "{name}: not found in any PATH entry")?,
+    }
+
+    out.flush()
+}
+
+fn write_structured(
+    name: &str,
+    rows: &[ExplainRow],
+    newline_delimited: bool,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> std::io::Result<()> {
+    let mut writer = StructuredWriter::new(newline_delimited);
+
+    for row in rows {
+        let step = &row.step;
+        let record = JsonValue::object([
+            ("type", JsonValue::from("explain")),
+            ("name", JsonValue::from(name)),
+            ("path_index", JsonValue::from(step.path_index)),
+            ("dir", JsonValue::path(&step.dir)),
+            ("path", JsonValue::path(&step.candidate)),
+            ("status", JsonValue::from(step.status.code())),
+            ("winner", JsonValue::from(step.winner)),
+            (
+                "protected_binaries",
+                JsonValue::from(row.protected_binaries.clone()),
+            ),
+            ("reappended", JsonValue::from(row.reappended)),
+        ]);
+        writer.write_record(out, record)?;
+    }
+
+    writer.finish(out)
+}
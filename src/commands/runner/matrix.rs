@@ -0,0 +1,219 @@
+use std::env;
+use std::io::{BufWriter, StdoutLock, Write};
+use std::os::unix::io::AsRawFd;
+
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::should_use_color;
+use crate::io::json::JsonValue;
+use crate::io::output::StructuredWriter;
+use crate::path::searcher::PathSearcher;
+use crate::platform;
+use crate::search::matrix::{Matrix, MatrixRow, PrefixGroup, build_matrix};
+use crate::search::shadows::ShadowEntry;
+
+use super::query::{open_index, save_index};
+
+/// Used when neither `COLUMNS` nor the terminal report a width
+const DEFAULT_WIDTH: usize = 80;
+
+pub(super) fn handle_matrix(
+    searcher: &PathSearcher,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let mut index = open_index(args);
+    let matrix = build_matrix(&args.matrix_names, searcher.dirs(), &mut index);
+    save_index(&mut index, args);
+
+    let prefixes = matrix.prefixes();
+    let code = match args.format {
+        OutputFormat::Human => write_human(&matrix, searcher, args, out),
+        OutputFormat::Json => write_structured(&matrix, &prefixes, false, out),
+        OutputFormat::Ndjson => write_structured(&matrix, &prefixes, true, out),
+    };
+
+    if args.format == OutputFormat::Human && prefixes.len() > 1 {
+        eprintln!(
+            "Warning: toolchain is split across {} prefixes:",
+            prefixes.len()
+        );
+        for group in &prefixes {
+            eprintln!("  {}: {}", group.prefix.display(), group.names.join(", "));
+        }
+    }
+
+    let all_found = matrix
+        .rows
+        .iter()
+        .all(|row| !row.is_corrected() && row.winner().is_some());
+    if code == 0 && !all_found { 1 } else { code }
+}
+
+/// `COLUMNS` if set, else the width of the terminal on stdout
+fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(|| platform::terminal_width(std::io::stdout().as_raw_fd()))
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+fn write_human(
+    matrix: &Matrix,
+    searcher: &PathSearcher,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let (green, gray, reset) = if use_color {
+        ("\x1b[1;32m", "\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+
+    for row in matrix.rows.iter().filter(|row| row.is_corrected()) {
+        eprintln!(
+            "Note: '{}' not found, showing '{}'",
+            row.requested, row.name
+        );
+    }
+
+    let label_width = matrix
+        .rows
+        .iter()
+        .map(|row| row.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let cell_width = matrix
+        .columns
+        .iter()
+        .map(|idx| format!("[{idx}]").len())
+        .max()
+        .unwrap_or(3)
+        + 1;
+    // Columns that do not fit are wrapped into further blocks, like `ls`
+    let per_block = (terminal_width().saturating_sub(label_width) / cell_width).max(1);
+
+    let mut blocks = matrix.columns.chunks(per_block).peekable();
+    if blocks.peek().is_none() {
+        for row in &matrix.rows {
+            writeln!(out, "{:<label_width$}  {gray}not found{reset}", row.name).ok();
+        }
+    }
+    while let Some(block) = blocks.next() {
+        write!(out, "{:label_width$}", "").ok();
+        for idx in block {
+            write!(out, "{:>cell_width$}", format!("[{idx}]")).ok();
+        }
+        writeln!(out).ok();
+
+        for row in &matrix.rows {
+            write!(out, "{:<label_width$}", row.name).ok();
+            for &idx in block {
+                let cell = match row.entry_at(idx) {
+                    Some(entry) if row.winner() == Some(entry) => format!("{green}●{reset}"),
+                    Some(entry) if entry.same_file => format!("{gray}={reset}"),
+                    Some(_) => format!("{gray}○{reset}"),
+                    None => format!("{gray}·{reset}"),
+                };
+                write!(out, "{:>pad$}{cell}", "", pad = cell_width - 1).ok();
+            }
+            writeln!(out).ok();
+        }
+        if blocks.peek().is_some() {
+            writeln!(out).ok();
+        }
+    }
+
+    if !matrix.columns.is_empty() {
+        writeln!(out).ok();
+    }
+    for &idx in &matrix.columns {
+        writeln!(
+            out,
+            "{:>5} {}",
+            format!("[{idx}]"),
+            searcher.dirs()[idx - 1].display()
+        )
+        .ok();
+    }
+
+    writeln!(
+        out,
+        "{gray}● winner  ○ shadowed  = same file as winner  · missing{reset}"
+    )
+    .ok();
+    out.flush().ok();
+    0
+}
+
+fn write_structured(
+    matrix: &Matrix,
+    prefixes: &[PrefixGroup],
+    newline_delimited: bool,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let mut writer = StructuredWriter::new(newline_delimited);
+
+    for row in &matrix.rows {
+        writer.write_record(out, row_to_json(row)).ok();
+    }
+
+    if prefixes.len() > 1 {
+        let record = JsonValue::object([
+            ("type", JsonValue::from("split_toolchain")),
+            (
+                "prefixes",
+                JsonValue::Array(prefixes.iter().map(prefix_to_json).collect()),
+            ),
+        ]);
+        writer.write_record(out, record).ok();
+    }
+
+    if let Err(err) = writer.finish(out) {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+    0
+}
+
+fn row_to_json(row: &MatrixRow) -> JsonValue {
+    JsonValue::object([
+        ("type", JsonValue::from("matrix_row")),
+        ("name", JsonValue::from(row.name.as_str())),
+        ("requested", JsonValue::from(row.requested.as_str())),
+        (
+            "winner",
+            row.winner().map_or(JsonValue::Null, entry_to_json),
+        ),
+        (
+            "entries",
+            JsonValue::Array(row.entries.iter().map(entry_to_json).collect()),
+        ),
+    ])
+}
+
+fn prefix_to_json(group: &PrefixGroup) -> JsonValue {
+    JsonValue::object([
+        ("prefix", JsonValue::path(&group.prefix)),
+        (
+            "names",
+            JsonValue::Array(
+                group
+                    .names
+                    .iter()
+                    .map(|name| JsonValue::from(name.as_str()))
+                    .collect(),
+            ),
+        ),
+    ])
+}
+
+fn entry_to_json(entry: &ShadowEntry) -> JsonValue {
+    JsonValue::object([
+        ("path_index", JsonValue::from(entry.path_index)),
+        ("path", JsonValue::path(&entry.path)),
+        ("same_file", JsonValue::from(entry.same_file)),
+    ])
+}
@@ -0,0 +1,143 @@
+use std::io::{BufWriter, StdoutLock, Write};
+
+use crate::cli::args::Args;
+use crate::commands::support::path_support::{active_pins, join_entries, split_entries};
+use crate::config::order_rules::{get_order_rules_path, load_order_rules};
+use crate::path::order::{OrderRule, RuleKind, enforce_order, find_violations};
+use crate::path::pins::pin_for;
+use crate::path::searcher::PathSearcher;
+
+use super::handle_path_result;
+
+/// Reorder `PATH` as little as the rules allow and emit the result
+pub(super) fn handle_enforce(searcher: &PathSearcher, args: &Args, out: &mut impl Write) -> i32 {
+    let result = load_order_rules().and_then(|rules| {
+        if rules.is_empty() {
+            return Err(no_rules_message());
+        }
+        enforce_order(&rules, searcher.dirs())
+    });
+
+    // Pins are applied afterwards, so the outcome is reported by `report_outcome`
+    handle_path_result(result.map(|entries| join_entries(&entries)), args, out)
+}
+
+/// Say whether `new_path`, with pins applied, meets the order rules, naming
+/// the pins that keep a rule from being met
+pub(super) fn report_outcome(searcher: &PathSearcher, new_path: &str, args: &Args) {
+    if args.quiet || args.silent {
+        return;
+    }
+    let Ok(rules) = load_order_rules() else {
+        return;
+    };
+
+    let entries = split_entries(new_path);
+    let violations = find_violations(&rules, &entries);
+    if violations.is_empty() {
+        match (args.enforce_order, new_path == searcher.to_path_string()) {
+            (true, true) => eprintln!("PATH already satisfies the order rules"),
+            (true, false) => eprintln!("Reordered PATH to satisfy the order rules"),
+            (false, _) => eprintln!("Note: Reordered PATH to satisfy the order rules"),
+        }
+        return;
+    }
+
+    let pins = active_pins().unwrap_or_default();
+    let mut warnings: Vec<String> = violations
+        .iter()
+        .map(|violation| {
+            let rule = &rules[violation.rule];
+            let pin = [violation.entry, violation.other]
+                .iter()
+                .find_map(|&index| pin_for(&pins, &entries[index]));
+            match pin {
+                Some(pin) => format!(
+                    "'{rule}' (line {}) is not met because {} is pinned at position {}",
+                    rule.line,
+                    pin.path.display(),
+                    pin.position
+                ),
+                None => format!("'{rule}' (line {}) is not met", rule.line),
+            }
+        })
+        .collect();
+    warnings.dedup();
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+}
+
+/// List the entries breaking each rule; exits 1 if there are any, 2 if the
+/// rules contradict each other
+pub(super) fn handle_check(
+    searcher: &PathSearcher,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let rules = match load_order_rules() {
+        Ok(rules) => rules,
+        Err(e) => {
+            if !args.silent {
+                eprintln!("Error: {e}");
+            }
+            return 2;
+        }
+    };
+    if rules.is_empty() {
+        writeln!(out, "{}", no_rules_message()).ok();
+        out.flush().ok();
+        return 0;
+    }
+
+    let entries = searcher.dirs();
+    let violations = find_violations(&rules, entries);
+    let mut current_rule = None;
+    for violation in &violations {
+        let rule = &rules[violation.rule];
+        if current_rule != Some(violation.rule) {
+            writeln!(out, "'{rule}' (line {}):", rule.line).ok();
+            current_rule = Some(violation.rule);
+        }
+        writeln!(
+            out,
+            "  [{}] {} comes {} [{}] {}",
+            violation.entry + 1,
+            entries[violation.entry].display(),
+            placement(rule),
+            violation.other + 1,
+            entries[violation.other].display()
+        )
+        .ok();
+    }
+
+    if violations.is_empty() {
+        writeln!(out, "PATH satisfies the order rules").ok();
+    }
+    out.flush().ok();
+
+    // Violations can only be fixed if the rules agree with each other
+    if let Err(e) = enforce_order(&rules, entries) {
+        if !args.silent {
+            eprintln!("Error: {e}");
+        }
+        return 2;
+    }
+
+    i32::from(!violations.is_empty())
+}
+
+/// Where a violating entry sits relative to the entry the rule orders it against
+fn placement(rule: &OrderRule) -> &'static str {
+    match rule.kind {
+        RuleKind::Before | RuleKind::First => "after",
+        RuleKind::After | RuleKind::Last => "before",
+    }
+}
+
+fn no_rules_message() -> String {
+    match get_order_rules_path() {
+        Ok(path) => format!("No order rules in {}", path.display()),
+        Err(_) => "No order rules in ~/.whi/order_rules".to_string(),
+    }
+}
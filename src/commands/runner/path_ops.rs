@@ -1,33 +1,187 @@
 use std::env;
 use std::fs;
-use std::io::{BufWriter, StdoutLock};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use crate::cli::args::{Args, DeleteTarget, PathEdit, PreferTarget};
+use crate::cli::args::{AddPosition, Args, DeleteTarget, PathAnchor, PathEdit, PreferTarget};
 use crate::commands::support::path_support::{
-    emit_line, output_path, should_use_color, write_snapshot_safe,
+    emit_line, output_path, should_use_color, warn_if_loud, write_snapshot_safe,
 };
+use crate::config::runtime::load_config;
+use crate::path::pattern::Pattern;
 use crate::path::resolve::{looks_like_exact_path, resolve_path};
-use crate::path::searcher::PathSearcher;
+use crate::path::searcher::{CleanReason, CleanReport, EntryMatch, PathSearcher};
+use crate::search::semver::{Version, VersionReq, compare_candidates};
+use crate::search::version::VersionProbe;
 
 use super::handle_path_result;
 use super::query::search_name;
 
-pub(super) fn handle_clean(
-    searcher: &PathSearcher,
-    args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
-) -> i32 {
-    let (new_path, _removed_indices) = searcher.clean_duplicates();
+pub(super) fn handle_clean(searcher: &PathSearcher, args: &Args, out: &mut impl Write) -> i32 {
+    let report = searcher.clean(args.clean_options);
+
+    if !args.quiet && !args.silent {
+        report_clean(&report);
+    }
+
+    write_snapshot_safe(&report.path, args);
+    output_path(out, &report.path)
+}
+
+fn report_clean(report: &CleanReport) {
+    for (index, path) in &report.normalized {
+        eprintln!("Normalized [{index}] to {}", path.display());
+    }
+
+    for removal in &report.removed {
+        let why = match &removal.reason {
+            CleanReason::Duplicate { kept } => format!("duplicate of [{kept}]"),
+            CleanReason::Normalized { kept } => {
+                format!("same as [{kept}] after normalization")
+            }
+            CleanReason::Canonical { kept, target } => {
+                format!("resolves to {} like [{kept}]", target.display())
+            }
+            CleanReason::Missing => "does not exist".to_string(),
+        };
+        eprintln!(
+            "Removed [{}] {} ({why})",
+            removal.index,
+            removal.path.display()
+        );
+    }
+}
+
+pub(super) fn handle_add(searcher: &PathSearcher, args: &Args, out: &mut impl Write) -> i32 {
+    let mut searcher = searcher.clone();
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    // Anchors name entries of the PATH the user sees, so they are resolved
+    // before entries being moved are taken out
+    let mut anchor = match anchor_index(&searcher, &args.add_position, &cwd) {
+        Ok(anchor) => anchor,
+        Err(e) => {
+            if !args.silent {
+                eprintln!("Error: {e}");
+            }
+            return 2;
+        }
+    };
+
+    let mut pending = Vec::new();
+    for path_str in &args.add_paths {
+        let resolved = match resolve_path(path_str, &cwd) {
+            Ok(p) => p,
+            Err(e) => {
+                warn_if_loud(args, &format!("Could not resolve path '{path_str}': {e}"));
+                PathBuf::from(path_str)
+            }
+        };
+
+        // Entries being moved are taken out first so `--at` refers to the
+        // PATH they are placed into
+        if let Some(existing) = searcher.find_path_index(&resolved) {
+            if !args.move_if_present {
+                if !args.quiet && !args.silent {
+                    eprintln!(
+                        "Note: '{}' is already on PATH at index {existing} (use --move-if-present to move it)",
+                        resolved.display()
+                    );
+                }
+                continue;
+            }
+            if anchor == Some(existing) {
+                if !args.silent {
+                    eprintln!(
+                        "Error: Cannot place '{}' relative to itself",
+                        resolved.display()
+                    );
+                }
+                return 2;
+            }
+            searcher.remove_at(existing).ok();
+            // Keep the anchor on the entry it named
+            if let Some(index) = anchor.as_mut()
+                && existing < *index
+            {
+                *index -= 1;
+            }
+        }
+        pending.push(resolved);
+    }
+
+    // Explicit placements keep the given order by inserting after each other
+    let mut next = match (&args.add_position, anchor) {
+        (AddPosition::At(index), _) => *index,
+        (AddPosition::After(_), Some(index)) => index + 1,
+        (_, Some(index)) => index,
+        _ => 1,
+    };
+
+    for resolved in pending {
+        let insert_at = match args.add_position {
+            AddPosition::Prepend => 1,
+            AddPosition::Append => searcher.dirs().len() + 1,
+            _ => next,
+        };
+        match searcher.insert_at(&resolved, insert_at) {
+            Ok(()) => next = insert_at + 1,
+            Err(e) => warn_if_loud(
+                args,
+                &format!("Could not add '{}': {e}", resolved.display()),
+            ),
+        }
+    }
+
+    let new_path = searcher.to_path_string();
     write_snapshot_safe(&new_path, args);
     output_path(out, &new_path)
 }
 
+/// The 1-based index of the entry `--before` or `--after` names, if given
+fn anchor_index(
+    searcher: &PathSearcher,
+    position: &AddPosition,
+    cwd: &Path,
+) -> Result<Option<usize>, String> {
+    match position {
+        AddPosition::Before(anchor) | AddPosition::After(anchor) => {
+            resolve_anchor(searcher, anchor, cwd).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+pub(super) fn resolve_anchor(
+    searcher: &PathSearcher,
+    anchor: &PathAnchor,
+    cwd: &Path,
+) -> Result<usize, String> {
+    let len = searcher.dirs().len();
+    match anchor {
+        PathAnchor::Index(index) if (1..=len).contains(index) => Ok(*index),
+        PathAnchor::Index(index) => Err(format!(
+            "Index {index} out of bounds (PATH has {len} entries)"
+        )),
+        PathAnchor::Path(path) => {
+            let resolved = resolve_path(path, cwd).unwrap_or_else(|_| PathBuf::from(path));
+            searcher
+                .find_path_index(&resolved)
+                .ok_or_else(|| format!("'{path}' is not on PATH"))
+        }
+        PathAnchor::Pattern(pattern) => searcher
+            .find_fuzzy_indices(pattern, None)
+            .first()
+            .map(|(index, _)| *index)
+            .ok_or_else(|| format!("No PATH entry matches '{pattern}'")),
+    }
+}
+
 pub(super) fn handle_move_or_swap(
     searcher: &PathSearcher,
     path_edit: &PathEdit,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     let result = match path_edit {
         PathEdit::Move { from, to } => searcher.move_entry(*from, *to),
@@ -40,7 +194,7 @@ pub(super) fn handle_prefer(
     searcher: &PathSearcher,
     target: &PreferTarget,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     match target {
         PreferTarget::IndexBased { name, index } => {
@@ -50,15 +204,87 @@ pub(super) fn handle_prefer(
             handle_prefer_path(searcher, name, path, args, out)
         }
         PreferTarget::PathOnly { path } => handle_prefer_path_only(searcher, path, args, out),
+        PreferTarget::VersionConstraint { name, constraint } => {
+            handle_prefer_version(searcher, name, constraint, args, out)
+        }
     }
 }
 
+fn handle_prefer_version(
+    searcher: &PathSearcher,
+    name: &str,
+    constraint: &VersionReq,
+    args: &Args,
+    out: &mut impl Write,
+) -> i32 {
+    let mut search_args = args.clone();
+    search_args.all = true;
+    let results = search_name(searcher, name, &search_args);
+
+    if results.is_empty() {
+        if !args.silent {
+            eprintln!("Error: {name}: not found");
+        }
+        return 1;
+    }
+
+    let config = load_config().unwrap_or_default();
+    let mut probe = VersionProbe::new(config.versions, !args.no_cache);
+    let candidates: Vec<(usize, &Path, Option<Version>)> = results
+        .iter()
+        .map(|result| {
+            let version = probe
+                .probe(&result.path)
+                .and_then(|line| Version::find_in(&line));
+            (result.path_index, result.path.as_path(), version)
+        })
+        .collect();
+    if let Err(e) = probe.save() {
+        warn_if_loud(args, &format!("Failed to update version cache: {e}"));
+    }
+
+    let best = candidates
+        .iter()
+        .filter_map(|&(index, path, version)| Some((version?, index, path)))
+        .filter(|(version, _, _)| constraint.matches(*version))
+        .min_by(|a, b| compare_candidates((a.0, a.1), (b.0, b.1)));
+
+    let Some((version, target_idx, path)) = best else {
+        if !args.silent {
+            eprintln!("Error: No {name} satisfies '{constraint}'. Found:");
+            for (index, path, version) in &candidates {
+                let version = version.map_or_else(|| "unknown".to_string(), |v| v.to_string());
+                eprintln!("  [{index}] {} ({version})", path.display());
+            }
+        }
+        return 1;
+    };
+
+    if target_idx == results[0].path_index {
+        if !args.silent {
+            eprintln!(
+                "{name} {version} at [{target_idx}] {} already wins",
+                path.display()
+            );
+        }
+        return output_path(out, &searcher.to_path_string());
+    }
+
+    if !args.silent {
+        eprintln!(
+            "Preferring {name} {version} at [{target_idx}] {}",
+            path.display()
+        );
+    }
+    handle_prefer_index(searcher, name, target_idx, args, out)
+}
+
 fn handle_prefer_index(
     searcher: &PathSearcher,
     name: &str,
     target_idx: usize,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     let mut search_args = args.clone();
     search_args.all = true;
@@ -110,11 +336,13 @@ fn handle_prefer_path(
     name: &str,
     path_str: &str,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
-    if looks_like_exact_path(path_str) {
+    // `--regex` and `--glob` targets contain `/` and friends, so they are
+    // never mistaken for exact paths
+    if looks_like_exact_path(path_str) && !args.pattern.is_explicit() {
         match resolve_path(path_str, &cwd) {
             Ok(resolved_path) => {
                 handle_prefer_exact_path(searcher, name, &resolved_path, args, out)
@@ -136,7 +364,7 @@ fn handle_prefer_exact_path(
     name: &str,
     path: &Path,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     if !path.exists() {
         if !args.silent {
@@ -190,7 +418,7 @@ fn handle_prefer_path_only(
     searcher: &PathSearcher,
     path_str: &str,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
@@ -238,10 +466,16 @@ fn handle_prefer_fuzzy(
     name: &str,
     pattern: &str,
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
-    let matches = searcher.find_fuzzy_indices(pattern, Some(name));
+    let Some(compiled) = compile_pattern(pattern, args) else {
+        return 2;
+    };
+    let matches = searcher.find_pattern_indices(&compiled, Some(name));
 
+    if args.explain_score && !args.silent {
+        explain_entry_scores(pattern, &matches);
+    }
     if matches.is_empty() {
         if !args.silent {
             eprintln!("Error: No PATH entries match pattern '{pattern}' containing '{name}'");
@@ -249,26 +483,104 @@ fn handle_prefer_fuzzy(
         return 1;
     }
 
-    if matches.len() > 1 {
+    // Fuzzy candidates are ranked, so the best one wins; unranked regex and
+    // glob matches must be unambiguous
+    let best = &matches[0];
+    if matches.len() > 1 && best.score.is_none() {
         if !args.silent {
             eprintln!("Error: Multiple PATH entries match pattern '{pattern}':");
-            for (idx, path) in &matches {
-                eprintln!("  [{}] {}", idx, path.display());
+            for entry in &matches {
+                eprintln!("  [{}] {}", entry.index, entry.path.display());
             }
             eprintln!("Please be more specific or use an index directly.");
         }
         return 2;
     }
+    if matches.len() > 1 && !args.silent {
+        eprintln!(
+            "{} PATH entries match pattern '{pattern}'; using the best match [{}] {}",
+            matches.len(),
+            best.index,
+            best.path.display()
+        );
+    }
 
-    let (index, _) = matches[0];
-    handle_prefer_index(searcher, name, index, args, out)
+    handle_prefer_index(searcher, name, best.index, args, out)
+}
+
+/// Compile a `PATH` entry pattern, reporting syntax errors
+fn compile_pattern(pattern: &str, args: &Args) -> Option<Pattern> {
+    Pattern::new(pattern, args.pattern)
+        .inspect_err(|e| {
+            if !args.silent {
+                eprintln!("Error: {e}");
+            }
+        })
+        .ok()
+}
+
+/// PATH indices named by a delete target that is a path or a pattern,
+/// or the exit code to stop with
+fn resolve_delete_path(
+    searcher: &PathSearcher,
+    path_str: &str,
+    cwd: &Path,
+    args: &Args,
+) -> Result<Vec<usize>, i32> {
+    if looks_like_exact_path(path_str) && !args.pattern.is_explicit() {
+        let resolved = resolve_path(path_str, cwd).map_err(|e| {
+            if !args.silent {
+                eprintln!("Error resolving path: {e}");
+            }
+            2
+        })?;
+
+        if let Some(idx) = searcher.find_path_index(&resolved) {
+            return Ok(vec![idx]);
+        }
+        if !args.silent {
+            eprintln!("Error: Path not found in PATH: {}", resolved.display());
+        }
+        return Err(1);
+    }
+
+    let compiled = compile_pattern(path_str, args).ok_or(2)?;
+    let matches = searcher.find_pattern_indices(&compiled, None);
+
+    if args.explain_score && !args.silent {
+        explain_entry_scores(path_str, &matches);
+    }
+    if matches.is_empty() {
+        if !args.silent {
+            eprintln!("Error: No PATH entries match pattern '{path_str}'");
+        }
+        return Err(1);
+    }
+
+    Ok(matches.iter().map(|entry| entry.index).collect())
+}
+
+/// `--explain-score`: every candidate entry for a pattern, best first
+fn explain_entry_scores(pattern: &str, matches: &[EntryMatch<'_>]) {
+    eprintln!("Candidates for '{pattern}', best first:");
+    for entry in matches {
+        match &entry.score {
+            Some(score) => eprintln!(
+                "  [{}] {}  score {}",
+                entry.index,
+                entry.path.display(),
+                score.describe()
+            ),
+            None => eprintln!("  [{}] {}  (unranked)", entry.index, entry.path.display()),
+        }
+    }
 }
 
 pub(super) fn handle_delete(
     searcher: &PathSearcher,
     targets: &[DeleteTarget],
     args: &Args,
-    out: &mut BufWriter<StdoutLock<'_>>,
+    out: &mut impl Write,
 ) -> i32 {
     let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     let mut indices_to_delete = Vec::new();
@@ -279,41 +591,9 @@ pub(super) fn handle_delete(
                 indices_to_delete.push(*idx);
             }
             DeleteTarget::Path(path_str) => {
-                if looks_like_exact_path(path_str) {
-                    match resolve_path(path_str, &cwd) {
-                        Ok(resolved) => {
-                            if let Some(idx) = searcher.find_path_index(&resolved) {
-                                indices_to_delete.push(idx);
-                            } else {
-                                if !args.silent {
-                                    eprintln!(
-                                        "Error: Path not found in PATH: {}",
-                                        resolved.display()
-                                    );
-                                }
-                                return 1;
-                            }
-                        }
-                        Err(e) => {
-                            if !args.silent {
-                                eprintln!("Error resolving path: {e}");
-                            }
-                            return 2;
-                        }
-                    }
-                } else {
-                    let matches = searcher.find_fuzzy_indices(path_str, None);
-
-                    if matches.is_empty() {
-                        if !args.silent {
-                            eprintln!("Error: No PATH entries match pattern '{path_str}'");
-                        }
-                        return 1;
-                    }
-
-                    for (idx, _) in &matches {
-                        indices_to_delete.push(*idx);
-                    }
+                match resolve_delete_path(searcher, path_str, &cwd, args) {
+                    Ok(indices) => indices_to_delete.extend(indices),
+                    Err(code) => return code,
                 }
             }
         }
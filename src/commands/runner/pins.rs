@@ -0,0 +1,153 @@
+use std::env;
+use std::io::{BufWriter, StdoutLock};
+use std::path::PathBuf;
+
+use crate::cli::args::{Args, PathAnchor, PinAction};
+use crate::commands::support::path_support::{
+    active_pins, join_entries, output_path, session_pins, write_session_pins, write_snapshot_safe,
+};
+use crate::config::runtime::{load_config, set_config_pin};
+use crate::path::pins::{Pin, pin_for, place_pinned};
+use crate::path::resolve::resolve_path;
+use crate::path::searcher::PathSearcher;
+
+use super::path_ops::resolve_anchor;
+use super::preview::handle_preview;
+
+pub(super) fn handle_pin_action(
+    searcher: &PathSearcher,
+    action: &PinAction,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let result = match action {
+        PinAction::Pin {
+            target,
+            position,
+            save,
+        } => handle_pin(searcher, target, *position, *save, args, out),
+        PinAction::Unpin { target, save } => handle_unpin(searcher, target, *save, args),
+    };
+
+    result.unwrap_or_else(|e| {
+        if !args.silent {
+            eprintln!("Error: {e}");
+        }
+        2
+    })
+}
+
+/// Pin the entry `target` names, moving it to `position` first if given, and
+/// emit the resulting `PATH`
+fn handle_pin(
+    searcher: &PathSearcher,
+    target: &PathAnchor,
+    position: Option<usize>,
+    save: bool,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> Result<i32, String> {
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let index = resolve_anchor(searcher, target, &cwd)?;
+    let path = searcher.dirs()[index - 1].clone();
+
+    let len = searcher.dirs().len();
+    let position = position.unwrap_or(index);
+    if position > len {
+        return Err(format!(
+            "Position {position} out of bounds (PATH has {len} entries)"
+        ));
+    }
+
+    let mut pins = active_pins()?;
+    if let Some(other) = pins
+        .iter()
+        .find(|pin| pin.position == position && pin.path != path)
+    {
+        return Err(format!(
+            "Position {position} is already pinned by {}",
+            other.path.display()
+        ));
+    }
+
+    let pin = Pin {
+        path: path.clone(),
+        position,
+    };
+    pins.retain(|p| p.path != path);
+    pins.push(pin.clone());
+    let new_path = join_entries(&place_pinned(searcher.dirs(), &pins));
+
+    if args.dry_run {
+        return Ok(handle_preview(&new_path, args, out));
+    }
+
+    let mut session = session_pins()?;
+    session.retain(|p| p.path != path);
+    session.push(pin);
+    session.sort_by_key(|p| p.position);
+    write_session_pins(&session)?;
+    if save {
+        set_config_pin(&path, Some(position))?;
+    }
+
+    if !args.quiet && !args.silent {
+        eprintln!("Pinned {} at position {position}", path.display());
+    }
+    if new_path != searcher.to_path_string() {
+        write_snapshot_safe(&new_path, args);
+    }
+    Ok(output_path(out, &new_path))
+}
+
+/// Drop the pin on `target`; `PATH` itself is left as it is
+fn handle_unpin(
+    searcher: &PathSearcher,
+    target: &PathAnchor,
+    save: bool,
+    args: &Args,
+) -> Result<i32, String> {
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let path = match target {
+        // A pinned directory can be unpinned after it left PATH
+        PathAnchor::Path(path) => resolve_path(path, &cwd).unwrap_or_else(|_| PathBuf::from(path)),
+        _ => searcher.dirs()[resolve_anchor(searcher, target, &cwd)? - 1].clone(),
+    };
+
+    let mut session = session_pins()?;
+    let pinned_in_session = pin_for(&session, &path).is_some();
+    if pinned_in_session {
+        session.retain(|pin| pin.path != path);
+        write_session_pins(&session)?;
+    }
+
+    let configured = load_config().map(|config| config.pins).unwrap_or_default();
+    let pinned_in_config = pin_for(&configured, &path).is_some();
+    if save && pinned_in_config {
+        set_config_pin(&path, None)?;
+    }
+
+    let loud = !args.quiet && !args.silent;
+    match (pinned_in_session, pinned_in_config, save) {
+        (false, false, _) => Err(format!("{} is not pinned", path.display())),
+        (false, true, false) => Err(format!(
+            "{} is pinned in ~/.whi/config.toml (use --save to unpin it there)",
+            path.display()
+        )),
+        (true, true, false) => {
+            if loud {
+                eprintln!(
+                    "Note: {} is still pinned in ~/.whi/config.toml (use --save to unpin it there)",
+                    path.display()
+                );
+            }
+            Ok(0)
+        }
+        _ => {
+            if loud {
+                eprintln!("Unpinned {}", path.display());
+            }
+            Ok(0)
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::env;
+use std::io::{BufWriter, StdoutLock, Write};
+
+use crate::cli::args::Args;
+use crate::commands::support::path_support::should_use_color;
+use crate::path::diff::{compute_diff, format_diff_with_limit};
+use crate::path::searcher::PathSearcher;
+use crate::search::winners::{WinnerChange, winner_changes};
+
+use super::query::{open_index, save_index};
+
+/// Winner changes listed before the rest are summarized
+const MAX_WINNER_CHANGES: usize = 15;
+
+/// `--dry-run`: show how `new_path` differs from the current `PATH` and which
+/// names would resolve differently, without touching history or the shell
+pub(super) fn handle_preview(
+    new_path: &str,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let current_path = env::var("PATH").unwrap_or_default();
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let (gray, reset) = if use_color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+
+    let diff = compute_diff(new_path, &current_path, false);
+    writeln!(out, "{gray}Dry run, PATH not changed{reset}").ok();
+    writeln!(out, "{}", format_diff_with_limit(&diff, use_color, false)).ok();
+
+    let mut index = open_index(args);
+    let changes = winner_changes(
+        PathSearcher::new(&current_path).dirs(),
+        PathSearcher::new(new_path).dirs(),
+        &mut index,
+    );
+    save_index(&mut index, args);
+
+    if !changes.is_empty() {
+        writeln!(out).ok();
+        writeln!(out, "Winners that would change:").ok();
+        write_winner_changes(&changes, gray, reset, out);
+    }
+
+    if let Err(err) = out.flush() {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+    0
+}
+
+fn write_winner_changes(
+    changes: &[WinnerChange],
+    gray: &str,
+    reset: &str,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) {
+    let width = changes
+        .iter()
+        .take(MAX_WINNER_CHANGES)
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let describe = |path: Option<&std::path::PathBuf>| {
+        path.map_or_else(|| "not found".to_string(), |p| p.display().to_string())
+    };
+
+    for change in changes.iter().take(MAX_WINNER_CHANGES) {
+        writeln!(
+            out,
+            "  {:<width$}  {} → {}",
+            change.name,
+            describe(change.before.as_ref()),
+            describe(change.after.as_ref())
+        )
+        .ok();
+    }
+    if changes.len() > MAX_WINNER_CHANGES {
+        writeln!(
+            out,
+            "  {gray}... and {} more{reset}",
+            changes.len() - MAX_WINNER_CHANGES
+        )
+        .ok();
+    }
+}
@@ -1,17 +1,34 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::HashSet;
+use std::env;
+use std::ffi::OsStr;
 use std::fs;
 use std::io::{self, BufRead, BufWriter, StdoutLock, Write};
 use std::path::{Path, PathBuf};
 
-use crate::cli::args::Args;
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::active_pins;
 use crate::config::runtime::Config;
-use crate::io::output::OutputFormatter;
-use crate::path::fuzzy::FuzzyMatcher;
+use crate::io::output::{
+    OutputFormatter, OutputTemplate, StructuredWriter, not_found_to_json, result_to_json,
+    write_suggestions,
+};
+use crate::io::time::TimeFormat;
+use crate::path::pattern::Pattern;
+use crate::path::pins::pin_for;
 use crate::path::searcher::PathSearcher;
+use crate::search::explain::{CandidateStatus, classify};
+use crate::search::index::{EntryKind, ExecutableIndex};
+use crate::search::introspect::introspect;
 use crate::search::result::{ExecutableCheck, SearchResult};
+use crate::search::shims::ShimResolver;
+use crate::search::suggest::{
+    DEFAULT_SUGGESTION_LIMIT, Suggestions, suggest, well_known_install_dirs,
+};
+use crate::search::symlink::resolve_chain;
+use crate::search::version::VersionProbe;
 
 pub(super) use crate::commands::support::path_support::{
-    output_path, should_use_color, write_snapshot_safe,
+    output_path, should_use_color, warn_if_loud, write_snapshot_safe,
 };
 
 fn write_directory_listing(
@@ -27,17 +44,152 @@ fn write_directory_listing(
         return 3;
     }
 
+    let pins = if args.no_index {
+        Vec::new()
+    } else {
+        active_pins().unwrap_or_default()
+    };
+
     for (idx, dir) in searcher.dirs().iter().enumerate() {
         if args.no_index {
             writeln!(out, "{}", dir.display()).ok();
-        } else {
-            writeln!(out, "{:>4} {}", format!("[{}]", idx + 1), dir.display()).ok();
+            continue;
         }
+
+        write!(out, "{:>4} {}", format!("[{}]", idx + 1), dir.display()).ok();
+        match pin_for(&pins, dir) {
+            Some(pin) if pin.position == idx + 1 => write!(out, " (pinned)").ok(),
+            Some(pin) => write!(out, " (pinned at {})", pin.position).ok(),
+            None => None,
+        };
+        writeln!(out).ok();
     }
     out.flush().ok();
     0
 }
 
+/// Destination for query matches: human-readable lines or JSON records
+enum ResultWriter {
+    Human(OutputFormatter),
+    Structured(StructuredWriter),
+    Template(OutputTemplate),
+}
+
+impl ResultWriter {
+    fn new(args: &Args, use_color: bool, time_format: TimeFormat) -> Self {
+        if let Some(template) = &args.format_template {
+            return ResultWriter::Template(template.clone().with_time_format(time_format));
+        }
+
+        match args.format {
+            OutputFormat::Human => ResultWriter::Human(
+                OutputFormatter::new(use_color, args.print0).with_time_format(time_format),
+            ),
+            OutputFormat::Json => ResultWriter::Structured(StructuredWriter::new(false)),
+            OutputFormat::Ndjson => ResultWriter::Structured(StructuredWriter::new(true)),
+        }
+    }
+
+    fn write_match<W: Write>(
+        &mut self,
+        out: &mut W,
+        name: &str,
+        result: &SearchResult,
+        is_winner: bool,
+        args: &Args,
+        details: &mut MatchDetails<'_>,
+    ) -> io::Result<()> {
+        // Only displayed matches are probed, so hidden fuzzy candidates never run
+        let detailed;
+        let result = if details.is_empty() && (args.explain_score || result.score.is_none()) {
+            result
+        } else {
+            detailed = SearchResult {
+                score: result.score.clone().filter(|_| args.explain_score),
+                ..details.fill(result)
+            };
+            &detailed
+        };
+
+        match self {
+            ResultWriter::Human(formatter) => formatter.write_result(
+                out,
+                result,
+                is_winner,
+                args.follow_symlinks,
+                !args.no_index,
+                3,
+            ),
+            ResultWriter::Structured(writer) => {
+                writer.write_record(out, result_to_json(name, result, is_winner))
+            }
+            ResultWriter::Template(template) => {
+                let terminator = if args.print0 { '\0' } else { '\n' };
+                write!(
+                    out,
+                    "{}{terminator}",
+                    template.render(name, result, is_winner)
+                )
+            }
+        }
+    }
+
+    fn finish<W: Write>(self, out: &mut W) -> io::Result<()> {
+        match self {
+            ResultWriter::Human(_) | ResultWriter::Template(_) => out.flush(),
+            ResultWriter::Structured(writer) => writer.finish(out),
+        }
+    }
+}
+
+/// Per-match lookups that are too costly to run for every candidate
+struct MatchDetails<'a> {
+    versions: Option<VersionProbe>,
+    shims: Option<ShimResolver<'a>>,
+    /// `PATH` used to resolve `#!/usr/bin/env` interpreters, set by `--stat`
+    introspect: Option<&'a [PathBuf]>,
+}
+
+impl<'a> MatchDetails<'a> {
+    fn new(searcher: &'a PathSearcher, args: &Args, config: &Config) -> Self {
+        let shims = args.resolve_shims.then(|| {
+            let cwd = env::current_dir().unwrap_or_default();
+            ShimResolver::new(searcher.dirs(), cwd)
+        });
+
+        MatchDetails {
+            versions: args
+                .versions
+                .then(|| VersionProbe::new(config.versions.clone(), !args.no_cache)),
+            shims,
+            introspect: args.stat.then(|| searcher.dirs()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.versions.is_none() && self.shims.is_none() && self.introspect.is_none()
+    }
+
+    fn fill(&mut self, result: &SearchResult) -> SearchResult {
+        SearchResult {
+            version: self.versions.as_mut().and_then(|p| p.probe(&result.path)),
+            shim: self.shims.as_ref().and_then(|r| r.resolve(&result.path)),
+            file_kind: self
+                .introspect
+                .and_then(|dirs| introspect(&result.path, dirs)),
+            ..result.clone()
+        }
+    }
+
+    fn save(&mut self, args: &Args) {
+        if let Some(probe) = &mut self.versions
+            && let Err(e) = probe.save()
+        {
+            warn_if_loud(args, &format!("Failed to update version cache: {e}"));
+        }
+    }
+}
+
 fn write_query_results(
     searcher: &PathSearcher,
     args: &Args,
@@ -50,13 +202,35 @@ fn write_query_results(
     let mut err = BufWriter::new(stderr.lock());
 
     let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
-    let mut formatter = OutputFormatter::new(use_color, args.print0);
+    let use_err_color = should_use_color(args, super::atty::is(super::atty::Stream::Stderr));
+    let time_format = args
+        .time_format
+        .clone()
+        .unwrap_or_else(|| config.display.time_format.clone());
+    let mut writer = ResultWriter::new(args, use_color, time_format);
+
+    // `--regex` and `--glob` always match against names, whatever the fuzzy setting
+    let use_pattern =
+        args.pattern.is_explicit() || config.search.executable_search_fuzzy ^ args.swap_fuzzy;
+    let mut index = if use_pattern {
+        open_index(args)
+    } else {
+        ExecutableIndex::disabled()
+    };
+    let mut details = MatchDetails::new(searcher, args, config);
 
     for name in names {
-        let use_fuzzy = config.search.executable_search_fuzzy ^ args.swap_fuzzy;
-
-        let results = if !name.contains('/') && use_fuzzy {
-            search_name_fuzzy(searcher, &name, args)
+        let by_pattern = use_pattern && (args.pattern.is_explicit() || !name.contains('/'));
+        let mut results = if by_pattern {
+            match Pattern::new(&name, args.pattern) {
+                Ok(pattern) => search_name_pattern(searcher, &pattern, args, &mut index),
+                Err(e) => {
+                    if !args.silent {
+                        eprintln!("Error: {e}");
+                    }
+                    return 2;
+                }
+            }
         } else {
             search_name(searcher, &name, args)
         };
@@ -64,8 +238,15 @@ fn write_query_results(
         if results.is_empty() {
             all_found = false;
 
-            if !args.silent && !args.quiet {
+            if let ResultWriter::Structured(structured) = &mut writer {
+                let suggestions = suggestions_for(&name, searcher, args, &mut index);
+                let record = not_found_to_json(&name, suggestions.as_ref());
+                structured.write_record(out, record).ok();
+            } else if !args.silent && !args.quiet {
                 writeln!(err, "{name}: not found").ok();
+                if let Some(suggestions) = suggestions_for(&name, searcher, args, &mut index) {
+                    write_suggestions(&mut err, &suggestions, use_err_color).ok();
+                }
             }
             continue;
         }
@@ -78,61 +259,28 @@ fn write_query_results(
             return 3;
         }
 
-        if !name.contains('/') && use_fuzzy {
-            let mut by_index: BTreeMap<usize, Vec<&SearchResult>> = BTreeMap::new();
-            for result in &results {
-                by_index.entry(result.path_index).or_default().push(result);
-            }
-
-            for index_results in by_index.values_mut() {
-                index_results.sort_by(|a, b| {
-                    let name_a = a.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    let name_b = b.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                    name_a.cmp(name_b)
-                });
-            }
+        if by_pattern {
+            rank_pattern_matches(&mut results);
+            let mut seen_names: HashSet<&OsStr> = HashSet::new();
 
-            let mut seen_names: HashSet<String> = HashSet::new();
-
-            for index_results in by_index.into_values() {
-                for result in &index_results {
-                    let file_name = result
-                        .path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("");
-
-                    let is_winner = seen_names.insert(file_name.to_string());
-
-                    if !args.all && !args.full && !is_winner {
-                        continue;
-                    }
+            for result in &results {
+                let file_name = result.path.file_name().unwrap_or_default();
+                let is_winner = seen_names.insert(file_name);
 
-                    formatter
-                        .write_result(
-                            out,
-                            result,
-                            is_winner,
-                            args.follow_symlinks,
-                            !args.no_index,
-                            3,
-                        )
-                        .ok();
+                if !args.all && !args.full && !is_winner {
+                    continue;
                 }
+
+                writer
+                    .write_match(out, &name, result, is_winner, args, &mut details)
+                    .ok();
             }
         } else {
             for (i, result) in results.iter().enumerate() {
                 let is_winner = i == 0;
 
-                formatter
-                    .write_result(
-                        out,
-                        result,
-                        is_winner,
-                        args.follow_symlinks,
-                        !args.no_index,
-                        3,
-                    )
+                writer
+                    .write_match(out, &name, result, is_winner, args, &mut details)
                     .ok();
 
                 if (!args.all && !args.full) || args.one {
@@ -141,34 +289,47 @@ fn write_query_results(
             }
         }
 
-        if args.full {
-            writeln!(out).ok();
-
-            let match_indices: HashSet<usize> = results.iter().map(|r| r.path_index).collect();
-
-            for (idx, dir) in searcher.dirs().iter().enumerate() {
-                let path_index = idx + 1;
-                let has_match = match_indices.contains(&path_index);
-
-                if !args.no_index {
-                    write!(out, "{:>4} ", format!("[{}]", path_index)).ok();
-                }
-
-                if use_color && has_match {
-                    writeln!(out, "\x1b[33m{}\x1b[0m", dir.display()).ok();
-                } else {
-                    writeln!(out, "{}", dir.display()).ok();
-                }
-            }
+        if args.full && matches!(writer, ResultWriter::Human(_)) {
+            write_full_listing(searcher, &results, use_color, args, out);
         }
     }
 
-    out.flush().ok();
+    save_index(&mut index, args);
+    details.save(args);
+    writer.finish(out).ok();
     err.flush().ok();
 
     i32::from(!all_found)
 }
 
+/// The `--full` trailer: every `PATH` entry, highlighting the ones with a match
+fn write_full_listing(
+    searcher: &PathSearcher,
+    results: &[SearchResult],
+    use_color: bool,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) {
+    writeln!(out).ok();
+
+    let match_indices: HashSet<usize> = results.iter().map(|r| r.path_index).collect();
+
+    for (idx, dir) in searcher.dirs().iter().enumerate() {
+        let path_index = idx + 1;
+        let has_match = match_indices.contains(&path_index);
+
+        if !args.no_index {
+            write!(out, "{:>4} ", format!("[{}]", path_index)).ok();
+        }
+
+        if use_color && has_match {
+            writeln!(out, "\x1b[33m{}\x1b[0m", dir.display()).ok();
+        } else {
+            writeln!(out, "{}", dir.display()).ok();
+        }
+    }
+}
+
 pub(super) fn run_query(
     searcher: &PathSearcher,
     args: &Args,
@@ -229,60 +390,69 @@ pub(super) fn search_name(searcher: &PathSearcher, name: &str, args: &Args) -> V
     results
 }
 
-fn check_dir_entry(entry: &fs::DirEntry, args: &Args, path_index: usize) -> Option<SearchResult> {
-    let path = entry.path();
-    let metadata = fs::metadata(&path).ok()?;
-
-    if !metadata.is_file() && !args.show_nonexec {
-        return None;
-    }
-
-    let checker = ExecutableCheck::with_metadata(&path, metadata.clone());
-
-    if !checker.is_executable() && !args.show_nonexec {
+/// "Did you mean" suggestions for a plain name with no match
+fn suggestions_for(
+    name: &str,
+    searcher: &PathSearcher,
+    args: &Args,
+    index: &mut ExecutableIndex,
+) -> Option<Suggestions> {
+    if name.contains('/') || args.pattern.is_explicit() {
         return None;
     }
 
-    let canonical_path = if args.follow_symlinks {
-        fs::canonicalize(&path).ok()
-    } else {
-        None
-    };
-
-    let file_metadata = if args.stat {
-        checker.get_file_metadata()
-    } else {
-        None
-    };
-
-    Some(SearchResult {
-        path,
-        canonical_path,
-        metadata: file_metadata,
-        path_index,
-    })
+    let install_dirs = well_known_install_dirs();
+    Some(suggest(
+        name,
+        searcher.dirs(),
+        &install_dirs,
+        index,
+        DEFAULT_SUGGESTION_LIMIT,
+    ))
 }
 
-fn search_name_fuzzy(searcher: &PathSearcher, query: &str, args: &Args) -> Vec<SearchResult> {
-    use std::ffi::OsStr;
+/// Order pattern matches for display
+///
+/// Fuzzy matches go best score first, each name's matches together in `PATH`
+/// order. Unranked regex and glob matches go in `PATH` order, then by name.
+fn rank_pattern_matches(results: &mut [SearchResult]) {
+    results.sort_by(|a, b| {
+        let total = |r: &SearchResult| r.score.as_ref().map(|score| score.total);
+        let by_name = a.path.file_name().cmp(&b.path.file_name());
+        let by_index = a.path_index.cmp(&b.path_index);
+
+        total(b).cmp(&total(a)).then(if a.score.is_some() {
+            by_name.then(by_index)
+        } else {
+            by_index.then(by_name)
+        })
+    });
+}
 
-    let matcher = FuzzyMatcher::new(query);
+fn search_name_pattern(
+    searcher: &PathSearcher,
+    pattern: &Pattern,
+    args: &Args,
+    index: &mut ExecutableIndex,
+) -> Vec<SearchResult> {
     let mut results = Vec::new();
 
     for (idx, dir) in searcher.dirs().iter().enumerate() {
-        let Ok(entries) = fs::read_dir(dir) else {
-            continue;
-        };
-
-        for entry in entries.flatten() {
-            let path = entry.path();
-            let Some(filename) = path.file_name().and_then(OsStr::to_str) else {
-                continue;
-            };
-
-            if matcher.matches(&PathBuf::from(filename))
-                && let Some(result) = check_dir_entry(&entry, args, idx + 1)
-            {
+        let names: Vec<String> = index
+            .listing(dir)
+            .iter()
+            .filter(|entry| match entry.kind {
+                EntryKind::Executable => true,
+                EntryKind::Broken => false,
+                _ => args.show_nonexec,
+            })
+            .filter(|entry| pattern.matches(&entry.name))
+            .map(|entry| entry.name.clone())
+            .collect();
+
+        for name in names {
+            if let Some(mut result) = check_path(&dir.join(&name), args, idx + 1) {
+                result.score = pattern.score(&name);
                 results.push(result);
             }
         }
@@ -291,14 +461,27 @@ fn search_name_fuzzy(searcher: &PathSearcher, query: &str, args: &Args) -> Vec<S
     results
 }
 
-fn check_path(path: &Path, args: &Args, path_index: usize) -> Option<SearchResult> {
-    let checker = ExecutableCheck::new(path);
+pub(super) fn open_index(args: &Args) -> ExecutableIndex {
+    if args.no_cache {
+        ExecutableIndex::disabled()
+    } else {
+        ExecutableIndex::load()
+    }
+}
 
-    if !checker.exists() {
-        return None;
+pub(super) fn save_index(index: &mut ExecutableIndex, args: &Args) {
+    if let Err(e) = index.save() {
+        warn_if_loud(args, &format!("Failed to update executable index: {e}"));
     }
+}
 
-    if !checker.is_executable() && !args.show_nonexec {
+fn check_path(path: &Path, args: &Args, path_index: usize) -> Option<SearchResult> {
+    let accepted = match classify(path) {
+        CandidateStatus::Executable => true,
+        CandidateStatus::NotExecutable | CandidateStatus::Directory => args.show_nonexec,
+        _ => false,
+    };
+    if !accepted {
         return None;
     }
 
@@ -309,7 +492,7 @@ fn check_path(path: &Path, args: &Args, path_index: usize) -> Option<SearchResul
     };
 
     let metadata = if args.stat {
-        checker.get_file_metadata()
+        ExecutableCheck::new(path).get_file_metadata()
     } else {
         None
     };
@@ -318,6 +501,11 @@ fn check_path(path: &Path, args: &Args, path_index: usize) -> Option<SearchResul
         path: path.to_path_buf(),
         canonical_path,
         metadata,
+        file_kind: None,
         path_index,
+        version: None,
+        shim: None,
+        symlink_chain: args.chain.then(|| resolve_chain(path)),
+        score: None,
     })
 }
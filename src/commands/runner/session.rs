@@ -3,12 +3,15 @@ use std::fs;
 use std::io::{self, BufWriter};
 use std::path::PathBuf;
 
-use crate::cli::args::ApplyTarget;
+use crate::cli::args::{ApplyTarget, Args};
 use crate::commands::support::path_support::{
     emit_line, guarded_path, history_for_current_scope, output_path,
 };
+use crate::config::runtime::load_config;
 use crate::config::{protected_paths, shell_paths};
-use crate::path::file::apply_path_sections;
+use crate::path::file::{apply_path_sections, expand_shell_vars};
+use crate::search::suggest::well_known_install_dirs;
+use crate::session::recover::{Origin, find_recoveries};
 use crate::session::store::cleanup_old_sessions;
 use crate::shell::detect::{Shell, detect_current_shell};
 use crate::shell::init as shell_init;
@@ -162,10 +165,14 @@ pub(super) fn handle_diff(full: bool) -> i32 {
     0
 }
 
-pub(super) fn handle_reset() -> i32 {
+pub(super) fn handle_reset(args: &Args) -> i32 {
     match history_for_current_scope() {
         Ok(history) => match history.initial_snapshot() {
             Ok(Some(initial_path)) => {
+                if args.dry_run {
+                    return super::preview(&initial_path, args);
+                }
+
                 if let Err(e) = history.truncate(1) {
                     eprintln!("Warning: Failed to truncate snapshot history: {e}");
                 }
@@ -206,7 +213,83 @@ fn validate_cursor_position(current_pos: usize, snapshot_len: usize) -> Result<(
     ))
 }
 
-pub(super) fn handle_undo(count: usize) -> i32 {
+pub(super) fn handle_history(args: &Args) -> i32 {
+    let time_format = match &args.time_format {
+        Some(format) => format.clone(),
+        None => load_config()
+            .map(|c| c.display.time_format)
+            .unwrap_or_default(),
+    };
+
+    let history = match history_for_current_scope() {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return 2;
+        }
+    };
+
+    let (snapshots, cursor) = match (history.read_timed_snapshots(), history.get_cursor()) {
+        (Ok(snapshots), Ok(cursor)) => (snapshots, cursor),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("Error: {e}");
+            return 2;
+        }
+    };
+
+    if snapshots.is_empty() {
+        eprintln!("No PATH history found. No operations have been performed in this session.");
+        return 1;
+    }
+
+    let current = cursor.unwrap_or(snapshots.len() - 1);
+    let times: Vec<String> = snapshots
+        .iter()
+        .map(|s| time_format.render(i64::try_from(s.timestamp).unwrap_or(i64::MAX)))
+        .collect();
+    let width = times.iter().map(|t| t.chars().count()).max().unwrap_or(0);
+    let mut previous: Option<Vec<&str>> = None;
+
+    for (index, (snapshot, when)) in snapshots.iter().zip(&times).enumerate() {
+        let entries: Vec<&str> = snapshot.path.split(':').filter(|e| !e.is_empty()).collect();
+
+        let change = match &previous {
+            None => "initial".to_string(),
+            Some(before) => describe_change(before, &entries),
+        };
+        let marker = match index.cmp(&current) {
+            std::cmp::Ordering::Equal => "  ← current",
+            std::cmp::Ordering::Greater => "  (undone)",
+            std::cmp::Ordering::Less => "",
+        };
+        let count = match entries.len() {
+            1 => "1 entry".to_string(),
+            n => format!("{n} entries"),
+        };
+
+        println!(
+            "{:>5} {when:<width$}  {count:>11}  {change}{marker}",
+            format!("[{index}]")
+        );
+        previous = Some(entries);
+    }
+
+    0
+}
+
+/// `+2 -1`, or `reordered` when only the order of entries changed
+fn describe_change(before: &[&str], after: &[&str]) -> String {
+    let added = after.iter().filter(|e| !before.contains(e)).count();
+    let removed = before.iter().filter(|e| !after.contains(e)).count();
+
+    match (added, removed) {
+        (0, 0) if before == after => "unchanged".to_string(),
+        (0, 0) => "reordered".to_string(),
+        _ => format!("+{added} -{removed}"),
+    }
+}
+
+pub(super) fn handle_undo(count: usize, args: &Args) -> i32 {
     if count == 0 {
         eprintln!("Error: Count must be at least 1");
         return 2;
@@ -250,6 +333,10 @@ pub(super) fn handle_undo(count: usize) -> i32 {
                 let target_index = current_pos - count;
                 let target_snapshot = &snapshots[target_index];
 
+                if args.dry_run {
+                    return super::preview(target_snapshot, args);
+                }
+
                 if let Err(e) = history.set_cursor(target_index) {
                     eprintln!("Error: Failed to set cursor: {e}");
                     return 2;
@@ -271,7 +358,7 @@ pub(super) fn handle_undo(count: usize) -> i32 {
     }
 }
 
-pub(super) fn handle_redo(count: usize) -> i32 {
+pub(super) fn handle_redo(count: usize, args: &Args) -> i32 {
     if count == 0 {
         eprintln!("Error: Count must be at least 1");
         return 2;
@@ -325,6 +412,10 @@ pub(super) fn handle_redo(count: usize) -> i32 {
                 let target_index = current_pos + count;
                 let target_snapshot = &snapshots[target_index];
 
+                if args.dry_run {
+                    return super::preview(target_snapshot, args);
+                }
+
                 if target_index == max_pos {
                     if let Err(e) = history.clear_cursor() {
                         eprintln!("Error: Failed to clear cursor: {e}");
@@ -445,3 +536,71 @@ pub(super) fn handle_remove_profile(profile_name: &str) -> i32 {
         }
     }
 }
+
+/// Backs the shells' command-not-found hooks: explain where `name` went and
+/// how to get it back, or exit 1 quietly so the shell prints its usual message
+pub(super) fn handle_not_found(name: &str) -> i32 {
+    if name.is_empty() || name.contains('/') {
+        return 1;
+    }
+
+    let current_path = env::var("PATH").unwrap_or_default();
+    let snapshots = history_for_current_scope()
+        .and_then(|history| history.read_timed_snapshots())
+        .unwrap_or_default();
+    let profiles = saved_profile_paths(&current_path);
+    let found = find_recoveries(
+        name,
+        &current_path,
+        &snapshots,
+        &profiles,
+        &well_known_install_dirs(),
+    );
+    if found.is_empty() {
+        return 1;
+    }
+
+    let time_format = load_config()
+        .map(|c| c.display.time_format)
+        .unwrap_or_default();
+
+    eprintln!("{name}: command not found");
+    for recovery in &found {
+        let origin = match &recovery.origin {
+            Origin::Removed {
+                removed_at: Some(at),
+            } => format!(
+                "removed from PATH this session at {}",
+                time_format.render(i64::try_from(*at).unwrap_or(i64::MAX))
+            ),
+            Origin::Removed { removed_at: None } => "removed from PATH this session".to_string(),
+            Origin::Profile(profile) => format!("on PATH in profile '{profile}'"),
+            Origin::InstallDir => "in an install directory not on PATH".to_string(),
+        };
+        eprintln!("  {} ({origin})", recovery.path.display());
+        eprintln!("    restore with: {}", recovery.restore_command());
+        if let Origin::Profile(profile) = &recovery.origin {
+            eprintln!("    or switch to the profile: whi load {profile}");
+        }
+    }
+    0
+}
+
+/// Each saved profile with the `PATH` it would produce from `current_path`,
+/// skipping profiles that fail to load
+fn saved_profile_paths(current_path: &str) -> Vec<(String, String)> {
+    shell_paths::list_profiles()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|profile| {
+            let parsed = shell_paths::load_profile(&profile).ok()?;
+            let path = apply_path_sections(current_path, &parsed.path).ok()?;
+            let expanded = path
+                .split(':')
+                .map(expand_shell_vars)
+                .collect::<Vec<_>>()
+                .join(":");
+            Some((profile, expanded))
+        })
+        .collect()
+}
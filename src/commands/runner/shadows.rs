@@ -0,0 +1,135 @@
+use std::io::{BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+
+use crate::cli::args::{Args, OutputFormat};
+use crate::commands::support::path_support::should_use_color;
+use crate::io::json::JsonValue;
+use crate::io::output::StructuredWriter;
+use crate::path::fuzzy::FuzzyMatcher;
+use crate::path::searcher::PathSearcher;
+use crate::search::shadows::{ShadowEntry, ShadowGroup, find_shadows};
+
+use super::query::{open_index, save_index};
+
+pub(super) fn handle_shadows(
+    searcher: &PathSearcher,
+    args: &Args,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    if let Some(index) = args.shadow_index
+        && (index == 0 || index > searcher.dirs().len())
+    {
+        eprintln!(
+            "Error: Index {index} out of bounds (PATH has {} entries)",
+            searcher.dirs().len()
+        );
+        return 2;
+    }
+
+    let matcher = args.shadow_pattern.as_deref().map(FuzzyMatcher::new);
+
+    let mut index = open_index(args);
+    let shadows = find_shadows(searcher.dirs(), &mut index);
+    save_index(&mut index, args);
+
+    let groups: Vec<ShadowGroup> = shadows
+        .into_iter()
+        .filter(|group| {
+            matcher
+                .as_ref()
+                .is_none_or(|m| m.matches(&PathBuf::from(&group.name)))
+        })
+        .filter(|group| args.all || group.has_distinct_copies())
+        .filter(|group| {
+            args.shadow_index
+                .is_none_or(|index| group.entries.iter().any(|e| e.path_index == index))
+        })
+        .collect();
+
+    match args.format {
+        OutputFormat::Human => write_human(&groups, args, out),
+        OutputFormat::Json => write_structured(&groups, false, out),
+        OutputFormat::Ndjson => write_structured(&groups, true, out),
+    }
+}
+
+fn write_human(groups: &[ShadowGroup], args: &Args, out: &mut BufWriter<StdoutLock<'_>>) -> i32 {
+    if groups.is_empty() {
+        writeln!(out, "No shadowed executables").ok();
+        out.flush().ok();
+        return 0;
+    }
+
+    let use_color = should_use_color(args, super::atty::is(super::atty::Stream::Stdout));
+    let (green, gray, reset) = if use_color {
+        ("\x1b[1;32m", "\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+
+    for group in groups {
+        writeln!(out, "{}", group.name).ok();
+
+        let winner = group.winner();
+        writeln!(
+            out,
+            "  {:>4} {green}{}{reset}",
+            format!("[{}]", winner.path_index),
+            winner.path.display()
+        )
+        .ok();
+
+        for entry in group.shadowed() {
+            let note = if entry.same_file {
+                "same file"
+            } else {
+                "shadowed"
+            };
+            writeln!(
+                out,
+                "  {:>4} {gray}{} ({note}){reset}",
+                format!("[{}]", entry.path_index),
+                entry.path.display()
+            )
+            .ok();
+        }
+    }
+
+    out.flush().ok();
+    0
+}
+
+fn write_structured(
+    groups: &[ShadowGroup],
+    newline_delimited: bool,
+    out: &mut BufWriter<StdoutLock<'_>>,
+) -> i32 {
+    let mut writer = StructuredWriter::new(newline_delimited);
+
+    for group in groups {
+        let record = JsonValue::object([
+            ("type", JsonValue::from("shadow")),
+            ("name", JsonValue::from(group.name.as_str())),
+            ("winner", entry_to_json(group.winner())),
+            (
+                "shadowed",
+                JsonValue::Array(group.shadowed().iter().map(entry_to_json).collect()),
+            ),
+        ]);
+        writer.write_record(out, record).ok();
+    }
+
+    if let Err(err) = writer.finish(out) {
+        eprintln!("Error: Failed to write output: {err}");
+        return 2;
+    }
+    0
+}
+
+fn entry_to_json(entry: &ShadowEntry) -> JsonValue {
+    JsonValue::object([
+        ("path_index", JsonValue::from(entry.path_index)),
+        ("path", JsonValue::path(&entry.path)),
+        ("same_file", JsonValue::from(entry.same_file)),
+    ])
+}
@@ -1,13 +1,82 @@
 use std::env;
 use std::io::Write;
+use std::path::PathBuf;
 
 use crate::cli::args::{Args, ColorWhen};
+use crate::config::order_rules::load_order_rules;
+use crate::config::runtime::load_config;
 use crate::path::guard::PathGuard;
+use crate::path::order::enforce_order;
+use crate::path::pins::{Pin, keep_pinned, merge_pins};
 use crate::session::history::HistoryContext;
+use crate::session::store;
 
 pub fn history_for_current_scope() -> Result<HistoryContext, String> {
-    let pid = get_session_pid().map_err(|e| e.to_string())?;
-    HistoryContext::global(pid)
+    HistoryContext::global(session_pid()?)
+}
+
+/// Pins from the config and this session; a session pin wins for the same path
+pub fn active_pins() -> Result<Vec<Pin>, String> {
+    let configured = load_config().map(|config| config.pins).unwrap_or_default();
+    let session = store::read_session_pins(session_pid()?)?;
+    Ok(merge_pins(&configured, &session))
+}
+
+/// Session pins only, as `whi pin` and `whi unpin` edit them
+pub fn session_pins() -> Result<Vec<Pin>, String> {
+    store::read_session_pins(session_pid()?)
+}
+
+pub fn write_session_pins(pins: &[Pin]) -> Result<(), String> {
+    store::write_session_pins(session_pid()?, pins)
+}
+
+/// Keep `pins` where they are across the edit from `current_path` to
+/// `new_path`, or explain which pin the edit would break
+pub fn keep_pins(current_path: &str, new_path: &str, pins: &[Pin]) -> Result<String, String> {
+    if pins.is_empty() {
+        return Ok(new_path.to_string());
+    }
+
+    let after = split_entries(new_path);
+    let kept = keep_pinned(&split_entries(current_path), &after, pins)?;
+
+    if kept == after {
+        return Ok(new_path.to_string());
+    }
+    Ok(join_entries(&kept))
+}
+
+/// `path` reordered by `~/.whi/order_rules` when `[order] auto_enforce` is on
+pub fn auto_order(path: &str) -> Result<String, String> {
+    if !load_config().is_ok_and(|config| config.order.auto_enforce) {
+        return Ok(path.to_string());
+    }
+
+    let rules = load_order_rules()?;
+    let entries = split_entries(path);
+    let ordered = enforce_order(&rules, &entries)?;
+    if ordered == entries {
+        return Ok(path.to_string());
+    }
+    Ok(join_entries(&ordered))
+}
+
+#[must_use]
+pub fn split_entries(path: &str) -> Vec<PathBuf> {
+    path.split(':')
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[must_use]
+pub fn join_entries(entries: &[PathBuf]) -> String {
+    entries
+        .iter()
+        .map(|entry| entry.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(":")
 }
 
 #[must_use]
@@ -17,6 +86,10 @@ pub fn guarded_path(new_path: &str) -> String {
 }
 
 pub fn write_snapshot_safe(new_path: &str, args: &Args) {
+    if args.defer_snapshot {
+        return;
+    }
+
     let guarded_path = guarded_path(new_path);
 
     match history_for_current_scope() {
@@ -79,6 +152,10 @@ pub fn warn_if_loud(args: &Args, message: &str) {
     }
 }
 
+fn session_pid() -> Result<u32, String> {
+    get_session_pid().map_err(|e| e.to_string())
+}
+
 fn get_session_pid() -> Result<u32, std::io::Error> {
     if let Ok(pid_str) = env::var("WHI_SESSION_PID") {
         pid_str.parse::<u32>().map_err(|_| {
@@ -1,4 +1,5 @@
 pub mod migration;
+pub mod order_rules;
 pub mod profiles;
 pub mod protected_paths;
 pub mod runtime;
@@ -0,0 +1,23 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::path::order::{OrderRule, parse_order_rules};
+
+pub fn get_order_rules_path() -> Result<PathBuf, String> {
+    let home = env::var("HOME").map_err(|_| "HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".whi").join("order_rules"))
+}
+
+/// Load `~/.whi/order_rules`; having no file means having no rules
+pub fn load_order_rules() -> Result<Vec<OrderRule>, String> {
+    let path = get_order_rules_path()?;
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {} file: {e}", path.display()))?;
+    parse_order_rules(&content).map_err(|e| format!("{}: {e}", path.display()))
+}
@@ -1,13 +1,35 @@
+use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::io::atomic_file::AtomicFile;
+use crate::io::time::TimeFormat;
+use crate::path::pins::Pin;
+use crate::path::resolve::expand_tilde;
 
 #[derive(Debug, Clone, Default)]
 pub struct Config {
     pub search: SearchConfig,
+    pub versions: VersionsConfig,
+    pub display: DisplayConfig,
+    pub environments: EnvironmentsConfig,
+    pub order: OrderConfig,
+    /// Pins from `[pins]`, kept in every session
+    pub pins: Vec<Pin>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplayConfig {
+    /// Used for `--stat`, `--format-string` and `whi history` unless `--time-format` is given
+    pub time_format: TimeFormat,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OrderConfig {
+    /// Reorder `PATH` by `~/.whi/order_rules` after every edit, not only on `whi enforce`
+    pub auto_enforce: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -15,12 +37,69 @@ pub struct SearchConfig {
     pub executable_search_fuzzy: bool,
 }
 
+/// Settings for `--versions` probing
+#[derive(Debug, Clone)]
+pub struct VersionsConfig {
+    /// Hard limit for a single probe before the process is killed
+    pub timeout_ms: u64,
+    /// Argument passed when no per-name override exists
+    pub default_arg: String,
+    /// Per-executable overrides from `[versions.args]`, e.g. `java = "-version"`
+    pub args: HashMap<String, String>,
+}
+
+impl Default for VersionsConfig {
+    fn default() -> Self {
+        VersionsConfig {
+            timeout_ms: 1000,
+            default_arg: "--version".to_string(),
+            args: HashMap::new(),
+        }
+    }
+}
+
+/// `PATH` sources for `--env`
+#[derive(Debug, Clone)]
+pub struct EnvironmentsConfig {
+    /// The minimal `PATH` cron gives jobs unless a crontab sets one
+    pub cron_path: String,
+    /// The `PATH` systemd gives units that do not set `Environment=PATH=...`
+    pub systemd_path: String,
+    /// Read for `secure_path`, following `@include` and `@includedir`
+    pub sudoers: PathBuf,
+}
+
+impl Default for EnvironmentsConfig {
+    fn default() -> Self {
+        EnvironmentsConfig {
+            cron_path: "/usr/bin:/bin".to_string(),
+            systemd_path: "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin"
+                .to_string(),
+            sudoers: PathBuf::from("/etc/sudoers"),
+        }
+    }
+}
+
+impl VersionsConfig {
+    /// The version argument to use for executable `name`
+    #[must_use]
+    pub fn arg_for(&self, name: &str) -> &str {
+        self.args.get(name).unwrap_or(&self.default_arg)
+    }
+}
+
 /// Get the config file path
 pub fn get_config_path() -> Result<PathBuf, String> {
     let home = env::var("HOME").map_err(|_| "HOME environment variable not set")?;
     Ok(PathBuf::from(home).join(".whi").join("config.toml"))
 }
 
+/// Get the directory holding whi's regenerable caches
+pub fn get_cache_dir() -> Result<PathBuf, String> {
+    let home = env::var("HOME").map_err(|_| "HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".whi").join("cache"))
+}
+
 /// Load config from file, or return default if file doesn't exist
 pub fn load_config() -> Result<Config, String> {
     let config_path = get_config_path()?;
@@ -66,8 +145,14 @@ fn generate_default_config() -> String {
     let defaults = Config::default();
 
     format!(
-        "# whi configuration file\n# This file is automatically created with default values\n\n[search]\n# Enable fuzzy search for executables (default: {exec_fuzzy})\n# When enabled: 'whi cargo' finds cargo, cargo-clippy, cargo-fmt, etc.\n# When disabled: 'whi cargo' finds only exact match 'cargo'\nexecutable_search_fuzzy = {exec_fuzzy}\n\n# NOTE: Protected paths configuration lives in ~/.whi/protected_paths\n",
+        "# whi configuration file\n# This file is automatically created with default values\n\n[search]\n# Enable fuzzy search for executables (default: {exec_fuzzy})\n# When enabled: 'whi cargo' finds cargo, cargo-clippy, cargo-fmt, etc.\n# When disabled: 'whi cargo' finds only exact match 'cargo'\nexecutable_search_fuzzy = {exec_fuzzy}\n\n[versions]\n# Used by --versions: milliseconds before a probe is killed (default: {timeout_ms})\ntimeout_ms = {timeout_ms}\n# Argument passed to each match (default: \"{version_arg}\")\narg = \"{version_arg}\"\n\n[versions.args]\n# Per-executable overrides, e.g.:\n# java = \"-version\"\n\n[display]\n# How timestamps are shown: local, iso8601, rfc3339, relative, epoch\n# or a strftime format such as \"%d.%m.%Y %H:%M\" (default: local)\n# Times use the zone from TZ, falling back to /etc/localtime\ntime_format = \"local\"\n\n[environments]\n# PATHs used by --env cron and --env systemd\ncron_path = \"{cron_path}\"\nsystemd_path = \"{systemd_path}\"\n# Where --env sudo reads secure_path from\nsudoers = \"{sudoers}\"\n\n[order]\n# Reorder PATH by ~/.whi/order_rules after every edit, not only on\n# whi enforce (default: {auto_enforce})\nauto_enforce = {auto_enforce}\n\n[pins]\n# PATH entries kept at a fixed position in every session, e.g.:\n# \"~/.local/bin\" = 1\n\n# NOTE: Protected paths configuration lives in ~/.whi/protected_paths\n",
         exec_fuzzy = defaults.search.executable_search_fuzzy,
+        timeout_ms = defaults.versions.timeout_ms,
+        version_arg = defaults.versions.default_arg,
+        cron_path = defaults.environments.cron_path,
+        systemd_path = defaults.environments.systemd_path,
+        sudoers = defaults.environments.sudoers.display(),
+        auto_enforce = defaults.order.auto_enforce,
     )
 }
 
@@ -91,8 +176,49 @@ fn parse_config(content: &str) -> Result<Config, String> {
             let key = key.trim();
             let value = value.trim();
 
-            if current_section.as_str() == "search" && key == "executable_search_fuzzy" {
-                config.search.executable_search_fuzzy = parse_bool(value)?;
+            match (current_section.as_str(), key) {
+                ("search", "executable_search_fuzzy") => {
+                    config.search.executable_search_fuzzy = parse_bool(value)?;
+                }
+                ("versions", "timeout_ms") => {
+                    config.versions.timeout_ms = value
+                        .parse()
+                        .map_err(|_| format!("Invalid timeout_ms value: {value}"))?;
+                }
+                ("display", "time_format") => {
+                    config.display.time_format = TimeFormat::parse(&parse_string(value))?;
+                }
+                ("environments", "cron_path") => {
+                    config.environments.cron_path = parse_string(value);
+                }
+                ("environments", "systemd_path") => {
+                    config.environments.systemd_path = parse_string(value);
+                }
+                ("environments", "sudoers") => {
+                    config.environments.sudoers = PathBuf::from(parse_string(value));
+                }
+                ("order", "auto_enforce") => {
+                    config.order.auto_enforce = parse_bool(value)?;
+                }
+                ("pins", path) => {
+                    let position = value
+                        .parse()
+                        .ok()
+                        .filter(|&position| position >= 1)
+                        .ok_or_else(|| format!("Invalid pin position for {path}: {value}"))?;
+                    config.pins.push(Pin {
+                        path: PathBuf::from(expand_tilde(&parse_string(path))),
+                        position,
+                    });
+                }
+                ("versions", "arg") => config.versions.default_arg = parse_string(value),
+                ("versions.args", name) => {
+                    config
+                        .versions
+                        .args
+                        .insert(name.trim_matches('"').to_string(), parse_string(value));
+                }
+                _ => {}
             }
         }
     }
@@ -100,6 +226,56 @@ fn parse_config(content: &str) -> Result<Config, String> {
     Ok(config)
 }
 
+/// Pin `path` at `position` in `[pins]`, or drop its pin when `position` is
+/// `None`, leaving the rest of the file as written
+pub fn set_config_pin(path: &Path, position: Option<usize>) -> Result<(), String> {
+    ensure_config_exists()?;
+    let config_path = get_config_path()?;
+    let content =
+        fs::read_to_string(&config_path).map_err(|e| format!("Failed to read config file: {e}"))?;
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_pins = false;
+    // Just past the last entry of `[pins]`, or past the header and the
+    // comments directly below it
+    let mut pins_end = None;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_pins = trimmed == "[pins]";
+        } else if in_pins
+            && let Some((key, _)) = trimmed.split_once('=')
+            && !trimmed.starts_with('#')
+            && Path::new(&expand_tilde(&parse_string(key.trim()))) == path
+        {
+            continue;
+        }
+        lines.push(line.to_string());
+        let is_entry = !trimmed.starts_with('#') && trimmed.contains('=');
+        let follows_end = pins_end == Some(lines.len() - 1) && !trimmed.is_empty();
+        if in_pins && (trimmed == "[pins]" || is_entry || follows_end) {
+            pins_end = Some(lines.len());
+        }
+    }
+
+    if let Some(position) = position {
+        let entry = format!("\"{}\" = {position}", path.display());
+        match pins_end {
+            Some(end) => lines.insert(end, entry),
+            None => lines.extend([String::new(), "[pins]".to_string(), entry]),
+        }
+    }
+
+    let mut atomic_file =
+        AtomicFile::new(&config_path).map_err(|e| format!("Failed to write config: {e}"))?;
+    atomic_file
+        .write_all(format!("{}\n", lines.join("\n")).as_bytes())
+        .map_err(|e| format!("Failed to write config: {e}"))?;
+    atomic_file
+        .commit()
+        .map_err(|e| format!("Failed to commit config file: {e}"))
+}
+
 fn parse_bool(s: &str) -> Result<bool, String> {
     match s.to_lowercase().as_str() {
         "true" => Ok(true),
@@ -107,3 +283,10 @@ fn parse_bool(s: &str) -> Result<bool, String> {
         _ => Err(format!("Invalid boolean value: {s}")),
     }
 }
+
+fn parse_string(s: &str) -> String {
+    s.strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .unwrap_or(s)
+        .to_string()
+}
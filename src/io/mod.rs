@@ -1,3 +1,6 @@
 pub mod atomic_file;
+pub mod json;
 pub mod line_utils;
 pub mod output;
+pub mod time;
+pub mod tz;
@@ -1,16 +1,37 @@
-use crate::search::result::SearchResult;
+use crate::io::json::JsonValue;
+use crate::io::time::{TimeFormat, unix_timestamp};
+use crate::path::fuzzy::FuzzyScore;
+use crate::search::introspect::FileKind;
+use crate::search::result::{FileMetadata, SearchResult};
+use crate::search::shims::ShimResolution;
+use crate::search::suggest::Suggestions;
+use crate::search::symlink::{ChainStatus, SymlinkChain};
+use std::fmt::Write as _;
 use std::io::Write;
+use std::path::Path;
 use std::time::SystemTime;
 
 pub struct OutputFormatter {
     use_color: bool,
     print0: bool,
+    time_format: TimeFormat,
 }
 
 impl OutputFormatter {
     #[must_use]
     pub fn new(use_color: bool, print0: bool) -> Self {
-        OutputFormatter { use_color, print0 }
+        OutputFormatter {
+            use_color,
+            print0,
+            time_format: TimeFormat::default(),
+        }
+    }
+
+    /// Render `--stat` timestamps with `time_format` instead of local `YYYY-MM-DD HH:MM:SS`
+    #[must_use]
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
     }
 
     pub fn write_result<W: Write>(
@@ -43,12 +64,28 @@ impl OutputFormatter {
             write!(out, " → {}", canonical.display())?;
         }
 
+        if let Some(ref version) = result.version {
+            if self.use_color {
+                write!(out, "  \x1b[90m({version})\x1b[0m")?;
+            } else {
+                write!(out, "  ({version})")?;
+            }
+        }
+
         if self.print0 {
             write!(out, "\0")?;
         } else {
             writeln!(out)?;
         }
 
+        if let Some(ref chain) = result.symlink_chain {
+            self.write_chain(out, chain)?;
+        }
+
+        if let Some(ref shim) = result.shim {
+            self.write_shim(out, shim)?;
+        }
+
         // Show metadata if present (works with or without -e)
         if let Some(ref meta) = result.metadata {
             writeln!(
@@ -56,70 +93,742 @@ impl OutputFormatter {
                 "  inode: {}, device: {}, size: {} bytes",
                 meta.ino, meta.dev, meta.size
             )?;
+            writeln!(
+                out,
+                "  mode: {} ({:04o}), uid: {}, gid: {}",
+                format_mode(meta.mode),
+                meta.mode & 0o7777,
+                meta.uid,
+                meta.gid
+            )?;
             if let Some(ctime) = meta.ctime {
-                writeln!(out, "  created:  {}", Self::format_time(ctime))?;
+                writeln!(out, "  created:  {}", self.format_time(ctime))?;
             }
             if let Some(mtime) = meta.mtime {
-                writeln!(out, "  modified: {}", Self::format_time(mtime))?;
+                writeln!(out, "  modified: {}", self.format_time(mtime))?;
             }
         }
 
+        if let Some(ref kind) = result.file_kind {
+            writeln!(out, "  type: {}", describe_file_kind(kind))?;
+        }
+
+        if let Some(ref score) = result.score {
+            writeln!(out, "  score: {}", score.describe())?;
+        }
+
         Ok(())
     }
 
-    fn format_time(time: SystemTime) -> String {
-        use std::time::UNIX_EPOCH;
+    fn write_chain<W: Write>(&self, out: &mut W, chain: &SymlinkChain) -> std::io::Result<()> {
+        let (gray, reset) = if self.use_color {
+            ("\x1b[90m", "\x1b[0m")
+        } else {
+            ("", "")
+        };
 
-        let duration = time.duration_since(UNIX_EPOCH).unwrap_or_default();
-        let secs = duration.as_secs();
+        for hop in &chain.hops {
+            if hop.relative {
+                writeln!(
+                    out,
+                    "  → {} {gray}(relative: {}){reset}",
+                    hop.path.display(),
+                    hop.target.display()
+                )?;
+            } else {
+                writeln!(out, "  → {} {gray}(absolute){reset}", hop.path.display())?;
+            }
+        }
 
-        // Convert to US date/time format: MM/DD/YYYY HH:MM:SS AM/PM
-        let days_since_epoch = secs / 86400;
-        let seconds_today = secs % 86400;
+        match chain.status {
+            ChainStatus::Resolved => Ok(()),
+            ChainStatus::Loop => writeln!(out, "  ✗ symlink loop"),
+            ChainStatus::Broken => writeln!(out, "  ✗ broken link"),
+        }
+    }
 
-        // Calculate date (simplified epoch calculation)
-        // Days since Unix epoch (Jan 1, 1970)
-        let mut year = 1970;
-        let mut days_left = days_since_epoch;
+    fn write_shim<W: Write>(&self, out: &mut W, shim: &ShimResolution) -> std::io::Result<()> {
+        let (gray, reset) = if self.use_color {
+            ("\x1b[90m", "\x1b[0m")
+        } else {
+            ("", "")
+        };
+
+        let mut label = shim.kind.as_str().to_string();
+        if let Some(ref selector) = shim.selector {
+            label.push(' ');
+            label.push_str(selector);
+        }
+        if let Some(ref source) = shim.source {
+            label.push_str(" via ");
+            label.push_str(source);
+        }
 
-        // Adjust for years
-        loop {
-            let days_in_year = if Self::is_leap_year(year) { 366 } else { 365 };
-            if days_left < days_in_year {
-                break;
+        match shim.target {
+            Some(ref target) => {
+                writeln!(out, "  shim → {} {gray}({label}){reset}", target.display())
             }
-            days_left -= days_in_year;
-            year += 1;
+            None => writeln!(out, "  shim → ✗ not installed {gray}({label}){reset}"),
+        }
+    }
+
+    fn format_time(&self, time: SystemTime) -> String {
+        self.time_format.render(unix_timestamp(time))
+    }
+}
+
+/// "Did you mean" lines printed under `NAME: not found`
+pub fn write_suggestions<W: Write>(
+    out: &mut W,
+    suggestions: &Suggestions,
+    use_color: bool,
+) -> std::io::Result<()> {
+    let (gray, reset) = if use_color {
+        ("\x1b[90m", "\x1b[0m")
+    } else {
+        ("", "")
+    };
+
+    if !suggestions.near.is_empty() {
+        writeln!(out, "  did you mean:")?;
+        let width = suggestions
+            .near
+            .iter()
+            .map(|near| near.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for near in &suggestions.near {
+            let dir = near.path.parent().unwrap_or(&near.path);
+            writeln!(
+                out,
+                "    {:<width$}  {gray}[{}] {}{reset}",
+                near.name,
+                near.path_index,
+                dir.display()
+            )?;
+        }
+    }
+
+    if !suggestions.off_path.is_empty() {
+        writeln!(out, "  found outside PATH:")?;
+        for found in &suggestions.off_path {
+            writeln!(
+                out,
+                "    {}  {gray}(run: {}){reset}",
+                found.path.display(),
+                found.add_command()
+            )?;
         }
+    }
 
-        // Find month and day
-        let days_in_months = if Self::is_leap_year(year) {
-            [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    Ok(())
+}
+
+/// Collects machine-readable records and writes them as JSON or NDJSON
+///
+/// NDJSON records are written as soon as they arrive; JSON records are buffered
+/// and written as a single array by [`StructuredWriter::finish`].
+pub struct StructuredWriter {
+    newline_delimited: bool,
+    records: Vec<JsonValue>,
+}
+
+impl StructuredWriter {
+    #[must_use]
+    pub fn new(newline_delimited: bool) -> Self {
+        StructuredWriter {
+            newline_delimited,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn write_record<W: Write>(
+        &mut self,
+        out: &mut W,
+        record: JsonValue,
+    ) -> std::io::Result<()> {
+        if self.newline_delimited {
+            writeln!(out, "{record}")
         } else {
-            [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+            self.records.push(record);
+            Ok(())
+        }
+    }
+
+    pub fn finish<W: Write>(self, out: &mut W) -> std::io::Result<()> {
+        if !self.newline_delimited {
+            writeln!(out, "{}", JsonValue::Array(self.records))?;
+        }
+        out.flush()
+    }
+}
+
+/// `ls -l` style permission string, e.g. `-rwxr-xr-x`
+fn format_mode(mode: u32) -> String {
+    let file_type = match mode & 0o170_000 {
+        0o040_000 => 'd',
+        0o120_000 => 'l',
+        0o100_000 => '-',
+        _ => '?',
+    };
+
+    let mut out = String::from(file_type);
+    for (shift, special, set_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => set_char,
+            (false, true) => set_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+fn describe_file_kind(kind: &FileKind) -> String {
+    match kind {
+        FileKind::Elf(elf) => {
+            let interpreter = elf
+                .interpreter
+                .as_ref()
+                .map(|path| format!(" (interpreter {})", path.display()))
+                .unwrap_or_default();
+            format!(
+                "ELF {}-bit {} {}, {}{interpreter}",
+                elf.bits,
+                if elf.little_endian { "LSB" } else { "MSB" },
+                elf.machine,
+                elf.linkage.as_str()
+            )
+        }
+        FileKind::MachO(macho) if macho.universal => {
+            format!("Mach-O universal ({})", macho.architectures.join(", "))
+        }
+        FileKind::MachO(macho) => format!("Mach-O {}", macho.architectures.join(", ")),
+        FileKind::Script(script) => {
+            let mut shebang = script.interpreter.clone();
+            if let Some(ref program) = script.env_program {
+                shebang.push(' ');
+                shebang.push_str(program);
+            }
+            match script.resolved {
+                Some(ref resolved) => format!("script (#!{shebang} → {})", resolved.display()),
+                None => format!("script (#!{shebang} → ✗ not found)"),
+            }
+        }
+        FileKind::Other => "other".to_string(),
+    }
+}
+
+/// A value that can appear in a `--format-string` template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateField {
+    Name,
+    Executable,
+    Path,
+    Dir,
+    Canonical,
+    Index,
+    Winner,
+    Version,
+    Chain,
+    Shim,
+    Kind,
+    Dev,
+    Ino,
+    Size,
+    Mode,
+    Perms,
+    Uid,
+    Gid,
+    Mtime,
+    Ctime,
+}
+
+impl TemplateField {
+    const ALL: [(&'static str, TemplateField); 20] = [
+        ("name", TemplateField::Name),
+        ("executable", TemplateField::Executable),
+        ("path", TemplateField::Path),
+        ("dir", TemplateField::Dir),
+        ("canonical", TemplateField::Canonical),
+        ("index", TemplateField::Index),
+        ("winner", TemplateField::Winner),
+        ("version", TemplateField::Version),
+        ("chain", TemplateField::Chain),
+        ("shim", TemplateField::Shim),
+        ("kind", TemplateField::Kind),
+        ("dev", TemplateField::Dev),
+        ("ino", TemplateField::Ino),
+        ("size", TemplateField::Size),
+        ("mode", TemplateField::Mode),
+        ("perms", TemplateField::Perms),
+        ("uid", TemplateField::Uid),
+        ("gid", TemplateField::Gid),
+        ("mtime", TemplateField::Mtime),
+        ("ctime", TemplateField::Ctime),
+    ];
+
+    fn from_name(name: &str) -> Option<TemplateField> {
+        Self::ALL
+            .iter()
+            .find(|(field_name, _)| *field_name == name)
+            .map(|(_, field)| *field)
+    }
+
+    fn is_time(self) -> bool {
+        matches!(self, TemplateField::Mtime | TemplateField::Ctime)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Align {
+    Left(usize),
+    Right(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// A field padded to a width
+    Value(TemplateField, Option<Align>),
+    /// `{mtime:%Y-%m-%d}` or `{mtime:relative}`; `None` uses the template's default
+    Time(TemplateField, Option<TimeFormat>),
+    /// `{winner:*| }`: the first text for the winning match, the second otherwise
+    Winner(String, String),
+}
+
+/// A parsed `--format-string` such as `{index}\t{path}\t{mtime:%Y-%m-%d}`
+///
+/// `{field}` expands to a match's value and is empty when the value is
+/// unavailable. `{field:<N}` and `{field:>N}` pad to a width, time fields take a
+/// `--time-format` value such as `relative` or `%Y-%m-%d`, and `{winner:TEXT}` or `{winner:TEXT|ELSE}` mark the
+/// winning match. `{{` and `}}` are literal braces; `\t`, `\n`, `\0` and `\\`
+/// are escapes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+    time_format: TimeFormat,
+}
+
+impl OutputTemplate {
+    pub fn parse(template: &str) -> Result<OutputTemplate, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('0') => literal.push('\0'),
+                    Some(other) => {
+                        literal.push('\\');
+                        if other != '\\' {
+                            literal.push(other);
+                        }
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err("Unmatched '}' in format string (use '}}')".to_string()),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => placeholder.push(ch),
+                            None => {
+                                return Err(format!("Unclosed '{{{placeholder}' in format string"));
+                            }
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Self::parse_placeholder(&placeholder)?);
+                }
+                _ => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(OutputTemplate {
+            segments,
+            time_format: TimeFormat::default(),
+        })
+    }
+
+    fn parse_placeholder(placeholder: &str) -> Result<Segment, String> {
+        let (name, spec) = match placeholder.split_once(':') {
+            Some((name, spec)) => (name.trim(), Some(spec)),
+            None => (placeholder.trim(), None),
+        };
+
+        let Some(field) = TemplateField::from_name(name) else {
+            let known: Vec<&str> = TemplateField::ALL.iter().map(|(n, _)| *n).collect();
+            return Err(format!(
+                "Unknown field '{{{name}}}' in format string (available: {})",
+                known.join(", ")
+            ));
         };
 
-        let mut month = 1;
-        let mut day = days_left + 1;
-        for &days_in_month in &days_in_months {
-            #[allow(clippy::cast_sign_loss)]
-            let days_in_month_u64 = days_in_month as u64;
-            if day <= days_in_month_u64 {
-                break;
+        if field == TemplateField::Winner {
+            let (yes, no) = match spec {
+                Some(spec) => spec.split_once('|').unwrap_or((spec, "")),
+                None => ("*", ""),
+            };
+            return Ok(Segment::Winner(yes.to_string(), no.to_string()));
+        }
+
+        if field.is_time() {
+            let format = spec.map(TimeFormat::parse).transpose()?;
+            return Ok(Segment::Time(field, format));
+        }
+
+        let align = match spec {
+            None => None,
+            Some(spec) => Some(Self::parse_align(spec).ok_or_else(|| {
+                format!("Invalid width '{spec}' for '{{{name}}}' (use <N or >N)")
+            })?),
+        };
+        Ok(Segment::Value(field, align))
+    }
+
+    fn parse_align(spec: &str) -> Option<Align> {
+        if let Some(width) = spec.strip_prefix('>') {
+            return width.parse().ok().map(Align::Right);
+        }
+        let width = spec.strip_prefix('<').unwrap_or(spec);
+        width.parse().ok().map(Align::Left)
+    }
+
+    /// Format for time fields written without their own format, e.g. `{mtime}`
+    #[must_use]
+    pub fn with_time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// Whether the template refers to any of `fields`
+    #[must_use]
+    pub fn uses(&self, fields: &[TemplateField]) -> bool {
+        self.segments.iter().any(|segment| match segment {
+            Segment::Value(field, _) | Segment::Time(field, _) => fields.contains(field),
+            Segment::Winner(..) => fields.contains(&TemplateField::Winner),
+            Segment::Literal(_) => false,
+        })
+    }
+
+    /// Whether the template needs the `--stat` metadata and file header details
+    #[must_use]
+    pub fn needs_stat(&self) -> bool {
+        self.uses(&[
+            TemplateField::Kind,
+            TemplateField::Dev,
+            TemplateField::Ino,
+            TemplateField::Size,
+            TemplateField::Mode,
+            TemplateField::Perms,
+            TemplateField::Uid,
+            TemplateField::Gid,
+            TemplateField::Mtime,
+            TemplateField::Ctime,
+        ])
+    }
+
+    /// Expand the template for one match
+    #[must_use]
+    pub fn render(&self, name: &str, result: &SearchResult, is_winner: bool) -> String {
+        let mut out = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Winner(yes, no) => out.push_str(if is_winner { yes } else { no }),
+                Segment::Time(field, format) => {
+                    let meta = result.metadata.as_ref();
+                    let time = match field {
+                        TemplateField::Ctime => meta.and_then(|m| m.ctime),
+                        _ => meta.and_then(|m| m.mtime),
+                    };
+                    if let Some(time) = time {
+                        let format = format.as_ref().unwrap_or(&self.time_format);
+                        out.push_str(&format.render(unix_timestamp(time)));
+                    }
+                }
+                Segment::Value(field, align) => {
+                    let value = Self::field_value(*field, name, result);
+                    match align {
+                        None => out.push_str(&value),
+                        Some(Align::Left(width)) => {
+                            write!(out, "{value:<width$}").ok();
+                        }
+                        Some(Align::Right(width)) => {
+                            write!(out, "{value:>width$}").ok();
+                        }
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    fn field_value(field: TemplateField, name: &str, result: &SearchResult) -> String {
+        let meta = result.metadata.as_ref();
+        let path_string =
+            |path: Option<&Path>| path.map(|p| p.display().to_string()).unwrap_or_default();
+
+        match field {
+            TemplateField::Name => name.to_string(),
+            TemplateField::Executable => result
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            TemplateField::Path => result.path.display().to_string(),
+            TemplateField::Dir => path_string(result.path.parent()),
+            TemplateField::Canonical => path_string(result.canonical_path.as_deref()),
+            TemplateField::Index => result.path_index.to_string(),
+            TemplateField::Version => result.version.clone().unwrap_or_default(),
+            TemplateField::Chain => result
+                .symlink_chain
+                .as_ref()
+                .map(|chain| {
+                    let hops: Vec<String> = chain
+                        .hops
+                        .iter()
+                        .map(|h| h.path.display().to_string())
+                        .collect();
+                    hops.join(" → ")
+                })
+                .unwrap_or_default(),
+            TemplateField::Shim => {
+                path_string(result.shim.as_ref().and_then(|s| s.target.as_deref()))
             }
-            day -= days_in_month_u64;
-            month += 1;
+            TemplateField::Kind => result
+                .file_kind
+                .as_ref()
+                .map(describe_file_kind)
+                .unwrap_or_default(),
+            TemplateField::Dev => meta.map(|m| m.dev.to_string()).unwrap_or_default(),
+            TemplateField::Ino => meta.map(|m| m.ino.to_string()).unwrap_or_default(),
+            TemplateField::Size => meta.map(|m| m.size.to_string()).unwrap_or_default(),
+            TemplateField::Mode => meta
+                .map(|m| format!("{:04o}", m.mode & 0o7777))
+                .unwrap_or_default(),
+            TemplateField::Perms => meta.map(|m| format_mode(m.mode)).unwrap_or_default(),
+            TemplateField::Uid => meta.map(|m| m.uid.to_string()).unwrap_or_default(),
+            TemplateField::Gid => meta.map(|m| m.gid.to_string()).unwrap_or_default(),
+            // Rendered as segments of their own
+            TemplateField::Winner | TemplateField::Mtime | TemplateField::Ctime => String::new(),
         }
+    }
+}
 
-        // Calculate time
-        let hour = (seconds_today / 3600) as u32;
-        let minute = ((seconds_today % 3600) / 60) as u32;
-        let second = (seconds_today % 60) as u32;
+/// Build the JSON record for a single query match
+#[must_use]
+pub fn result_to_json(name: &str, result: &SearchResult, is_winner: bool) -> JsonValue {
+    let executable = result
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned());
 
-        format!("{year}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}")
+    JsonValue::object([
+        ("type", JsonValue::from("match")),
+        ("name", JsonValue::from(name)),
+        ("executable", JsonValue::from(executable)),
+        ("path", JsonValue::path(&result.path)),
+        (
+            "canonical_path",
+            result
+                .canonical_path
+                .as_deref()
+                .map_or(JsonValue::Null, JsonValue::path),
+        ),
+        ("path_index", JsonValue::from(result.path_index)),
+        ("winner", JsonValue::from(is_winner)),
+        ("version", JsonValue::from(result.version.clone())),
+        (
+            "symlink_chain",
+            result
+                .symlink_chain
+                .as_ref()
+                .map_or(JsonValue::Null, chain_to_json),
+        ),
+        (
+            "shim",
+            result.shim.as_ref().map_or(JsonValue::Null, shim_to_json),
+        ),
+        (
+            "file_kind",
+            result
+                .file_kind
+                .as_ref()
+                .map_or(JsonValue::Null, file_kind_to_json),
+        ),
+        (
+            "metadata",
+            result
+                .metadata
+                .as_ref()
+                .map_or(JsonValue::Null, metadata_to_json),
+        ),
+        (
+            "score",
+            result.score.as_ref().map_or(JsonValue::Null, score_to_json),
+        ),
+    ])
+}
+
+/// Score breakdown for `--explain-score`; `gaps` is subtracted from the bonuses
+#[must_use]
+pub fn score_to_json(score: &FuzzyScore) -> JsonValue {
+    JsonValue::object([
+        ("total", JsonValue::from(score.total)),
+        ("matched", JsonValue::from(score.matched)),
+        ("contiguous", JsonValue::from(score.contiguous)),
+        ("boundary", JsonValue::from(score.boundary)),
+        ("basename", JsonValue::from(score.basename)),
+        ("exact", JsonValue::from(score.exact)),
+        ("gaps", JsonValue::from(score.gaps)),
+        ("positions", JsonValue::from(score.positions.clone())),
+    ])
+}
+
+/// Build the JSON record emitted when a queried name has no matches
+#[must_use]
+pub fn not_found_to_json(name: &str, suggestions: Option<&Suggestions>) -> JsonValue {
+    let mut fields = vec![
+        ("type", JsonValue::from("not_found")),
+        ("name", JsonValue::from(name)),
+    ];
+
+    if let Some(suggestions) = suggestions {
+        let near = suggestions.near.iter().map(|near| {
+            JsonValue::object([
+                ("name", JsonValue::from(near.name.as_str())),
+                ("distance", JsonValue::from(near.distance)),
+                ("path_index", JsonValue::from(near.path_index)),
+                ("path", JsonValue::path(&near.path)),
+            ])
+        });
+        let off_path = suggestions.off_path.iter().map(|found| {
+            JsonValue::object([
+                ("path", JsonValue::path(&found.path)),
+                ("dir", JsonValue::path(&found.dir)),
+                ("command", JsonValue::from(found.add_command())),
+            ])
+        });
+        fields.push(("suggestions", JsonValue::Array(near.collect())));
+        fields.push(("outside_path", JsonValue::Array(off_path.collect())));
     }
 
-    fn is_leap_year(year: u64) -> bool {
-        (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+    JsonValue::object(fields)
+}
+
+fn chain_to_json(chain: &SymlinkChain) -> JsonValue {
+    let hops = chain
+        .hops
+        .iter()
+        .map(|hop| {
+            JsonValue::object([
+                ("path", JsonValue::path(&hop.path)),
+                ("target", JsonValue::path(&hop.target)),
+                ("relative", JsonValue::from(hop.relative)),
+            ])
+        })
+        .collect();
+
+    JsonValue::object([
+        ("status", JsonValue::from(chain.status.as_str())),
+        ("hops", JsonValue::Array(hops)),
+    ])
+}
+
+fn shim_to_json(shim: &ShimResolution) -> JsonValue {
+    JsonValue::object([
+        ("manager", JsonValue::from(shim.kind.as_str())),
+        ("selector", JsonValue::from(shim.selector.clone())),
+        ("source", JsonValue::from(shim.source.clone())),
+        (
+            "target",
+            shim.target
+                .as_deref()
+                .map_or(JsonValue::Null, JsonValue::path),
+        ),
+    ])
+}
+
+fn file_kind_to_json(kind: &FileKind) -> JsonValue {
+    match kind {
+        FileKind::Elf(elf) => JsonValue::object([
+            ("type", JsonValue::from("elf")),
+            ("bits", JsonValue::from(u32::from(elf.bits))),
+            ("little_endian", JsonValue::from(elf.little_endian)),
+            ("machine", JsonValue::from(elf.machine.as_str())),
+            ("linkage", JsonValue::from(elf.linkage.as_str())),
+            (
+                "interpreter",
+                elf.interpreter
+                    .as_deref()
+                    .map_or(JsonValue::Null, JsonValue::path),
+            ),
+        ]),
+        FileKind::MachO(macho) => JsonValue::object([
+            ("type", JsonValue::from("macho")),
+            ("universal", JsonValue::from(macho.universal)),
+            (
+                "architectures",
+                JsonValue::from(macho.architectures.clone()),
+            ),
+        ]),
+        FileKind::Script(script) => JsonValue::object([
+            ("type", JsonValue::from("script")),
+            ("interpreter", JsonValue::from(script.interpreter.as_str())),
+            ("env_program", JsonValue::from(script.env_program.clone())),
+            (
+                "resolved",
+                script
+                    .resolved
+                    .as_deref()
+                    .map_or(JsonValue::Null, JsonValue::path),
+            ),
+        ]),
+        FileKind::Other => JsonValue::object([("type", JsonValue::from("other"))]),
     }
 }
+
+fn metadata_to_json(meta: &FileMetadata) -> JsonValue {
+    JsonValue::object([
+        ("dev", JsonValue::from(meta.dev)),
+        ("ino", JsonValue::from(meta.ino)),
+        ("size", JsonValue::from(meta.size)),
+        ("mode", JsonValue::from(meta.mode & 0o7777)),
+        ("uid", JsonValue::from(meta.uid)),
+        ("gid", JsonValue::from(meta.gid)),
+        ("mtime", JsonValue::from(meta.mtime.map(epoch_secs))),
+        ("ctime", JsonValue::from(meta.ctime.map(epoch_secs))),
+    ])
+}
+
+fn epoch_secs(time: SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
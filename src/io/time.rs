@@ -0,0 +1,320 @@
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::io::tz::TimeZone;
+
+/// Format used for timestamps when none is given
+pub const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// How timestamps are rendered, chosen with `--time-format` or `[display] time_format`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    /// `2024-02-29 23:30:05` in local time
+    #[default]
+    Local,
+    /// `2024-02-29T23:30:05+01:00`
+    Iso8601,
+    /// `2024-02-29 23:30:05+01:00`
+    Rfc3339,
+    /// `3 days ago`
+    Relative,
+    /// Seconds since the Unix epoch
+    Epoch,
+    /// Any `strftime`-style format, e.g. `%d.%m.%Y`
+    Custom(String),
+}
+
+impl TimeFormat {
+    /// Parse a format name or a custom format containing `%`
+    pub fn parse(input: &str) -> Result<TimeFormat, String> {
+        match input.trim().to_ascii_lowercase().as_str() {
+            "local" | "default" => Ok(TimeFormat::Local),
+            "iso8601" | "iso-8601" | "iso" => Ok(TimeFormat::Iso8601),
+            "rfc3339" | "rfc-3339" => Ok(TimeFormat::Rfc3339),
+            "relative" => Ok(TimeFormat::Relative),
+            "epoch" | "unix" => Ok(TimeFormat::Epoch),
+            _ if input.contains('%') => Ok(TimeFormat::Custom(input.to_string())),
+            _ => Err(format!(
+                "Invalid time format: {input} (expected local, iso8601, rfc3339, relative, epoch or a strftime format)"
+            )),
+        }
+    }
+
+    /// Render `timestamp` in local time
+    #[must_use]
+    pub fn render(&self, timestamp: i64) -> String {
+        let now = unix_timestamp(SystemTime::now());
+        self.render_at(&DateTime::local(timestamp), now)
+    }
+
+    /// Render `time`, measuring relative formats from `now`
+    #[must_use]
+    pub fn render_at(&self, time: &DateTime, now: i64) -> String {
+        match self {
+            TimeFormat::Local => time.format(DEFAULT_TIME_FORMAT),
+            TimeFormat::Iso8601 => time.format("%Y-%m-%dT%H:%M:%S%:z"),
+            TimeFormat::Rfc3339 => time.format("%Y-%m-%d %H:%M:%S%:z"),
+            TimeFormat::Relative => relative(now - time.timestamp),
+            TimeFormat::Epoch => time.timestamp.to_string(),
+            TimeFormat::Custom(format) => time.format(format),
+        }
+    }
+}
+
+/// `3 days ago` for positive ages, `in 2 hours` for timestamps in the future
+fn relative(age: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (7 * 86_400, "week"),
+        (86_400, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+    ];
+
+    let seconds = age.abs();
+    if seconds < 10 {
+        return "just now".to_string();
+    }
+
+    let (count, unit) = UNITS
+        .iter()
+        .find(|(size, _)| seconds >= *size)
+        .map_or((seconds, "second"), |(size, unit)| (seconds / size, *unit));
+    let plural = if count == 1 { "" } else { "s" };
+
+    if age < 0 {
+        format!("in {count} {unit}{plural}")
+    } else {
+        format!("{count} {unit}{plural} ago")
+    }
+}
+
+/// A broken-down calendar time at a fixed UTC offset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// Seconds since the Unix epoch
+    pub timestamp: i64,
+    pub year: i64,
+    /// 1-12
+    pub month: u32,
+    /// 1-31
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// 0 is Sunday
+    pub weekday: u32,
+    /// 1-366
+    pub yday: u32,
+    /// Seconds east of UTC
+    pub offset: i64,
+    /// Zone abbreviation such as `CET`, or empty if unknown
+    pub zone: String,
+}
+
+impl DateTime {
+    #[must_use]
+    pub fn utc(timestamp: i64) -> Self {
+        DateTime::with_offset(timestamp, 0, "UTC")
+    }
+
+    /// `timestamp` in the local time zone from `TZ` or `/etc/localtime`
+    #[must_use]
+    pub fn local(timestamp: i64) -> Self {
+        static LOCAL_ZONE: OnceLock<TimeZone> = OnceLock::new();
+        DateTime::in_zone(timestamp, LOCAL_ZONE.get_or_init(TimeZone::local))
+    }
+
+    #[must_use]
+    pub fn in_zone(timestamp: i64, zone: &TimeZone) -> Self {
+        let local_type = zone.local_type(timestamp);
+        DateTime::with_offset(timestamp, local_type.offset, &local_type.abbreviation)
+    }
+
+    #[must_use]
+    pub fn with_offset(timestamp: i64, offset: i64, zone: &str) -> Self {
+        let local = timestamp + offset;
+        let days = local.div_euclid(86_400);
+        let seconds = local.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+
+        DateTime {
+            timestamp,
+            year,
+            month,
+            day,
+            hour: field(seconds / 3600),
+            minute: field(seconds % 3600 / 60),
+            second: field(seconds % 60),
+            // 1970-01-01 was a Thursday
+            weekday: field((days + 4).rem_euclid(7)),
+            yday: field(days - days_from_civil(year, 1, 1) + 1),
+            offset,
+            zone: zone.to_string(),
+        }
+    }
+
+    /// Render with a `strftime`-style format string
+    ///
+    /// Supports `%Y %C %y %m %d %e %j %H %I %M %S %p %a %A %b %B %h %u %w %z %:z
+    /// %Z %s %F %T %D %R %n %t %%`. Unknown conversions are copied through as-is.
+    #[must_use]
+    pub fn format(&self, fmt: &str) -> String {
+        let mut out = String::with_capacity(fmt.len() + 16);
+        let mut chars = fmt.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                out.push(ch);
+                continue;
+            }
+
+            let Some(spec) = chars.next() else {
+                out.push('%');
+                break;
+            };
+
+            // `%:z` is `%z` with a colon, as in RFC 3339
+            if spec == ':' && chars.clone().next() == Some('z') {
+                chars.next();
+                self.write_offset(&mut out, ":");
+                continue;
+            }
+            self.write_conversion(&mut out, spec);
+        }
+
+        out
+    }
+
+    fn write_offset(&self, out: &mut String, separator: &str) {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let minutes = self.offset.abs() / 60;
+        write!(
+            out,
+            "{sign}{:02}{separator}{:02}",
+            minutes / 60,
+            minutes % 60
+        )
+        .ok();
+    }
+
+    fn write_conversion(&self, out: &mut String, spec: char) {
+        let hour12 = match self.hour % 12 {
+            0 => 12,
+            h => h,
+        };
+        let weekday = WEEKDAYS[self.weekday as usize % 7];
+        let month = MONTHS[(self.month as usize + 11) % 12];
+
+        match spec {
+            'Y' => write!(out, "{}", self.year),
+            'C' => write!(out, "{:02}", self.year.div_euclid(100)),
+            'y' => write!(out, "{:02}", self.year.rem_euclid(100)),
+            'm' => write!(out, "{:02}", self.month),
+            'd' => write!(out, "{:02}", self.day),
+            'e' => write!(out, "{:>2}", self.day),
+            'j' => write!(out, "{:03}", self.yday),
+            'H' => write!(out, "{:02}", self.hour),
+            'I' => write!(out, "{hour12:02}"),
+            'M' => write!(out, "{:02}", self.minute),
+            'S' => write!(out, "{:02}", self.second),
+            'p' => out.write_str(if self.hour < 12 { "AM" } else { "PM" }),
+            'a' => out.write_str(&weekday[..3]),
+            'A' => out.write_str(weekday),
+            'b' | 'h' => out.write_str(&month[..3]),
+            'B' => out.write_str(month),
+            'u' => write!(out, "{}", if self.weekday == 0 { 7 } else { self.weekday }),
+            'w' => write!(out, "{}", self.weekday),
+            'z' => {
+                self.write_offset(out, "");
+                Ok(())
+            }
+            'Z' => out.write_str(&self.zone),
+            's' => write!(out, "{}", self.timestamp),
+            'F' => write!(out, "{}-{:02}-{:02}", self.year, self.month, self.day),
+            'T' => write!(
+                out,
+                "{:02}:{:02}:{:02}",
+                self.hour, self.minute, self.second
+            ),
+            'D' => write!(
+                out,
+                "{:02}/{:02}/{:02}",
+                self.month,
+                self.day,
+                self.year.rem_euclid(100)
+            ),
+            'R' => write!(out, "{:02}:{:02}", self.hour, self.minute),
+            'n' => out.write_str("\n"),
+            't' => out.write_str("\t"),
+            '%' => out.write_str("%"),
+            other => write!(out, "%{other}"),
+        }
+        .ok();
+    }
+}
+
+/// Seconds since the Unix epoch, negative for times before it
+#[must_use]
+pub fn unix_timestamp(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_secs()).unwrap_or(i64::MAX),
+        Err(before) => -i64::try_from(before.duration().as_secs()).unwrap_or(i64::MAX),
+    }
+}
+
+/// A calendar field that is known to be small and non-negative
+fn field(value: i64) -> u32 {
+    u32::try_from(value).unwrap_or(0)
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian `(year, month, day)`
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = field(doy - (153 * mp + 2) / 5 + 1);
+    let month = field(if mp < 10 { mp + 3 } else { mp - 9 });
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
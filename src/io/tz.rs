@@ -0,0 +1,489 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::io::time::{civil_from_days, days_from_civil};
+
+/// Directories searched for named zones like `Europe/Berlin` when `TZDIR` is unset
+const ZONEINFO_DIRS: [&str; 3] = [
+    "/usr/share/zoneinfo",
+    "/usr/lib/zoneinfo",
+    "/usr/share/lib/zoneinfo",
+];
+
+/// Rule POSIX implies when a zone names a DST abbreviation but no dates
+const DEFAULT_DST_RULE: &str = "M3.2.0,M11.1.0";
+
+/// One local time type: an offset from UTC and its abbreviation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalType {
+    /// Seconds east of UTC
+    pub offset: i64,
+    pub is_dst: bool,
+    pub abbreviation: String,
+}
+
+/// A time zone loaded from `TZif` data or a POSIX `TZ` string
+///
+/// `TZif` transitions cover the past; the footer rule (a POSIX `TZ` string)
+/// covers every instant after the last transition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    transitions: Vec<i64>,
+    /// Index into `types` for each transition
+    transition_types: Vec<usize>,
+    types: Vec<LocalType>,
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    #[must_use]
+    pub fn utc() -> Self {
+        TimeZone::fixed(0, "UTC")
+    }
+
+    fn fixed(offset: i64, abbreviation: &str) -> Self {
+        TimeZone {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: vec![LocalType {
+                offset,
+                is_dst: false,
+                abbreviation: abbreviation.to_string(),
+            }],
+            rule: None,
+        }
+    }
+
+    /// The zone named by `TZ`, or `/etc/localtime` when `TZ` is unset
+    #[must_use]
+    pub fn local() -> Self {
+        match env::var("TZ") {
+            Ok(tz) => TimeZone::from_tz_var(&tz),
+            Err(_) => TimeZone::from_file(Path::new("/etc/localtime")).unwrap_or_else(Self::utc),
+        }
+    }
+
+    /// Interpret a `TZ` value the way the C library does
+    ///
+    /// An absolute path or zone name such as `Europe/Berlin` loads `TZif` data;
+    /// anything else is parsed as a POSIX rule like `CET-1CEST,M3.5.0,M10.5.0/3`.
+    /// Unusable values fall back to UTC.
+    #[must_use]
+    pub fn from_tz_var(tz: &str) -> Self {
+        let name = tz.strip_prefix(':').unwrap_or(tz);
+        if name.is_empty() {
+            return TimeZone::utc();
+        }
+
+        if name.starts_with('/') {
+            return TimeZone::from_file(Path::new(name)).unwrap_or_else(Self::utc);
+        }
+
+        if !name.split('/').any(|part| part == "..")
+            && let Some(zone) = zoneinfo_dirs()
+                .iter()
+                .find_map(|dir| TimeZone::from_file(&dir.join(name)))
+        {
+            return zone;
+        }
+
+        TimeZone::from_posix(name).unwrap_or_else(|_| Self::utc())
+    }
+
+    fn from_file(path: &Path) -> Option<Self> {
+        let data = fs::read(path).ok()?;
+        TimeZone::from_tzif(&data).ok()
+    }
+
+    /// Parse `TZif` data (RFC 8536), versions 1 through 4
+    pub fn from_tzif(data: &[u8]) -> Result<Self, String> {
+        let header = TzifHeader::parse(data)?;
+
+        if header.version == 0 {
+            let (zone, _) = parse_tzif_block(data, &header, 4)?;
+            return Ok(zone);
+        }
+
+        // Version 2+ repeats the data with 64-bit times after the legacy block
+        let rest = data
+            .get(header.block_len(4)..)
+            .ok_or_else(|| "Truncated TZif data".to_string())?;
+        let header64 = TzifHeader::parse(rest)?;
+        let (mut zone, used) = parse_tzif_block(rest, &header64, 8)?;
+
+        let footer = rest
+            .get(used..)
+            .and_then(|f| f.strip_prefix(b"\n"))
+            .and_then(|f| f.split(|&b| b == b'\n').next())
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+        // A footer that cannot be parsed only loses coverage of future instants
+        if let Ok(footer_zone) = TimeZone::from_posix(&footer) {
+            zone.rule = footer_zone.rule;
+            if zone.types.is_empty() {
+                zone.types = footer_zone.types;
+            }
+        }
+
+        if zone.types.is_empty() {
+            return Err("TZif data has no local time types".to_string());
+        }
+        Ok(zone)
+    }
+
+    /// Parse a POSIX `TZ` rule such as `EST5EDT,M3.2.0,M11.1.0` or `JST-9`
+    pub fn from_posix(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid TZ rule: {spec}");
+        let mut parser = PosixParser { rest: spec };
+
+        let std_name = parser.name().ok_or_else(invalid)?;
+        let std_offset = parser.offset().ok_or_else(invalid)?;
+        let standard = LocalType {
+            offset: std_offset,
+            is_dst: false,
+            abbreviation: std_name,
+        };
+
+        if parser.rest.is_empty() {
+            return Ok(TimeZone {
+                transitions: Vec::new(),
+                transition_types: Vec::new(),
+                types: vec![standard],
+                rule: None,
+            });
+        }
+
+        let dst_name = parser.name().ok_or_else(invalid)?;
+        let dst_offset = if parser.rest.starts_with(',') || parser.rest.is_empty() {
+            std_offset + 3600
+        } else {
+            parser.offset().ok_or_else(invalid)?
+        };
+
+        let rules = parser.rest.strip_prefix(',').unwrap_or(DEFAULT_DST_RULE);
+        let (start, end) = rules.split_once(',').ok_or_else(invalid)?;
+        let daylight = LocalType {
+            offset: dst_offset,
+            is_dst: true,
+            abbreviation: dst_name,
+        };
+
+        Ok(TimeZone {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: vec![standard.clone()],
+            rule: Some(PosixRule {
+                start: TransitionDate::parse(start).ok_or_else(invalid)?,
+                end: TransitionDate::parse(end).ok_or_else(invalid)?,
+                standard,
+                daylight,
+            }),
+        })
+    }
+
+    /// The local time type in effect at `timestamp`
+    #[must_use]
+    pub fn local_type(&self, timestamp: i64) -> &LocalType {
+        let after_last = self
+            .transitions
+            .last()
+            .is_none_or(|&last| timestamp >= last);
+        if after_last && let Some(rule) = &self.rule {
+            return rule.local_type(timestamp);
+        }
+
+        match self.transitions.partition_point(|&t| t <= timestamp) {
+            // Before the first transition the first local time type applies
+            0 => &self.types[0],
+            n => &self.types[self.transition_types[n - 1]],
+        }
+    }
+}
+
+fn zoneinfo_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var("TZDIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    dirs.extend(ZONEINFO_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+struct TzifHeader {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl TzifHeader {
+    const LEN: usize = 44;
+
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < Self::LEN || !data.starts_with(b"TZif") {
+            return Err("Not TZif data".to_string());
+        }
+
+        let count = |index: usize| {
+            let at = 20 + index * 4;
+            let raw: [u8; 4] = data[at..at + 4].try_into().unwrap_or_default();
+            u32::from_be_bytes(raw) as usize
+        };
+
+        Ok(TzifHeader {
+            version: match data[4] {
+                0 => 0,
+                v => v.saturating_sub(b'0'),
+            },
+            isutcnt: count(0),
+            isstdcnt: count(1),
+            leapcnt: count(2),
+            timecnt: count(3),
+            typecnt: count(4),
+            charcnt: count(5),
+        })
+    }
+
+    /// Bytes taken by the header and data block when times are `time_size` wide
+    fn block_len(&self, time_size: usize) -> usize {
+        Self::LEN
+            + self.timecnt * time_size
+            + self.timecnt
+            + self.typecnt * 6
+            + self.charcnt
+            + self.leapcnt * (time_size + 4)
+            + self.isstdcnt
+            + self.isutcnt
+    }
+}
+
+/// Decode one `TZif` data block, returning the zone and the bytes consumed
+fn parse_tzif_block(
+    data: &[u8],
+    header: &TzifHeader,
+    time_size: usize,
+) -> Result<(TimeZone, usize), String> {
+    let len = header.block_len(time_size);
+    if data.len() < len || header.typecnt == 0 {
+        return Err("Truncated TZif data".to_string());
+    }
+
+    let mut at = TzifHeader::LEN;
+    let mut take = |n: usize| {
+        let slice = &data[at..at + n];
+        at += n;
+        slice
+    };
+
+    let transitions: Vec<i64> = take(header.timecnt * time_size)
+        .chunks_exact(time_size)
+        .map(|raw| match time_size {
+            8 => i64::from_be_bytes(raw.try_into().unwrap_or_default()),
+            _ => i64::from(i32::from_be_bytes(raw.try_into().unwrap_or_default())),
+        })
+        .collect();
+    let transition_types: Vec<usize> = take(header.timecnt)
+        .iter()
+        .map(|&t| usize::from(t))
+        .collect();
+    let raw_types = take(header.typecnt * 6).to_vec();
+    let designations = take(header.charcnt);
+
+    let types = raw_types
+        .chunks_exact(6)
+        .map(|raw| {
+            let offset = i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]);
+            let start = usize::from(raw[5]).min(designations.len());
+            let abbreviation = designations[start..]
+                .split(|&b| b == 0)
+                .next()
+                .unwrap_or_default();
+            LocalType {
+                offset: i64::from(offset),
+                is_dst: raw[4] != 0,
+                abbreviation: String::from_utf8_lossy(abbreviation).into_owned(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if transition_types.iter().any(|&t| t >= types.len()) {
+        return Err("TZif transition refers to a missing local time type".to_string());
+    }
+
+    let zone = TimeZone {
+        transitions,
+        transition_types,
+        types,
+        rule: None,
+    };
+    Ok((zone, len))
+}
+
+/// Daylight saving rule from a POSIX `TZ` string
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PosixRule {
+    start: TransitionDate,
+    end: TransitionDate,
+    standard: LocalType,
+    daylight: LocalType,
+}
+
+impl PosixRule {
+    fn local_type(&self, timestamp: i64) -> &LocalType {
+        let (year, _, _) = civil_from_days((timestamp + self.standard.offset).div_euclid(86_400));
+
+        // The start is given in standard time, the end in daylight time
+        let start = self.start.local_seconds(year) - self.standard.offset;
+        let end = self.end.local_seconds(year) - self.daylight.offset;
+
+        let in_dst = if start < end {
+            timestamp >= start && timestamp < end
+        } else {
+            // Southern hemisphere: daylight time spans the new year
+            !(timestamp >= end && timestamp < start)
+        };
+
+        if in_dst {
+            &self.daylight
+        } else {
+            &self.standard
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateRule {
+    /// `Jn`: day 1-365, never counting February 29
+    Julian(i64),
+    /// `n`: day 0-365, counting February 29
+    ZeroBased(i64),
+    /// `Mm.w.d`: day `d` (0 = Sunday) of week `w` (5 = last) of month `m`
+    MonthWeekDay(u32, i64, i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TransitionDate {
+    rule: DateRule,
+    /// Local time of day in seconds, which may be negative or exceed a day
+    time: i64,
+}
+
+impl TransitionDate {
+    fn parse(input: &str) -> Option<Self> {
+        let (date, time) = match input.split_once('/') {
+            Some((date, time)) => (date, parse_hms(time)?),
+            None => (input, 7200),
+        };
+
+        let rule = if let Some(day) = date.strip_prefix('J') {
+            DateRule::Julian(day.parse().ok().filter(|d| (1..=365).contains(d))?)
+        } else if let Some(spec) = date.strip_prefix('M') {
+            let mut parts = spec.split('.');
+            let month: u32 = parts.next()?.parse().ok()?;
+            let week: i64 = parts.next()?.parse().ok()?;
+            let day: i64 = parts.next()?.parse().ok()?;
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || !(0..=6).contains(&day) {
+                return None;
+            }
+            DateRule::MonthWeekDay(month, week, day)
+        } else {
+            DateRule::ZeroBased(date.parse().ok().filter(|d| (0..=365).contains(d))?)
+        };
+
+        Some(TransitionDate { rule, time })
+    }
+
+    /// Seconds from the epoch to this date in `year`, in zone-local time
+    fn local_seconds(&self, year: i64) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        let leap = is_leap_year(year);
+
+        let day = match self.rule {
+            DateRule::Julian(n) => jan1 + n - 1 + i64::from(leap && n >= 60),
+            DateRule::ZeroBased(n) => jan1 + n,
+            DateRule::MonthWeekDay(month, week, weekday) => {
+                let first = days_from_civil(year, month, 1);
+                let first_weekday = (first + 4).rem_euclid(7);
+                let mut day = first + (weekday - first_weekday).rem_euclid(7) + (week - 1) * 7;
+
+                let next_month = if month == 12 {
+                    days_from_civil(year + 1, 1, 1)
+                } else {
+                    days_from_civil(year, month + 1, 1)
+                };
+                while day >= next_month {
+                    day -= 7;
+                }
+                day
+            }
+        };
+
+        day * 86_400 + self.time
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// `[+-]hh[:mm[:ss]]` in seconds
+fn parse_hms(input: &str) -> Option<i64> {
+    let (sign, digits) = match input.as_bytes().first()? {
+        b'-' => (-1, &input[1..]),
+        b'+' => (1, &input[1..]),
+        _ => (1, input),
+    };
+
+    let mut parts = digits.split(':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next().map_or(Some(0), |m| m.parse().ok())?;
+    let seconds: i64 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if hours > 167 || minutes > 59 || seconds > 59 || parts.next().is_some() {
+        return None;
+    }
+
+    Some(sign * (hours * 3600 + minutes * 60 + seconds))
+}
+
+struct PosixParser<'a> {
+    rest: &'a str,
+}
+
+impl PosixParser<'_> {
+    /// An abbreviation: three or more letters, or anything quoted in `<...>`
+    fn name(&mut self) -> Option<String> {
+        if let Some(quoted) = self.rest.strip_prefix('<') {
+            let (name, rest) = quoted.split_once('>')?;
+            self.rest = rest;
+            return (name.len() >= 3).then(|| name.to_string());
+        }
+
+        let len = self
+            .rest
+            .find(|ch: char| !ch.is_ascii_alphabetic())
+            .unwrap_or(self.rest.len());
+        if len < 3 {
+            return None;
+        }
+        let (name, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(name.to_string())
+    }
+
+    /// A POSIX offset, which counts hours west of UTC, as seconds east of UTC
+    fn offset(&mut self) -> Option<i64> {
+        let len = self
+            .rest
+            .find(|ch: char| !(ch.is_ascii_digit() || matches!(ch, ':' | '+' | '-')))
+            .unwrap_or(self.rest.len());
+        let (offset, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        parse_hms(offset).map(|seconds| -seconds)
+    }
+}
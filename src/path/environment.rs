@@ -0,0 +1,333 @@
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::runtime::{EnvironmentsConfig, get_cache_dir};
+use crate::io::atomic_file::AtomicFile;
+use crate::search::explain::{CandidateStatus, classify};
+
+/// How long a login shell may take to print its `PATH`
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// Marks the `PATH` line among anything the login scripts print
+const LOGIN_MARKER: &str = "__WHI_LOGIN_PATH__";
+/// Nested sudoers includes deeper than this are ignored, as sudo does
+const MAX_INCLUDE_DEPTH: usize = 128;
+
+/// A context that runs commands with a `PATH` of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Cron,
+    Systemd,
+    Sudo,
+    Login,
+}
+
+impl Environment {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Environment::Cron => "cron",
+            Environment::Systemd => "systemd",
+            Environment::Sudo => "sudo",
+            Environment::Login => "login",
+        }
+    }
+
+    /// The `PATH` commands get in this context
+    ///
+    /// sudo keeps `current_path` when no `secure_path` is set. The login
+    /// shell's `PATH` is captured once and cached until `whi cache clear`.
+    pub fn path(self, config: &EnvironmentsConfig, current_path: &str) -> Result<String, String> {
+        match self {
+            Environment::Cron => Ok(config.cron_path.clone()),
+            Environment::Systemd => Ok(config.systemd_path.clone()),
+            Environment::Sudo => {
+                let secure_path = sudo_secure_path(&config.sudoers)
+                    .map_err(|e| format!("{e} (set [environments] sudoers to a readable copy)"))?;
+                Ok(secure_path.unwrap_or_else(|| current_path.to_string()))
+            }
+            Environment::Login => login_path(),
+        }
+    }
+}
+
+/// How one name resolves in an environment compared to the current shell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvResolution {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub current: Option<PathBuf>,
+}
+
+impl EnvResolution {
+    #[must_use]
+    pub fn differs(&self) -> bool {
+        self.path != self.current
+    }
+}
+
+/// Resolve `name` against both `env_path` and `current_path`
+#[must_use]
+pub fn compare_resolution(name: &str, env_path: &str, current_path: &str) -> EnvResolution {
+    EnvResolution {
+        name: name.to_string(),
+        path: winner(name, env_path),
+        current: winner(name, current_path),
+    }
+}
+
+fn winner(name: &str, path: &str) -> Option<PathBuf> {
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join(name))
+        .find(|candidate| classify(candidate) == CandidateStatus::Executable)
+}
+
+/// The last `secure_path` set by a plain `Defaults` line in `sudoers` or the
+/// files it includes, or `None` if it is never set or is negated
+pub fn sudo_secure_path(sudoers: &Path) -> Result<Option<String>, String> {
+    let mut secure_path = None;
+    read_sudoers(sudoers, 0, &mut secure_path)?;
+    Ok(secure_path)
+}
+
+fn read_sudoers(file: &Path, depth: usize, secure_path: &mut Option<String>) -> Result<(), String> {
+    let content =
+        fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {e}", file.display()))?;
+    let base = file.parent().unwrap_or(Path::new("/"));
+
+    for line in logical_lines(&content) {
+        let line = line.trim();
+
+        if let Some((directive, target)) = include_directive(line) {
+            if depth >= MAX_INCLUDE_DEPTH {
+                continue;
+            }
+            // Like sudo, skip an include that cannot be read and keep going
+            let target = base.join(target);
+            if directive.ends_with("dir") {
+                for included in included_dir_files(&target) {
+                    read_sudoers(&included, depth + 1, secure_path).ok();
+                }
+            } else {
+                read_sudoers(&target, depth + 1, secure_path).ok();
+            }
+            continue;
+        }
+
+        let Some(settings) = line
+            .strip_prefix("Defaults")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+        else {
+            continue;
+        };
+
+        for setting in split_settings(strip_comment(settings)) {
+            let setting = setting.trim();
+            if setting == "!secure_path" {
+                *secure_path = None;
+            } else if let Some(value) = setting
+                .strip_prefix("secure_path")
+                .and_then(|rest| rest.trim_start().strip_prefix('='))
+            {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                *secure_path = Some(value.to_string());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lines with backslash continuations joined
+fn logical_lines(content: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        if let Some(continued) = line.strip_suffix('\\') {
+            current.push_str(continued);
+        } else {
+            current.push_str(line);
+            lines.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// `@include`, `@includedir` and their older `#` spellings
+fn include_directive(line: &str) -> Option<(&str, &str)> {
+    let (directive, target) = line.split_once(char::is_whitespace)?;
+    match directive {
+        "@include" | "#include" | "@includedir" | "#includedir" => {
+            Some((directive, target.trim().trim_matches('"')))
+        }
+        _ => None,
+    }
+}
+
+/// Files sudo reads from an included directory: sorted, skipping names with
+/// a `.` or ending in `~`
+fn included_dir_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.contains('.') && !name.ends_with('~')
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (pos, ch) in line.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..pos],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Split `a, b="x,y", c` on commas outside quotes
+fn split_settings(settings: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+
+    for (pos, ch) in settings.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                parts.push(&settings[start..pos]);
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&settings[start..]);
+    parts
+}
+
+/// The login shell's `PATH`, from the cache or by starting `$SHELL -l`
+fn login_path() -> Result<String, String> {
+    let shell = env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    let cache = get_cache_dir()?.join("login_path");
+
+    if let Ok(content) = fs::read_to_string(&cache)
+        && let Some((cached_shell, path)) = content.trim_end().split_once('\t')
+        && cached_shell == shell
+    {
+        return Ok(path.to_string());
+    }
+
+    let path = capture_login_path(&shell)?;
+
+    if let Some(parent) = cache.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    if let Ok(mut file) = AtomicFile::new(&cache)
+        && file
+            .write_all(format!("{shell}\t{path}\n").as_bytes())
+            .is_ok()
+    {
+        file.commit().ok();
+    }
+
+    Ok(path)
+}
+
+/// Start `shell` as a login shell in a near-empty environment and read the
+/// `PATH` it ends up with
+fn capture_login_path(shell: &str) -> Result<String, String> {
+    let mut command = Command::new(shell);
+    command.env_clear();
+    for key in ["HOME", "USER", "LOGNAME", "SHELL", "LANG"] {
+        if let Some(value) = env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+
+    let mut child = command
+        .args([
+            "-l",
+            "-c",
+            &format!("printf '{LOGIN_MARKER}%s\\n' \"$PATH\""),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start login shell {shell}: {e}"))?;
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take().ok_or("Login shell has no stdout")?;
+    thread::spawn(move || {
+        // Stop at the marker: an agent started from the profile can hold
+        // stdout open long after the shell is gone
+        let mut reader = BufReader::new(stdout);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).is_ok_and(|n| n > 0) {
+            let text = String::from_utf8_lossy(&line);
+            if let Some(path) = text.trim_end_matches('\n').strip_prefix(LOGIN_MARKER) {
+                tx.send(path.to_string()).ok();
+                return;
+            }
+            line.clear();
+        }
+    });
+
+    let deadline = Instant::now() + LOGIN_TIMEOUT;
+    let reported = rx.recv_timeout(LOGIN_TIMEOUT);
+    // The shell exits right after printing the marker; reap it, or stop it
+    // if it hangs on the way out
+    while matches!(child.try_wait(), Ok(None)) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    child.kill().ok();
+    child.wait().ok();
+
+    match reported {
+        Ok(path) if !path.is_empty() => Ok(path),
+        Err(RecvTimeoutError::Timeout) => Err(format!(
+            "Login shell {shell} did not finish within {} seconds",
+            LOGIN_TIMEOUT.as_secs()
+        )),
+        _ => Err(format!("Login shell {shell} did not report a PATH")),
+    }
+}
+
+/// Forget the cached login shell `PATH`
+pub fn clear_login_path() -> Result<(), String> {
+    let cache = get_cache_dir()?.join("login_path");
+    if cache.exists() {
+        fs::remove_file(&cache).map_err(|e| format!("Failed to remove login PATH cache: {e}"))?;
+    }
+    Ok(())
+}
@@ -1 +1,209 @@
 pub use super::resolve::FuzzyMatcher;
+
+/// Every matched query character
+const MATCH: i64 = 16;
+/// A character matched right after the previous one
+const CONTIGUOUS: i64 = 12;
+/// A character at the start of the text or right after `/`
+const SEGMENT_START: i64 = 10;
+/// A character right after `-`, `_`, `.` or a space, or a camelCase hump
+const WORD_START: i64 = 8;
+/// A character inside the final path component
+const BASENAME: i64 = 4;
+/// The final path component is exactly the last query part
+const EXACT_BASENAME: i64 = 100;
+/// Opening a gap between two matched characters
+const GAP_START: i64 = 3;
+/// Each skipped character after the first in a gap
+const GAP_EXTEND: i64 = 1;
+
+/// How well a fuzzy query matched, broken into its parts so rankings can be
+/// explained; `total` is the sum of the bonuses minus `gaps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyScore {
+    pub total: i64,
+    pub matched: i64,
+    pub contiguous: i64,
+    pub boundary: i64,
+    pub basename: i64,
+    pub exact: i64,
+    pub gaps: i64,
+    /// Character offsets of the matched query characters
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyScore {
+    /// One-line breakdown such as `212 = 80 matched + 36 contiguous - 4 gaps`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("{} = {} matched", self.total, self.matched)];
+        for (value, label) in [
+            (self.contiguous, "contiguous"),
+            (self.boundary, "boundary"),
+            (self.basename, "basename"),
+            (self.exact, "exact basename"),
+        ] {
+            if value != 0 {
+                parts.push(format!("+ {value} {label}"));
+            }
+        }
+        if self.gaps != 0 {
+            parts.push(format!("- {} gaps", self.gaps));
+        }
+        parts.join(" ")
+    }
+}
+
+/// Score the best alignment of `query` as a subsequence of `text`
+///
+/// Whitespace in the query is ignored. Returns `None` if the characters do
+/// not appear in order.
+#[must_use]
+pub fn score_subsequence(query: &str, text: &str, case_sensitive: bool) -> Option<FuzzyScore> {
+    let fold = |ch: char| {
+        if case_sensitive {
+            ch
+        } else {
+            ch.to_lowercase().next().unwrap_or(ch)
+        }
+    };
+    let needle: Vec<char> = query
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(fold)
+        .collect();
+    let original: Vec<char> = text.chars().collect();
+    let haystack: Vec<char> = original.iter().copied().map(fold).collect();
+
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+
+    let basename_start = original
+        .iter()
+        .rposition(|&ch| ch == '/')
+        .map_or(0, |slash| slash + 1);
+    let char_bonus: Vec<i64> = (0..original.len())
+        .map(|pos| {
+            boundary_bonus(&original, pos) + if pos >= basename_start { BASENAME } else { 0 }
+        })
+        .collect();
+
+    let positions = best_alignment(&needle, &haystack, &char_bonus)?;
+
+    let last_part = query.split_whitespace().last().unwrap_or_default();
+    let basename: String = original[basename_start..].iter().collect();
+    let exact = if case_sensitive {
+        basename == last_part
+    } else {
+        basename.to_lowercase() == last_part.to_lowercase()
+    };
+
+    Some(breakdown(&original, positions, basename_start, exact))
+}
+
+fn boundary_bonus(text: &[char], pos: usize) -> i64 {
+    let Some(prev) = pos.checked_sub(1).map(|p| text[p]) else {
+        return SEGMENT_START;
+    };
+    match prev {
+        '/' => SEGMENT_START,
+        '-' | '_' | '.' | ' ' => WORD_START,
+        _ if prev.is_lowercase() && text[pos].is_uppercase() => WORD_START,
+        _ => 0,
+    }
+}
+
+/// Dynamic programming over `needle[i]` placed at `haystack[j]`, keeping the
+/// best predecessor so the winning positions can be recovered
+fn best_alignment(needle: &[char], haystack: &[char], char_bonus: &[i64]) -> Option<Vec<usize>> {
+    const NONE: i64 = i64::MIN / 4;
+    let (m, n) = (needle.len(), haystack.len());
+    let mut best = vec![vec![NONE; n]; m];
+    let mut from = vec![vec![usize::MAX; n]; m];
+
+    for j in 0..n {
+        if haystack[j] == needle[0] {
+            best[0][j] = MATCH + char_bonus[j];
+        }
+    }
+
+    for i in 1..m {
+        // Best score for a gap ending just before `j`, with where it started
+        let mut gap = (NONE, usize::MAX);
+        for j in 1..n {
+            if j >= 2 {
+                let opened = best[i - 1][j - 2] - GAP_START;
+                let extended = gap.0 - GAP_EXTEND;
+                gap = if opened >= extended {
+                    (opened, j - 2)
+                } else {
+                    (extended, gap.1)
+                };
+            }
+            if haystack[j] != needle[i] {
+                continue;
+            }
+
+            let contiguous = best[i - 1][j - 1] + CONTIGUOUS;
+            let (prev_score, prev) = if contiguous >= gap.0 {
+                (contiguous, j - 1)
+            } else {
+                gap
+            };
+            if prev_score > NONE / 2 {
+                best[i][j] = prev_score + MATCH + char_bonus[j];
+                from[i][j] = prev;
+            }
+        }
+    }
+
+    let (mut j, score) = best[m - 1]
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    if score <= NONE / 2 {
+        return None;
+    }
+
+    let mut positions = vec![0; m];
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some(positions)
+}
+
+fn breakdown(
+    text: &[char],
+    positions: Vec<usize>,
+    basename_start: usize,
+    exact: bool,
+) -> FuzzyScore {
+    let count = |n: usize| i64::try_from(n).unwrap_or(i64::MAX / 64);
+    let mut score = FuzzyScore {
+        total: 0,
+        matched: MATCH * count(positions.len()),
+        contiguous: 0,
+        boundary: positions.iter().map(|&p| boundary_bonus(text, p)).sum(),
+        basename: BASENAME * count(positions.iter().filter(|&&p| p >= basename_start).count()),
+        exact: if exact { EXACT_BASENAME } else { 0 },
+        gaps: 0,
+        positions: Vec::new(),
+    };
+
+    for pair in positions.windows(2) {
+        let skipped = pair[1] - pair[0] - 1;
+        if skipped == 0 {
+            score.contiguous += CONTIGUOUS;
+        } else {
+            score.gaps += GAP_START + GAP_EXTEND * (count(skipped) - 1);
+        }
+    }
+
+    score.total = score.matched + score.contiguous + score.boundary + score.basename + score.exact
+        - score.gaps;
+    score.positions = positions;
+    score
+}
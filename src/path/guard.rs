@@ -61,6 +61,19 @@ impl PathGuard {
         result
     }
 
+    /// Protected binaries whose winning copy in `path_str` lives in `dir`
+    ///
+    /// These are the binaries that would make `PathGuard` re-append `dir` if an
+    /// operation removed it.
+    #[must_use]
+    pub fn binaries_protected_by(&self, path_str: &str, dir: &Path) -> Vec<&str> {
+        self.protected_binaries
+            .iter()
+            .filter(|binary| Self::find_binary_dir(path_str, binary).is_some_and(|d| d == dir))
+            .map(String::as_str)
+            .collect()
+    }
+
     /// Find directories containing protected binaries in current `PATH`
     ///
     /// Silently ignores binaries that are not found - no crashes if binary doesn't exist
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::search::index::{EntryKind, ExecutableIndex};
+
+/// How serious a `PATH` health finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// A single problem found while checking `PATH`
+///
+/// `code` is stable across releases so scripts can match on it. `index` is the
+/// 1-based position the entry has in whi's view of `PATH` (empty and invalid
+/// components are skipped, matching `whi` listings), so `fix` commands that
+/// reference it can be run as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub index: Option<usize>,
+    pub entry: Option<String>,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    #[must_use]
+    pub fn new(severity: Severity, code: &'static str, message: impl Into<String>) -> Self {
+        Finding {
+            severity,
+            code,
+            index: None,
+            entry: None,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    #[must_use]
+    pub fn at(mut self, index: usize, entry: &str) -> Self {
+        self.index = Some(index);
+        self.entry = Some(entry.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn entry(mut self, entry: &str) -> Self {
+        self.entry = Some(entry.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn fix(mut self, command: impl Into<String>) -> Self {
+        self.fix = Some(command.into());
+        self
+    }
+}
+
+const SHELL_METACHARACTERS: &[char] = &['$', '`', ';', '&', '|', '<', '>', '(', ')', '{', '}'];
+
+/// Check a raw `PATH` value for problems that do not depend on whi's configuration
+///
+/// Unlike [`crate::path::searcher::PathSearcher::new`], nothing is printed; every
+/// problem is returned as a [`Finding`].
+pub fn check_path_entries(path_var: &str, index: &mut ExecutableIndex) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut empty_components = 0;
+    let mut seen_exact: HashMap<&str, usize> = HashMap::new();
+    let mut seen_canonical: HashMap<PathBuf, usize> = HashMap::new();
+    let mut position = 0;
+
+    for raw in path_var.split(':') {
+        if raw.is_empty() {
+            empty_components += 1;
+            continue;
+        }
+
+        if raw.chars().any(|ch| ch.is_control() && ch != '\t') {
+            findings.push(
+                Finding::new(
+                    Severity::Error,
+                    "invalid-entry",
+                    "Entry contains control characters and is ignored by whi",
+                )
+                .entry(&raw.escape_debug().to_string())
+                .fix("whi clean"),
+            );
+            continue;
+        }
+
+        position += 1;
+
+        if let Some(&first) = seen_exact.get(raw) {
+            findings.push(
+                Finding::new(
+                    Severity::Warning,
+                    "duplicate-entry",
+                    format!("Duplicate of entry [{first}]"),
+                )
+                .at(position, raw)
+                .fix("whi clean"),
+            );
+            continue;
+        }
+        seen_exact.insert(raw, position);
+
+        check_entry(raw, position, &mut seen_canonical, index, &mut findings);
+    }
+
+    if empty_components > 0 {
+        findings.insert(
+            0,
+            Finding::new(
+                Severity::Warning,
+                "empty-component",
+                format!(
+                    "{empty_components} empty component(s); some shells treat them as the current directory"
+                ),
+            )
+            .fix("whi clean"),
+        );
+    }
+
+    findings
+}
+
+/// Checks for a single entry that is not an exact duplicate of an earlier one
+fn check_entry(
+    raw: &str,
+    position: usize,
+    seen_canonical: &mut HashMap<PathBuf, usize>,
+    index: &mut ExecutableIndex,
+    findings: &mut Vec<Finding>,
+) {
+    let delete = format!("whi delete {position}");
+
+    if let Some(ch) = raw.chars().find(|ch| SHELL_METACHARACTERS.contains(ch)) {
+        findings.push(
+            Finding::new(
+                Severity::Warning,
+                "shell-metacharacter",
+                format!("Entry contains shell metacharacter '{ch}'"),
+            )
+            .at(position, raw)
+            .fix(delete.clone()),
+        );
+    }
+
+    if !raw.starts_with('/') {
+        findings.push(
+            Finding::new(
+                Severity::Warning,
+                "relative-entry",
+                "Relative entry depends on the current directory",
+            )
+            .at(position, raw)
+            .fix(delete.clone()),
+        );
+    }
+
+    let dir = Path::new(raw);
+    let Ok(metadata) = fs::metadata(dir) else {
+        findings.push(
+            Finding::new(Severity::Warning, "missing-dir", "Directory does not exist")
+                .at(position, raw)
+                .fix(delete),
+        );
+        return;
+    };
+
+    if !metadata.is_dir() {
+        findings.push(
+            Finding::new(
+                Severity::Error,
+                "not-a-directory",
+                "Entry is not a directory",
+            )
+            .at(position, raw)
+            .fix(delete),
+        );
+        return;
+    }
+
+    if let Ok(canonical) = fs::canonicalize(dir) {
+        if let Some(&first) = seen_canonical.get(&canonical) {
+            findings.push(
+                Finding::new(
+                    Severity::Info,
+                    "canonical-duplicate",
+                    format!("Resolves to {} like entry [{first}]", canonical.display()),
+                )
+                .at(position, raw)
+                .fix("whi clean --canonical"),
+            );
+            return;
+        }
+        seen_canonical.insert(canonical, position);
+    }
+
+    let has_executables = index
+        .listing(dir)
+        .iter()
+        .any(|entry| entry.kind == EntryKind::Executable);
+    if !has_executables {
+        findings.push(
+            Finding::new(
+                Severity::Info,
+                "no-executables",
+                "Directory provides no executables",
+            )
+            .at(position, raw)
+            .fix(delete),
+        );
+    }
+}
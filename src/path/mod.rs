@@ -1,8 +1,14 @@
 pub mod diff;
+pub mod environment;
 pub mod file;
 pub mod fuzzy;
 pub mod guard;
+pub mod health;
+pub mod order;
+pub mod pattern;
+pub mod pins;
 pub mod resolve;
 pub mod searcher;
+pub mod source;
 
 pub use searcher::PathSearcher;
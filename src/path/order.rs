@@ -0,0 +1,328 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::io::line_utils::{is_section_header, strip_inline_comment};
+use crate::path::pattern::Regex;
+use crate::path::resolve::expand_tilde;
+
+/// One side of an ordering rule: a directory, or a glob such as
+/// `/nix/store/*` for everything under it
+#[derive(Debug, Clone)]
+pub struct EntryPattern {
+    text: String,
+    matcher: EntryMatcher,
+}
+
+#[derive(Debug, Clone)]
+enum EntryMatcher {
+    Exact(PathBuf),
+    Glob(Regex),
+}
+
+impl EntryPattern {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        let expanded = expand_tilde(text);
+        let matcher = if expanded.contains(['*', '?', '[', '{']) {
+            EntryMatcher::Glob(Regex::from_glob(&expanded, true))
+        } else {
+            EntryMatcher::Exact(PathBuf::from(expanded))
+        };
+        EntryPattern {
+            text: text.to_string(),
+            matcher,
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, entry: &Path) -> bool {
+        match &self.matcher {
+            EntryMatcher::Exact(path) => entry == path,
+            EntryMatcher::Glob(glob) => glob.is_match(&entry.to_string_lossy()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// `!order.before`: every subject entry comes before every anchor entry
+    Before,
+    /// `!order.after`: every subject entry comes after every anchor entry
+    After,
+    /// `!order.first`: subject entries come before all other entries
+    First,
+    /// `!order.last`: subject entries come after all other entries
+    Last,
+}
+
+/// A rule from `~/.whi/order_rules`
+#[derive(Debug, Clone)]
+pub struct OrderRule {
+    pub kind: RuleKind,
+    pub subject: EntryPattern,
+    /// The entries `subject` is ordered against; `None` for first and last
+    pub anchor: Option<EntryPattern>,
+    /// Line in the rules file, for messages
+    pub line: usize,
+}
+
+impl fmt::Display for OrderRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let subject = &self.subject.text;
+        match (&self.kind, &self.anchor) {
+            (RuleKind::Before, Some(anchor)) => write!(f, "{subject} before {}", anchor.text),
+            (RuleKind::After, Some(anchor)) => write!(f, "{subject} after {}", anchor.text),
+            (RuleKind::First, _) => write!(f, "{subject} first"),
+            _ => write!(f, "{subject} last"),
+        }
+    }
+}
+
+/// Parse an order rules file
+///
+/// ```text
+/// !order.before
+/// ~/.cargo/bin /usr/bin
+/// !order.after
+/// /opt/homebrew/bin /usr/local/bin
+/// !order.last
+/// /nix/store/*
+/// ```
+pub fn parse_order_rules(content: &str) -> Result<Vec<OrderRule>, String> {
+    let mut rules = Vec::new();
+    let mut kind = None;
+
+    for (number, line) in content.lines().enumerate() {
+        let line_number = number + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line = strip_inline_comment(trimmed);
+
+        if is_section_header(line) {
+            kind = Some(match line {
+                "!order.before" => RuleKind::Before,
+                "!order.after" => RuleKind::After,
+                "!order.first" => RuleKind::First,
+                "!order.last" => RuleKind::Last,
+                other => {
+                    return Err(format!(
+                        "Unknown directive {other} on line {line_number} (expected !order.before, !order.after, !order.first or !order.last)"
+                    ));
+                }
+            });
+            continue;
+        }
+
+        let Some(kind) = kind else {
+            return Err(format!(
+                "Rule on line {line_number} comes before any !order directive"
+            ));
+        };
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let rule = match (kind, parts.as_slice()) {
+            (RuleKind::Before | RuleKind::After, [subject, anchor]) => OrderRule {
+                kind,
+                subject: EntryPattern::new(subject),
+                anchor: Some(EntryPattern::new(anchor)),
+                line: line_number,
+            },
+            (RuleKind::First | RuleKind::Last, [subject]) => OrderRule {
+                kind,
+                subject: EntryPattern::new(subject),
+                anchor: None,
+                line: line_number,
+            },
+            (RuleKind::Before | RuleKind::After, _) => {
+                return Err(format!(
+                    "Line {line_number}: expected two paths, the second one being the anchor"
+                ));
+            }
+            _ => return Err(format!("Line {line_number}: expected one path")),
+        };
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+/// A rule the current order breaks: `entry` should come before or after
+/// `other`, both 0-based indices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: usize,
+    pub entry: usize,
+    pub other: usize,
+}
+
+/// Entries breaking each rule, paired with the furthest entry they should
+/// have been placed past
+#[must_use]
+pub fn find_violations(rules: &[OrderRule], entries: &[PathBuf]) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let in_anchor = |entry: &PathBuf| match &rule.anchor {
+            Some(anchor) => anchor.matches(entry),
+            None => !rule.subject.matches(entry),
+        };
+
+        for (index, entry) in entries.iter().enumerate() {
+            if !rule.subject.matches(entry) {
+                continue;
+            }
+            let mut others = entries
+                .iter()
+                .enumerate()
+                .filter(|&(other, dir)| other != index && in_anchor(dir))
+                .map(|(other, _)| other);
+            let other = match rule.kind {
+                RuleKind::Before | RuleKind::First => others.find(|&other| other < index),
+                RuleKind::After | RuleKind::Last => others.rfind(|&other| other > index),
+            };
+            if let Some(other) = other {
+                violations.push(Violation {
+                    rule: rule_index,
+                    entry: index,
+                    other,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Reorder `entries` to satisfy every rule, keeping their current order
+/// wherever no rule says otherwise
+///
+/// This is a stable topological sort: entries are placed in their current
+/// order, except that an entry a `before` or `first` rule moves up is placed
+/// where the first entry it has to precede was, and an entry an `after` or
+/// `last` rule moves down waits until everything it follows is placed. An
+/// order that already satisfies the rules comes back unchanged. Rules that
+/// contradict each other for these entries are an error naming them.
+pub fn enforce_order(rules: &[OrderRule], entries: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let edges = constraint_edges(rules, entries);
+    let mut successors = vec![Vec::new(); entries.len()];
+    let mut in_degree = vec![0usize; entries.len()];
+    let mut key: Vec<usize> = (0..entries.len()).collect();
+    for &(from, to, rule) in &edges {
+        successors[from].push(to);
+        in_degree[to] += 1;
+        if matches!(rules[rule].kind, RuleKind::Before | RuleKind::First) {
+            key[from] = key[from].min(to);
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<(usize, usize)>> = (0..entries.len())
+        .filter(|&index| in_degree[index] == 0)
+        .map(|index| Reverse((key[index], index)))
+        .collect();
+    let mut order = Vec::with_capacity(entries.len());
+    while let Some(Reverse((_, index))) = ready.pop() {
+        order.push(index);
+        for &next in &successors[index] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse((key[next], next)));
+            }
+        }
+    }
+
+    if order.len() < entries.len() {
+        return Err(describe_cycle(rules, entries, &edges, &in_degree));
+    }
+    Ok(order
+        .into_iter()
+        .map(|index| entries[index].clone())
+        .collect())
+}
+
+/// `(from, to, rule)`: entry `from` must come before entry `to` because of `rule`
+fn constraint_edges(rules: &[OrderRule], entries: &[PathBuf]) -> Vec<(usize, usize, usize)> {
+    let mut edges = Vec::new();
+
+    for (rule_index, rule) in rules.iter().enumerate() {
+        let subjects: Vec<usize> = (0..entries.len())
+            .filter(|&index| rule.subject.matches(&entries[index]))
+            .collect();
+        let anchors: Vec<usize> = (0..entries.len())
+            .filter(|&index| match &rule.anchor {
+                Some(anchor) => anchor.matches(&entries[index]),
+                None => !subjects.contains(&index),
+            })
+            .collect();
+
+        for &subject in &subjects {
+            for &anchor in anchors.iter().filter(|&&anchor| anchor != subject) {
+                edges.push(match rule.kind {
+                    RuleKind::Before | RuleKind::First => (subject, anchor, rule_index),
+                    RuleKind::After | RuleKind::Last => (anchor, subject, rule_index),
+                });
+            }
+        }
+    }
+
+    edges
+}
+
+/// Name the rules along one cycle among the entries the sort could not place
+fn describe_cycle(
+    rules: &[OrderRule],
+    entries: &[PathBuf],
+    edges: &[(usize, usize, usize)],
+    in_degree: &[usize],
+) -> String {
+    // Every unplaced entry has an unplaced predecessor, so walking backwards
+    // from one must come back to an entry already seen
+    let unplaced = |index: usize| in_degree[index] > 0;
+    let predecessor = |index: usize| {
+        edges
+            .iter()
+            .find(|&&(from, to, _)| to == index && unplaced(from))
+            .copied()
+    };
+
+    let mut path: Vec<(usize, usize, usize)> = Vec::new();
+    let mut current = (0..entries.len())
+        .find(|&index| unplaced(index))
+        .unwrap_or(0);
+    while let Some(edge) = predecessor(current) {
+        if let Some(start) = path.iter().position(|&(_, to, _)| to == edge.0) {
+            path.drain(..start);
+            path.push(edge);
+            break;
+        }
+        path.push(edge);
+        current = edge.0;
+    }
+
+    let mut rule_indices: Vec<usize> = path.iter().map(|&(_, _, rule)| rule).collect();
+    rule_indices.sort_unstable();
+    rule_indices.dedup();
+
+    let mut cycle_entries: Vec<&Path> = path
+        .iter()
+        .map(|&(from, _, _)| entries[from].as_path())
+        .collect();
+    cycle_entries.sort();
+    cycle_entries.dedup();
+
+    format!(
+        "Order rules contradict each other for {}: {}",
+        cycle_entries
+            .iter()
+            .map(|entry| entry.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        rule_indices
+            .iter()
+            .map(|&rule| format!("'{}' (line {})", rules[rule], rules[rule].line))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
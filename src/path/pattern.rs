@@ -0,0 +1,649 @@
+use std::cell::Cell;
+use std::path::Path;
+
+use super::fuzzy::FuzzyScore;
+use super::resolve::FuzzyMatcher;
+
+/// Backtracking steps before a match attempt is abandoned as a non-match,
+/// which keeps patterns like `(a*)*b` from hanging on long inputs
+const MAX_STEPS: usize = 1_000_000;
+
+/// Largest count a `{n,m}` repetition may name
+const MAX_REPEAT: usize = 1000;
+
+/// How a query is interpreted, chosen with `--regex` or `--glob`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PatternKind {
+    /// Whitespace-separated parts that must appear in order
+    #[default]
+    Fuzzy,
+    /// A regular expression searched anywhere in the text
+    Regex,
+    /// A shell glob that must match the whole text
+    Glob,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMode {
+    /// Insensitive unless a regex or glob contains an uppercase letter;
+    /// fuzzy queries stay insensitive
+    #[default]
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PatternOptions {
+    pub kind: PatternKind,
+    pub case: CaseMode,
+}
+
+impl PatternOptions {
+    /// Whether the query is a regex or glob rather than the default fuzzy query
+    #[must_use]
+    pub fn is_explicit(self) -> bool {
+        self.kind != PatternKind::Fuzzy
+    }
+}
+
+/// A compiled query that executable names and `PATH` entries are matched against
+pub enum Pattern {
+    Fuzzy(FuzzyMatcher),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, options: PatternOptions) -> Result<Self, String> {
+        let smart_sensitive = has_uppercase(pattern);
+        let case_sensitive = match options.case {
+            CaseMode::Sensitive => true,
+            CaseMode::Insensitive => false,
+            CaseMode::Smart => smart_sensitive && options.kind != PatternKind::Fuzzy,
+        };
+
+        match options.kind {
+            PatternKind::Fuzzy => Ok(Pattern::Fuzzy(FuzzyMatcher::with_case(
+                pattern,
+                case_sensitive,
+            ))),
+            PatternKind::Regex => Regex::new(pattern, case_sensitive).map(Pattern::Regex),
+            PatternKind::Glob => Ok(Pattern::Regex(Regex::from_glob(pattern, case_sensitive))),
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Fuzzy(matcher) => matcher.matches(Path::new(text)),
+            Pattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+
+    #[must_use]
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.matches(&path.to_string_lossy())
+    }
+
+    /// Ranking score for fuzzy queries; regex and glob matches are unranked
+    #[must_use]
+    pub fn score(&self, text: &str) -> Option<FuzzyScore> {
+        match self {
+            Pattern::Fuzzy(matcher) => matcher.score(Path::new(text)),
+            Pattern::Regex(_) => None,
+        }
+    }
+}
+
+/// Uppercase letters outside of escapes such as `\D` or `\W`
+fn has_uppercase(pattern: &str) -> bool {
+    let mut escaped = false;
+    pattern.chars().any(|ch| {
+        let upper = !escaped && ch.is_uppercase();
+        escaped = !escaped && ch == '\\';
+        upper
+    })
+}
+
+/// A small backtracking regular expression engine
+///
+/// Supports literals, `.`, `[...]` classes with ranges and negation, `\d \w \s`
+/// and their negations, `\b \B`, anchors, groups (`(...)` and `(?:...)`),
+/// alternation and the `* + ? {n} {n,} {n,m}` quantifiers with lazy variants.
+/// There are no captures or backreferences.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    node: Node,
+    case_sensitive: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str, case_sensitive: bool) -> Result<Self, String> {
+        let mut parser = RegexParser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+        };
+        let node = parser
+            .parse_alternation()
+            .map_err(|e| format!("Invalid regex '{pattern}': {e}"))?;
+        if parser.pos < parser.chars.len() {
+            return Err(format!("Invalid regex '{pattern}': unmatched ')'"));
+        }
+
+        Ok(Regex {
+            node,
+            case_sensitive,
+        })
+    }
+
+    /// Compile a glob, where `*` and `?` also match `/`, `[...]` and `[!...]`
+    /// are classes, `{a,b}` is alternation and `\` escapes the next character
+    #[must_use]
+    pub fn from_glob(pattern: &str, case_sensitive: bool) -> Self {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut pos = 0;
+        let body = parse_glob(&chars, &mut pos, false);
+
+        Regex {
+            node: Node::Concat(vec![Node::Start, body, Node::End]),
+            case_sensitive,
+        }
+    }
+
+    #[must_use]
+    pub fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let matcher = Matcher {
+            text: &chars,
+            case_sensitive: self.case_sensitive,
+            steps: Cell::new(0),
+        };
+
+        (0..=chars.len()).any(|start| matcher.node(&self.node, start, &mut |_| true))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    WordBoundary(bool),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn contains(self, ch: char) -> bool {
+        match self {
+            ClassItem::Range(lo, hi) => lo <= ch && ch <= hi,
+            ClassItem::Digit(negated) => ch.is_ascii_digit() != negated,
+            ClassItem::Word(negated) => is_word(ch) != negated,
+            ClassItem::Space(negated) => ch.is_whitespace() != negated,
+        }
+    }
+}
+
+impl Class {
+    fn single(item: ClassItem) -> Node {
+        Node::Class(Class {
+            negated: false,
+            items: vec![item],
+        })
+    }
+
+    fn matches(&self, ch: char, case_sensitive: bool) -> bool {
+        let hit = |c: char| self.items.iter().any(|item| item.contains(c));
+        let found = hit(ch)
+            || (!case_sensitive && (ch.to_lowercase().any(hit) || ch.to_uppercase().any(hit)));
+        found != self.negated
+    }
+}
+
+fn is_word(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, String> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.eat('|') {
+            branches.push(self.parse_concat()?);
+        }
+
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Node::Alternation(branches)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+
+        while let Some(ch) = self.peek() {
+            if ch == '|' || ch == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        let Some(ch) = self.peek() else {
+            return Err("unexpected end of pattern".to_string());
+        };
+        self.pos += 1;
+
+        match ch {
+            '(' => {
+                // Groups never capture, so `(?:` is the same as `(`
+                if self.peek() == Some('?') {
+                    if self.chars.get(self.pos + 1) != Some(&':') {
+                        return Err("only (?:...) groups are supported".to_string());
+                    }
+                    self.pos += 2;
+                }
+                let inner = self.parse_alternation()?;
+                if !self.eat(')') {
+                    return Err("unclosed '('".to_string());
+                }
+                Ok(inner)
+            }
+            '[' => self.parse_class(),
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '\\' => self.parse_escape(),
+            '*' | '+' | '?' => Err(format!("nothing to repeat before '{ch}'")),
+            other => Ok(Node::Char(other)),
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<Node, String> {
+        let Some(ch) = self.peek() else {
+            return Err("trailing '\\'".to_string());
+        };
+        self.pos += 1;
+
+        match ch {
+            'b' => Ok(Node::WordBoundary(true)),
+            'B' => Ok(Node::WordBoundary(false)),
+            _ => match escape_class(ch) {
+                Some(item) => Ok(Class::single(item)),
+                None => escape_char(ch).map(Node::Char),
+            },
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let mut first = true;
+
+        loop {
+            let Some(ch) = self.peek() else {
+                return Err("unclosed '['".to_string());
+            };
+            self.pos += 1;
+
+            if ch == ']' && !first {
+                break;
+            }
+            first = false;
+
+            let lo = if ch == '\\' {
+                let Some(escaped) = self.peek() else {
+                    return Err("unclosed '['".to_string());
+                };
+                self.pos += 1;
+                if let Some(item) = escape_class(escaped) {
+                    items.push(item);
+                    continue;
+                }
+                escape_char(escaped)?
+            } else {
+                ch
+            };
+
+            // `a-z` is a range, but a `-` before `]` is literal
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&c| c != ']') {
+                self.pos += 1;
+                let mut hi = self.chars[self.pos];
+                self.pos += 1;
+                if hi == '\\' {
+                    let escaped = self.peek().ok_or("unclosed '['")?;
+                    self.pos += 1;
+                    hi = escape_char(escaped)?;
+                }
+                if hi < lo {
+                    return Err(format!("invalid range {lo}-{hi}"));
+                }
+                items.push(ClassItem::Range(lo, hi));
+            } else {
+                items.push(ClassItem::Range(lo, lo));
+            }
+        }
+
+        Ok(Node::Class(Class { negated, items }))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => self.advance((0, None)),
+            Some('+') => self.advance((1, None)),
+            Some('?') => self.advance((0, Some(1))),
+            Some('{') => match self.parse_braces() {
+                Some(bounds) => bounds,
+                // `{` that is not a valid quantifier is a literal
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End | Node::WordBoundary(_)) {
+            return Err("nothing to repeat".to_string());
+        }
+        if min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(format!("repetition count above {MAX_REPEAT}"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("invalid repetition {{{min},{}}}", max.unwrap_or(0)));
+        }
+
+        let greedy = !self.eat('?');
+        let node = Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+        };
+        self.parse_quantifier(node)
+    }
+
+    fn advance<T>(&mut self, value: T) -> T {
+        self.pos += 1;
+        value
+    }
+
+    /// Parse `{n}`, `{n,}` or `{n,m}` and move past it
+    fn parse_braces(&mut self) -> Option<(usize, Option<usize>)> {
+        let rest: String = self.chars[self.pos + 1..].iter().collect();
+        let close = rest.find('}')?;
+        let body = &rest[..close];
+
+        let bounds = match body.split_once(',') {
+            None => {
+                let n = body.parse().ok()?;
+                (n, Some(n))
+            }
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+        };
+
+        self.pos += body.chars().count() + 2;
+        Some(bounds)
+    }
+}
+
+fn escape_class(ch: char) -> Option<ClassItem> {
+    match ch {
+        'd' => Some(ClassItem::Digit(false)),
+        'D' => Some(ClassItem::Digit(true)),
+        'w' => Some(ClassItem::Word(false)),
+        'W' => Some(ClassItem::Word(true)),
+        's' => Some(ClassItem::Space(false)),
+        'S' => Some(ClassItem::Space(true)),
+        _ => None,
+    }
+}
+
+fn escape_char(ch: char) -> Result<char, String> {
+    match ch {
+        'n' => Ok('\n'),
+        't' => Ok('\t'),
+        'r' => Ok('\r'),
+        c if c.is_alphanumeric() => Err(format!("unknown escape '\\{c}'")),
+        c => Ok(c),
+    }
+}
+
+/// Parse glob syntax up to the end, or up to `,` / `}` inside braces
+fn parse_glob(chars: &[char], pos: &mut usize, in_braces: bool) -> Node {
+    let mut nodes = Vec::new();
+
+    while let Some(&ch) = chars.get(*pos) {
+        if in_braces && (ch == ',' || ch == '}') {
+            break;
+        }
+        *pos += 1;
+
+        let node = match ch {
+            '*' => Node::Repeat {
+                node: Box::new(Node::Any),
+                min: 0,
+                max: None,
+                greedy: true,
+            },
+            '?' => Node::Any,
+            '\\' => match chars.get(*pos) {
+                Some(&escaped) => {
+                    *pos += 1;
+                    Node::Char(escaped)
+                }
+                None => Node::Char('\\'),
+            },
+            '[' => glob_class(chars, pos).unwrap_or(Node::Char('[')),
+            '{' => glob_braces(chars, pos).unwrap_or(Node::Char('{')),
+            other => Node::Char(other),
+        };
+        nodes.push(node);
+    }
+
+    Node::Concat(nodes)
+}
+
+/// `{a,b,c}` alternation, or `None` (leaving `pos` alone) if it is not closed
+fn glob_braces(chars: &[char], pos: &mut usize) -> Option<Node> {
+    let mut cursor = *pos;
+    let mut branches = Vec::new();
+
+    loop {
+        branches.push(parse_glob(chars, &mut cursor, true));
+        match chars.get(cursor) {
+            Some(',') => cursor += 1,
+            Some('}') => {
+                *pos = cursor + 1;
+                return Some(Node::Alternation(branches));
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// `[abc]`, `[a-z]` or `[!abc]`, or `None` (leaving `pos` alone) if it is not closed
+fn glob_class(chars: &[char], pos: &mut usize) -> Option<Node> {
+    let mut cursor = *pos;
+    let negated = matches!(chars.get(cursor), Some('!' | '^'));
+    if negated {
+        cursor += 1;
+    }
+
+    let mut items = Vec::new();
+    let mut first = true;
+
+    loop {
+        let mut ch = *chars.get(cursor)?;
+        cursor += 1;
+
+        if ch == ']' && !first {
+            break;
+        }
+        first = false;
+
+        if ch == '\\' {
+            ch = *chars.get(cursor)?;
+            cursor += 1;
+        }
+
+        if chars.get(cursor) == Some(&'-') && chars.get(cursor + 1).is_some_and(|&c| c != ']') {
+            let hi = chars[cursor + 1];
+            cursor += 2;
+            items.push(ClassItem::Range(ch.min(hi), ch.max(hi)));
+        } else {
+            items.push(ClassItem::Range(ch, ch));
+        }
+    }
+
+    *pos = cursor;
+    Some(Node::Class(Class { negated, items }))
+}
+
+/// Continuation-passing matcher: each node calls `next` with every position
+/// it can end at until one of them leads to an overall match
+struct Matcher<'a> {
+    text: &'a [char],
+    case_sensitive: bool,
+    steps: Cell<usize>,
+}
+
+type Next<'n> = dyn FnMut(usize) -> bool + 'n;
+
+impl Matcher<'_> {
+    fn node(&self, node: &Node, pos: usize, next: &mut Next<'_>) -> bool {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        if steps > MAX_STEPS {
+            return false;
+        }
+
+        match node {
+            Node::Char(expected) => {
+                self.text
+                    .get(pos)
+                    .is_some_and(|&ch| self.chars_equal(ch, *expected))
+                    && next(pos + 1)
+            }
+            Node::Any => pos < self.text.len() && next(pos + 1),
+            Node::Class(class) => {
+                self.text
+                    .get(pos)
+                    .is_some_and(|&ch| class.matches(ch, self.case_sensitive))
+                    && next(pos + 1)
+            }
+            Node::Start => pos == 0 && next(pos),
+            Node::End => pos == self.text.len() && next(pos),
+            Node::WordBoundary(expected) => {
+                let before = pos > 0 && is_word(self.text[pos - 1]);
+                let after = self.text.get(pos).is_some_and(|&ch| is_word(ch));
+                (before != after) == *expected && next(pos)
+            }
+            Node::Concat(nodes) => self.sequence(nodes, pos, next),
+            Node::Alternation(branches) => {
+                branches.iter().any(|branch| self.node(branch, pos, next))
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => self.repeat(node, (*min, *max, *greedy), 0, pos, next),
+        }
+    }
+
+    fn sequence(&self, nodes: &[Node], pos: usize, next: &mut Next<'_>) -> bool {
+        match nodes.split_first() {
+            None => next(pos),
+            Some((first, rest)) => self.node(first, pos, &mut |end| self.sequence(rest, end, next)),
+        }
+    }
+
+    fn repeat(
+        &self,
+        node: &Node,
+        bounds: (usize, Option<usize>, bool),
+        count: usize,
+        pos: usize,
+        next: &mut Next<'_>,
+    ) -> bool {
+        let (min, max, greedy) = bounds;
+        if count < min {
+            return self.once_more(node, bounds, count, pos, next);
+        }
+        if max.is_some_and(|max| count >= max) {
+            return next(pos);
+        }
+
+        // Lazy repetitions try to stop first, greedy ones to continue first
+        if !greedy && next(pos) {
+            return true;
+        }
+        self.once_more(node, bounds, count, pos, next) || (greedy && next(pos))
+    }
+
+    fn once_more(
+        &self,
+        node: &Node,
+        bounds: (usize, Option<usize>, bool),
+        count: usize,
+        pos: usize,
+        next: &mut Next<'_>,
+    ) -> bool {
+        self.node(node, pos, &mut |end| {
+            // An iteration that consumed nothing can only count towards `min`,
+            // otherwise `(a*)*` would loop forever; it can repeat in place, so
+            // it makes up all the missing iterations at once
+            if end == pos {
+                count < bounds.0 && self.repeat(node, bounds, bounds.0, end, next)
+            } else {
+                self.repeat(node, bounds, count + 1, end, next)
+            }
+        })
+    }
+
+    fn chars_equal(&self, a: char, b: char) -> bool {
+        a == b || (!self.case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+}
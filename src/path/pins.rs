@@ -0,0 +1,180 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// A `PATH` entry kept at a fixed 1-based position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub path: PathBuf,
+    pub position: usize,
+}
+
+/// Parse pins stored one per line as `POSITION<TAB>PATH`
+pub fn parse_pins(content: &str) -> Result<Vec<Pin>, String> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (position, path) = line
+                .split_once('\t')
+                .ok_or_else(|| format!("Invalid pin line: {line}"))?;
+            let position = position
+                .parse()
+                .ok()
+                .filter(|&position| position >= 1)
+                .ok_or_else(|| format!("Invalid pin position: {position}"))?;
+            Ok(Pin {
+                path: PathBuf::from(path),
+                position,
+            })
+        })
+        .collect()
+}
+
+#[must_use]
+pub fn format_pins(pins: &[Pin]) -> String {
+    let mut content = String::new();
+    for pin in pins {
+        writeln!(content, "{}\t{}", pin.position, pin.path.display()).ok();
+    }
+    content
+}
+
+/// Session pins layered over configured ones: a session pin replaces a
+/// configured pin for the same path
+#[must_use]
+pub fn merge_pins(configured: &[Pin], session: &[Pin]) -> Vec<Pin> {
+    let mut pins: Vec<Pin> = configured
+        .iter()
+        .filter(|pin| !session.iter().any(|s| s.path == pin.path))
+        .chain(session)
+        .cloned()
+        .collect();
+    pins.sort_by(|a, b| {
+        a.position
+            .cmp(&b.position)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    pins
+}
+
+#[must_use]
+pub fn pin_for<'a>(pins: &'a [Pin], path: &Path) -> Option<&'a Pin> {
+    pins.iter().find(|pin| pin.path == path)
+}
+
+/// Put pinned entries an edit displaced back at their positions
+///
+/// `before` and `after` are the entries around one edit. Entries inserted or
+/// removed elsewhere only shift a pinned entry, which is then moved back.
+/// Edits that remove a pinned entry, move it themselves, or only work by
+/// displacing one are refused.
+pub fn keep_pinned(
+    before: &[PathBuf],
+    after: &[PathBuf],
+    pins: &[Pin],
+) -> Result<Vec<PathBuf>, String> {
+    let is_pinned = |entry: &PathBuf| pin_for(pins, entry).is_some();
+
+    for pin in pins {
+        if before.contains(&pin.path) && !after.contains(&pin.path) {
+            return Err(pin_conflict(pin, "would be removed"));
+        }
+    }
+
+    let displaced: Vec<&Pin> = pins
+        .iter()
+        .filter(|pin| {
+            after
+                .iter()
+                .position(|entry| *entry == pin.path)
+                .is_some_and(|index| index + 1 != pin.position)
+        })
+        .collect();
+    if displaced.is_empty() {
+        return Ok(after.to_vec());
+    }
+
+    // Entries outside the longest common subsequence are the ones the edit
+    // moved; pinned entries only break ties, so a shift is not taken for a move
+    let kept = kept_entries(before, after, &is_pinned);
+    if let Some(pin) = displaced
+        .iter()
+        .find(|pin| before.contains(&pin.path) && !kept.contains(&&pin.path))
+    {
+        return Err(pin_conflict(pin, "would be moved"));
+    }
+
+    let restored = place_pinned(after, pins);
+    if restored == before && after != before {
+        return Err(pin_conflict(displaced[0], "would be displaced"));
+    }
+
+    Ok(restored)
+}
+
+/// `entries` with every pinned entry on it moved to its position, in
+/// ascending position order; positions past the end append
+#[must_use]
+pub fn place_pinned(entries: &[PathBuf], pins: &[Pin]) -> Vec<PathBuf> {
+    let mut placed: Vec<PathBuf> = entries
+        .iter()
+        .filter(|entry| pin_for(pins, entry).is_none())
+        .cloned()
+        .collect();
+
+    let mut active: Vec<&Pin> = pins
+        .iter()
+        .filter(|pin| entries.contains(&pin.path))
+        .collect();
+    active.sort_by_key(|pin| pin.position);
+    for pin in active {
+        let index = (pin.position - 1).min(placed.len());
+        placed.insert(index, pin.path.clone());
+    }
+    placed
+}
+
+/// Why an edit is refused: `what` says what it would do to `pin`
+#[must_use]
+pub fn pin_conflict(pin: &Pin, what: &str) -> String {
+    format!(
+        "{} is pinned at position {} and {what} (use --force, or whi unpin first)",
+        pin.path.display(),
+        pin.position
+    )
+}
+
+fn kept_entries<'a>(
+    before: &'a [PathBuf],
+    after: &[PathBuf],
+    is_pinned: &dyn Fn(&PathBuf) -> bool,
+) -> Vec<&'a PathBuf> {
+    let weight = |entry: &PathBuf| if is_pinned(entry) { 3 } else { 2 };
+    let (n, m) = (before.len(), after.len());
+    let mut table = vec![vec![0usize; m + 1]; n + 1];
+
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if before[i] == after[j] {
+                table[i + 1][j + 1] + weight(&before[i])
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut kept = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if before[i] == after[j] {
+            kept.push(&before[i]);
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    kept
+}
@@ -2,6 +2,8 @@ use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::fuzzy::{FuzzyScore, score_subsequence};
+
 /// Expands tilde notation in paths
 #[must_use]
 pub fn expand_tilde(path: &str) -> String {
@@ -40,14 +42,22 @@ pub fn resolve_path(path_str: &str, cwd: &Path) -> Result<PathBuf, String> {
 }
 
 /// Normalizes a path by resolving . and .. components
-fn normalize_path(path: &Path) -> PathBuf {
+///
+/// This is purely lexical: symlinks are not followed and trailing slashes are dropped.
+#[must_use]
+pub fn normalize_path(path: &Path) -> PathBuf {
     let mut components = Vec::new();
 
     for component in path.components() {
         match component {
-            std::path::Component::ParentDir => {
-                components.pop();
-            }
+            std::path::Component::ParentDir => match components.last() {
+                Some(std::path::Component::Normal(_)) => {
+                    components.pop();
+                }
+                // `..` at the root stays at the root
+                Some(std::path::Component::RootDir) => {}
+                _ => components.push(component),
+            },
             std::path::Component::CurDir => {
                 // Skip
             }
@@ -55,6 +65,10 @@ fn normalize_path(path: &Path) -> PathBuf {
         }
     }
 
+    if components.is_empty() {
+        return PathBuf::from(".");
+    }
+
     components.iter().collect()
 }
 
@@ -87,14 +101,23 @@ fn find_ci(haystack: &str, needle: &str, start: usize) -> Option<usize> {
 /// Performs fuzzy matching on a path using zoxide-style rules
 pub struct FuzzyMatcher {
     query_parts: Vec<String>,
+    case_sensitive: bool,
 }
 
 impl FuzzyMatcher {
+    #[must_use]
     pub fn new(query: &str) -> Self {
-        // Split by whitespace and convert to lowercase for case-insensitive matching
-        let query_parts: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        FuzzyMatcher::with_case(query, false)
+    }
 
-        FuzzyMatcher { query_parts }
+    #[must_use]
+    pub fn with_case(query: &str, case_sensitive: bool) -> Self {
+        let query_parts: Vec<String> = query.split_whitespace().map(str::to_string).collect();
+
+        FuzzyMatcher {
+            query_parts,
+            case_sensitive,
+        }
     }
 
     /// Check if a path matches the fuzzy query
@@ -104,8 +127,15 @@ impl FuzzyMatcher {
         let mut position = 0;
 
         for part in &self.query_parts {
-            // Find this part starting from current position using case-insensitive search
-            if let Some(idx) = find_ci(&path_str, part, position) {
+            let found = if self.case_sensitive {
+                path_str[position..]
+                    .find(part.as_str())
+                    .map(|idx| idx + position)
+            } else {
+                find_ci(&path_str, part, position)
+            };
+
+            if let Some(idx) = found {
                 position = idx + part.len();
             } else {
                 return false; // Part not found
@@ -115,17 +145,15 @@ impl FuzzyMatcher {
         true
     }
 
-    /// Score a match (lower is better, 0 is exact match)
-    #[allow(dead_code)]
+    /// Score a match for ranking (higher is better), or `None` if it does not match
     #[must_use]
-    pub fn score(&self, path: &Path) -> Option<usize> {
+    pub fn score(&self, path: &Path) -> Option<FuzzyScore> {
         if !self.matches(path) {
             return None;
         }
 
-        // Simple scoring: shorter paths that match are better
-        let path_str = path.to_string_lossy();
-        Some(path_str.len())
+        let query = self.query_parts.join(" ");
+        score_subsequence(&query, &path.to_string_lossy(), self.case_sensitive)
     }
 }
 
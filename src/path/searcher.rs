@@ -1,5 +1,9 @@
 use std::path::PathBuf;
 
+use crate::path::fuzzy::FuzzyScore;
+use crate::path::pattern::Pattern;
+
+#[derive(Clone)]
 pub struct PathSearcher {
     dirs: Vec<PathBuf>,
     canon_dirs: std::cell::RefCell<Vec<Option<PathBuf>>>,
@@ -7,6 +11,58 @@ pub struct PathSearcher {
 
 type PathOpResult = Result<String, String>;
 
+/// A `PATH` entry matched by [`PathSearcher::find_pattern_indices`]
+#[derive(Debug, Clone)]
+pub struct EntryMatch<'a> {
+    /// 1-based `PATH` index
+    pub index: usize,
+    pub path: &'a PathBuf,
+    /// Set for fuzzy patterns
+    pub score: Option<FuzzyScore>,
+}
+
+/// Extra kinds of redundant entries removed by [`PathSearcher::clean`]
+///
+/// Exact duplicates are always removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CleanOptions {
+    /// Remove entries that resolve to the same directory as an earlier entry
+    pub canonical: bool,
+    /// Strip trailing slashes and `.`/`..` segments before comparing
+    pub normalize: bool,
+    /// Remove entries that do not exist on disk
+    pub missing: bool,
+}
+
+/// Why [`PathSearcher::clean`] removed an entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanReason {
+    /// Identical to the kept entry
+    Duplicate { kept: usize },
+    /// Identical to the kept entry once both are normalized
+    Normalized { kept: usize },
+    /// Resolves to the same directory as the kept entry
+    Canonical { kept: usize, target: PathBuf },
+    /// Does not exist on disk
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanRemoval {
+    /// 1-based index in the original `PATH`
+    pub index: usize,
+    pub path: PathBuf,
+    pub reason: CleanReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanReport {
+    pub path: String,
+    pub removed: Vec<CleanRemoval>,
+    /// Kept entries rewritten by normalization, as (1-based index, new value)
+    pub normalized: Vec<(usize, PathBuf)>,
+}
+
 /// Validate a `PATH` entry for suspicious or malicious content
 fn validate_path_entry(path: &str) -> Result<(), String> {
     // Check for null bytes
@@ -201,6 +257,14 @@ impl PathSearcher {
         Ok(())
     }
 
+    /// Remove the entry at a position (1-based), mutating this `PathSearcher`
+    pub fn remove_at(&mut self, position: usize) -> Result<PathBuf, String> {
+        let remove_idx = self.validate_index(position, "position")?;
+
+        self.canon_dirs.borrow_mut().remove(remove_idx);
+        Ok(self.dirs.remove(remove_idx))
+    }
+
     pub fn move_entry(&self, from: usize, to: usize) -> PathOpResult {
         let (from_idx, to_idx) = self.validate_move_indices(from, to)?;
         let mut new_dirs = self.dirs.clone();
@@ -218,21 +282,74 @@ impl PathSearcher {
 
     #[must_use]
     pub fn clean_duplicates(&self) -> (String, Vec<usize>) {
-        let mut seen = std::collections::HashSet::new();
+        let report = self.clean(CleanOptions::default());
+        let removed_indices = report.removed.iter().map(|r| r.index).collect();
+        (report.path, removed_indices)
+    }
+
+    /// Remove redundant entries, keeping the first occurrence of each directory
+    #[must_use]
+    pub fn clean(&self, options: CleanOptions) -> CleanReport {
+        use std::collections::HashMap;
+
+        // Keyed by the raw string: `PathBuf` equality ignores trailing slashes
+        let mut seen: HashMap<std::ffi::OsString, (usize, &PathBuf)> = HashMap::new();
+        let mut seen_canonical: HashMap<PathBuf, usize> = HashMap::new();
         let mut cleaned = Vec::new();
-        let mut removed_indices = Vec::new();
+        let mut removed = Vec::new();
+        let mut normalized = Vec::new();
 
         for (idx, dir) in self.dirs.iter().enumerate() {
-            let dir_str = dir.display().to_string();
-            if seen.insert(dir_str.clone()) {
-                cleaned.push(dir_str);
+            let index = idx + 1;
+            let removal = |reason| CleanRemoval {
+                index,
+                path: dir.clone(),
+                reason,
+            };
+
+            if options.missing && !dir.exists() {
+                removed.push(removal(CleanReason::Missing));
+                continue;
+            }
+
+            let entry = if options.normalize {
+                crate::path::resolve::normalize_path(dir)
             } else {
-                // Duplicate found - track 1-based index
-                removed_indices.push(idx + 1);
+                dir.clone()
+            };
+
+            if let Some(&(kept, kept_dir)) = seen.get(entry.as_os_str()) {
+                let reason = if kept_dir.as_os_str() == dir.as_os_str() {
+                    CleanReason::Duplicate { kept }
+                } else {
+                    CleanReason::Normalized { kept }
+                };
+                removed.push(removal(reason));
+                continue;
+            }
+
+            if options.canonical
+                && let Some(target) = self.canonicalize_index(idx)
+            {
+                if let Some(&kept) = seen_canonical.get(&target) {
+                    removed.push(removal(CleanReason::Canonical { kept, target }));
+                    continue;
+                }
+                seen_canonical.insert(target, index);
             }
+
+            if entry.as_os_str() != dir.as_os_str() {
+                normalized.push((index, entry.clone()));
+            }
+            seen.insert(entry.as_os_str().to_os_string(), (index, dir));
+            cleaned.push(entry);
         }
 
-        (cleaned.join(":"), removed_indices)
+        CleanReport {
+            path: Self::join_dirs(&cleaned),
+            removed,
+            normalized,
+        }
     }
 
     pub fn delete_entry(&self, idx: usize) -> PathOpResult {
@@ -300,7 +417,7 @@ impl PathSearcher {
         None
     }
 
-    /// Find all indices matching a fuzzy pattern
+    /// Find all indices matching a fuzzy pattern, best match first
     #[must_use]
     pub fn find_fuzzy_indices(
         &self,
@@ -309,11 +426,27 @@ impl PathSearcher {
     ) -> Vec<(usize, &PathBuf)> {
         use crate::path::fuzzy::FuzzyMatcher;
 
-        let matcher = FuzzyMatcher::new(pattern);
-        let mut fuzzy_results = Vec::new();
+        self.find_pattern_indices(&Pattern::Fuzzy(FuzzyMatcher::new(pattern)), executable_name)
+            .into_iter()
+            .map(|entry| (entry.index, entry.path))
+            .collect()
+    }
+
+    /// Find all entries whose directory matches `pattern`
+    ///
+    /// Fuzzy matches are ranked by score, then shorter paths, then `PATH`
+    /// order, so ties always resolve the same way. Regex and glob matches are
+    /// ranked by length and `PATH` order only.
+    #[must_use]
+    pub fn find_pattern_indices(
+        &self,
+        pattern: &Pattern,
+        executable_name: Option<&str>,
+    ) -> Vec<EntryMatch<'_>> {
+        let mut results = Vec::new();
 
         for (idx, dir) in self.dirs.iter().enumerate() {
-            if matcher.matches(dir) {
+            if pattern.matches_path(dir) {
                 // If executable specified, check it exists
                 if let Some(name) = executable_name
                     && !self.has_executable(dir, name)
@@ -321,14 +454,23 @@ impl PathSearcher {
                     continue;
                 }
 
-                fuzzy_results.push((idx + 1, dir)); // 1-based index
+                results.push(EntryMatch {
+                    index: idx + 1, // 1-based index
+                    path: dir,
+                    score: pattern.score(&dir.to_string_lossy()),
+                });
             }
         }
 
-        // Sort by match quality (shorter paths first)
-        fuzzy_results.sort_by_key(|(_, path)| path.as_os_str().len());
+        results.sort_by_key(|entry| {
+            (
+                std::cmp::Reverse(entry.score.as_ref().map(|score| score.total)),
+                entry.path.as_os_str().len(),
+                entry.index,
+            )
+        });
 
-        fuzzy_results
+        results
     }
 
     /// Delete a `PATH` entry by exact path match
@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::config::shell_paths;
+use crate::path::file::{ParsedPathFile, apply_path_sections, expand_shell_vars, parse_path_file};
+use crate::session::history::HistoryContext;
+use crate::shell::detect::Shell;
+
+/// Where `--path-from` reads the `PATH` to search instead of the environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSource {
+    /// `profile:NAME`, applied to the current `PATH` as `whi load` would
+    Profile(String),
+    /// `saved:SHELL`, the `PATH` stored by `whi save`
+    Saved(Shell),
+    /// `snapshot:N`, an index as listed by `whi history`; negative values
+    /// count back from the current snapshot, so `-3` is three operations ago
+    Snapshot(i64),
+    /// `pid:PID`, read from `/proc/PID/environ`
+    Pid(u32),
+    /// `file:FILE`, a colon-separated `PATH` or a profile-style file
+    File(PathBuf),
+}
+
+impl FromStr for PathSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((kind, value)) = s.split_once(':').filter(|(_, value)| !value.is_empty()) else {
+            return Err(format!(
+                "Invalid PATH source: {s} (expected profile:NAME, saved:SHELL, snapshot:N, pid:PID or file:FILE)"
+            ));
+        };
+
+        match kind {
+            "profile" => Ok(PathSource::Profile(value.to_string())),
+            "saved" => Shell::from_str(value).map(PathSource::Saved),
+            "snapshot" => value
+                .parse()
+                .map(PathSource::Snapshot)
+                .map_err(|_| format!("Invalid snapshot index: {value}")),
+            "pid" => value
+                .parse()
+                .map(PathSource::Pid)
+                .map_err(|_| format!("Invalid process ID: {value}")),
+            "file" => Ok(PathSource::File(PathBuf::from(value))),
+            other => Err(format!(
+                "Unknown PATH source: {other} (expected profile, saved, snapshot, pid or file)"
+            )),
+        }
+    }
+}
+
+impl PathSource {
+    /// Read the `PATH` this source describes
+    ///
+    /// `current_path` is the base that profiles and files with
+    /// prepend/append sections are applied to. `history` is only opened for
+    /// snapshots.
+    pub fn read<H>(&self, current_path: &str, history: H) -> Result<String, String>
+    where
+        H: FnOnce() -> Result<HistoryContext, String>,
+    {
+        match self {
+            PathSource::Profile(name) => {
+                apply_parsed(current_path, &shell_paths::load_profile(name)?)
+            }
+            PathSource::Saved(shell) => shell_paths::load_saved_path_for_shell(shell),
+            PathSource::Snapshot(index) => {
+                let history = history()?;
+                select_snapshot(history.read_snapshots()?, history.get_cursor()?, *index)
+            }
+            PathSource::Pid(pid) => read_process_path(*pid),
+            PathSource::File(file) => {
+                let content = fs::read_to_string(file)
+                    .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+                apply_parsed(current_path, &parse_path_file(&content)?)
+            }
+        }
+    }
+}
+
+fn apply_parsed(current_path: &str, parsed: &ParsedPathFile) -> Result<String, String> {
+    Ok(apply_path_sections(current_path, &parsed.path)?
+        .split(':')
+        .map(expand_shell_vars)
+        .collect::<Vec<_>>()
+        .join(":"))
+}
+
+fn select_snapshot(
+    mut snapshots: Vec<String>,
+    cursor: Option<usize>,
+    index: i64,
+) -> Result<String, String> {
+    if snapshots.is_empty() {
+        return Err("No PATH history found for this session".to_string());
+    }
+
+    let current = cursor.unwrap_or(snapshots.len() - 1);
+    let position = if index < 0 {
+        usize::try_from(index.unsigned_abs())
+            .ok()
+            .and_then(|back| current.checked_sub(back))
+    } else {
+        usize::try_from(index).ok()
+    };
+
+    match position.filter(|&position| position < snapshots.len()) {
+        Some(position) => Ok(snapshots.swap_remove(position)),
+        None => Err(format!(
+            "Snapshot {index} out of range (history has {} snapshots, current is {current})",
+            snapshots.len()
+        )),
+    }
+}
+
+fn read_process_path(pid: u32) -> Result<String, String> {
+    let environ = fs::read(format!("/proc/{pid}/environ"))
+        .map_err(|e| format!("Cannot read environment of process {pid}: {e}"))?;
+
+    environ
+        .split(|&byte| byte == 0)
+        .find_map(|var| var.strip_prefix(b"PATH="))
+        .map(|path| String::from_utf8_lossy(path).into_owned())
+        .ok_or_else(|| format!("Process {pid} has no PATH in its environment"))
+}
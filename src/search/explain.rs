@@ -0,0 +1,106 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// What a `PATH` lookup found at one candidate location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CandidateStatus {
+    /// The `PATH` entry itself does not exist
+    MissingDir,
+    /// The `PATH` entry exists but is not a directory
+    NotADirectory,
+    /// The directory has no entry with that name
+    NoSuchFile,
+    /// A symlink whose target does not exist
+    BrokenSymlink,
+    /// A directory with that name
+    Directory,
+    /// A file (or special file) without any execute bit
+    NotExecutable,
+    /// An executable regular file
+    Executable,
+}
+
+impl CandidateStatus {
+    /// Stable identifier used in structured output
+    #[must_use]
+    pub fn code(self) -> &'static str {
+        match self {
+            CandidateStatus::MissingDir => "missing-dir",
+            CandidateStatus::NotADirectory => "not-a-directory",
+            CandidateStatus::NoSuchFile => "no-such-file",
+            CandidateStatus::BrokenSymlink => "broken-symlink",
+            CandidateStatus::Directory => "directory",
+            CandidateStatus::NotExecutable => "not-executable",
+            CandidateStatus::Executable => "executable",
+        }
+    }
+
+    #[must_use]
+    pub fn describe(self) -> &'static str {
+        match self {
+            CandidateStatus::MissingDir => "directory does not exist",
+            CandidateStatus::NotADirectory => "PATH entry is not a directory",
+            CandidateStatus::NoSuchFile => "no such file",
+            CandidateStatus::BrokenSymlink => "broken symlink",
+            CandidateStatus::Directory => "is a directory",
+            CandidateStatus::NotExecutable => "present but not executable",
+            CandidateStatus::Executable => "executable",
+        }
+    }
+}
+
+/// Classify a candidate file path (symlinks are followed)
+#[must_use]
+pub fn classify(path: &Path) -> CandidateStatus {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => CandidateStatus::Directory,
+        Ok(metadata) if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 => {
+            CandidateStatus::Executable
+        }
+        Ok(_) => CandidateStatus::NotExecutable,
+        Err(_) if fs::symlink_metadata(path).is_ok() => CandidateStatus::BrokenSymlink,
+        Err(_) => CandidateStatus::NoSuchFile,
+    }
+}
+
+/// Outcome of looking up a name in one `PATH` entry
+#[derive(Debug, Clone)]
+pub struct ExplainStep {
+    /// 1-based `PATH` index
+    pub path_index: usize,
+    pub dir: PathBuf,
+    pub candidate: PathBuf,
+    pub status: CandidateStatus,
+    /// True for the first executable match, which is what the shell runs
+    pub winner: bool,
+}
+
+/// Walk every `PATH` entry in order and classify what `name` resolves to in each
+#[must_use]
+pub fn explain_name(dirs: &[PathBuf], name: &str) -> Vec<ExplainStep> {
+    let mut found_winner = false;
+
+    dirs.iter()
+        .enumerate()
+        .map(|(idx, dir)| {
+            let candidate = dir.join(name);
+            let status = match fs::metadata(dir) {
+                Err(_) => CandidateStatus::MissingDir,
+                Ok(metadata) if !metadata.is_dir() => CandidateStatus::NotADirectory,
+                Ok(_) => classify(&candidate),
+            };
+
+            let winner = status == CandidateStatus::Executable && !found_winner;
+            found_winner |= winner;
+
+            ExplainStep {
+                path_index: idx + 1,
+                dir: dir.clone(),
+                candidate,
+                status,
+                winner,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::config::runtime::get_cache_dir;
+use crate::io::atomic_file::AtomicFile;
+
+const INDEX_HEADER: &str = "!whi.index 1";
+
+/// What a directory entry resolves to (symlinks are followed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Executable,
+    File,
+    Directory,
+    Other,
+    /// Metadata could not be read, e.g. a dangling symlink
+    Broken,
+}
+
+impl EntryKind {
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        if metadata.is_file() {
+            if metadata.permissions().mode() & 0o111 != 0 {
+                EntryKind::Executable
+            } else {
+                EntryKind::File
+            }
+        } else if metadata.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::Other
+        }
+    }
+
+    fn code(self) -> char {
+        match self {
+            EntryKind::Executable => 'x',
+            EntryKind::File => 'f',
+            EntryKind::Directory => 'd',
+            EntryKind::Other => 'o',
+            EntryKind::Broken => 'b',
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "x" => Some(EntryKind::Executable),
+            "f" => Some(EntryKind::File),
+            "d" => Some(EntryKind::Directory),
+            "o" => Some(EntryKind::Other),
+            "b" => Some(EntryKind::Broken),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub name: String,
+    pub kind: EntryKind,
+}
+
+/// Identity of a directory at the time it was scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DirStamp {
+    ino: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+}
+
+impl DirStamp {
+    fn of(dir: &Path) -> Option<Self> {
+        let metadata = fs::metadata(dir).ok()?;
+        if !metadata.is_dir() {
+            return None;
+        }
+
+        Some(DirStamp {
+            ino: metadata.ino(),
+            mtime: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DirListing {
+    stamp: DirStamp,
+    entries: Vec<IndexEntry>,
+}
+
+/// On-disk cache of `PATH` directory listings
+///
+/// Each directory is keyed by its path and invalidated when its inode or
+/// mtime changes, so adding or removing files triggers a rescan. Changing the
+/// mode of an existing file does not touch the directory mtime; run
+/// `whi cache rebuild` after a `chmod` if results look stale.
+pub struct ExecutableIndex {
+    file: Option<PathBuf>,
+    dirs: HashMap<PathBuf, DirListing>,
+    dirty: bool,
+}
+
+impl ExecutableIndex {
+    /// Load the index from the cache directory, starting empty if it is missing or unreadable
+    #[must_use]
+    pub fn load() -> Self {
+        let Ok(file) = index_file_path() else {
+            return Self::disabled();
+        };
+
+        let dirs = fs::read_to_string(&file)
+            .ok()
+            .map(|content| parse_index(&content))
+            .unwrap_or_default();
+
+        ExecutableIndex {
+            file: Some(file),
+            dirs,
+            dirty: false,
+        }
+    }
+
+    /// An index that always scans directories and never touches the disk cache
+    #[must_use]
+    pub fn disabled() -> Self {
+        ExecutableIndex {
+            file: None,
+            dirs: HashMap::new(),
+            dirty: false,
+        }
+    }
+
+    /// Get the entries of `dir`, rescanning it if the cached listing is stale
+    ///
+    /// Returns an empty listing for directories that do not exist or cannot be read.
+    pub fn listing(&mut self, dir: &Path) -> &[IndexEntry] {
+        let Some(stamp) = DirStamp::of(dir) else {
+            return &[];
+        };
+
+        let fresh = self
+            .dirs
+            .get(dir)
+            .is_some_and(|listing| listing.stamp == stamp);
+
+        if !fresh {
+            self.dirs.insert(
+                dir.to_path_buf(),
+                DirListing {
+                    stamp,
+                    entries: scan_dir(dir),
+                },
+            );
+
+            // Relative entries depend on the working directory and are never persisted
+            if dir.is_absolute() {
+                self.dirty = true;
+            }
+        }
+
+        &self.dirs[dir].entries
+    }
+
+    /// Names of the executable files in `dir`
+    pub fn executables(&mut self, dir: &Path) -> Vec<String> {
+        self.listing(dir)
+            .iter()
+            .filter(|entry| entry.kind == EntryKind::Executable)
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+
+    /// Drop all cached listings and rescan `dirs` from scratch
+    ///
+    /// Returns the number of readable directories and entries indexed.
+    pub fn rebuild(&mut self, dirs: &[PathBuf]) -> (usize, usize) {
+        self.dirs.clear();
+        self.dirty = true;
+
+        let mut entry_count = 0;
+        for dir in dirs {
+            if !self.dirs.contains_key(dir) {
+                entry_count += self.listing(dir).len();
+            }
+        }
+
+        (self.dirs.len(), entry_count)
+    }
+
+    /// Write the index back to disk if any listing changed
+    pub fn save(&mut self) -> Result<(), String> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {e}"))?;
+        }
+
+        let mut atomic_file =
+            AtomicFile::new(file).map_err(|e| format!("Failed to create index file: {e}"))?;
+
+        atomic_file
+            .write_all(format_index(&self.dirs).as_bytes())
+            .map_err(|e| format!("Failed to write index: {e}"))?;
+
+        atomic_file
+            .commit()
+            .map_err(|e| format!("Failed to commit index file: {e}"))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Remove the on-disk index
+    pub fn clear() -> Result<(), String> {
+        let file = index_file_path()?;
+        if file.exists() {
+            fs::remove_file(&file).map_err(|e| format!("Failed to remove index file: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn index_file_path() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join("exec_index"))
+}
+
+fn scan_dir(dir: &Path) -> Vec<IndexEntry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut listing = Vec::new();
+    for entry in entries.flatten() {
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+
+        // Names that would break the line-based cache format are not indexed
+        if name.contains('\n') || name.contains('\t') {
+            continue;
+        }
+
+        let kind = fs::metadata(entry.path()).map_or(EntryKind::Broken, |metadata| {
+            EntryKind::from_metadata(&metadata)
+        });
+
+        listing.push(IndexEntry { name, kind });
+    }
+
+    listing.sort_by(|a, b| a.name.cmp(&b.name));
+    listing
+}
+
+fn format_index(dirs: &HashMap<PathBuf, DirListing>) -> String {
+    let mut keys: Vec<&PathBuf> = dirs.keys().filter(|dir| dir.is_absolute()).collect();
+    keys.sort();
+
+    let mut output = String::from(INDEX_HEADER);
+    output.push('\n');
+
+    for dir in keys {
+        let Some(dir_str) = dir.to_str() else {
+            continue;
+        };
+        if dir_str.contains('\n') {
+            continue;
+        }
+
+        let listing = &dirs[dir];
+        writeln!(
+            output,
+            "!dir {} {} {} {dir_str}",
+            listing.stamp.ino, listing.stamp.mtime, listing.stamp.mtime_nsec
+        )
+        .ok();
+
+        for entry in &listing.entries {
+            output.push(entry.kind.code());
+            output.push('\t');
+            output.push_str(&entry.name);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+fn parse_index(content: &str) -> HashMap<PathBuf, DirListing> {
+    let mut dirs = HashMap::new();
+    let mut lines = content.lines();
+
+    if lines.next() != Some(INDEX_HEADER) {
+        return dirs;
+    }
+
+    let mut current: Option<(PathBuf, DirListing)> = None;
+
+    for line in lines {
+        if let Some(rest) = line.strip_prefix("!dir ") {
+            if let Some((dir, listing)) = current.take() {
+                dirs.insert(dir, listing);
+            }
+            current = parse_dir_header(rest);
+            continue;
+        }
+
+        let Some((_, listing)) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some((code, name)) = line.split_once('\t')
+            && let Some(kind) = EntryKind::from_code(code)
+        {
+            listing.entries.push(IndexEntry {
+                name: name.to_string(),
+                kind,
+            });
+        }
+    }
+
+    if let Some((dir, listing)) = current {
+        dirs.insert(dir, listing);
+    }
+
+    dirs
+}
+
+fn parse_dir_header(rest: &str) -> Option<(PathBuf, DirListing)> {
+    let mut parts = rest.splitn(4, ' ');
+    let ino = parts.next()?.parse().ok()?;
+    let mtime = parts.next()?.parse().ok()?;
+    let mtime_nsec = parts.next()?.parse().ok()?;
+    let dir = PathBuf::from(parts.next()?);
+
+    Some((
+        dir,
+        DirListing {
+            stamp: DirStamp {
+                ino,
+                mtime,
+                mtime_nsec,
+            },
+            entries: Vec::new(),
+        },
+    ))
+}
@@ -0,0 +1,323 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::search::explain::{CandidateStatus, classify};
+
+/// Longest shebang line considered, matching the usual kernel limit
+const MAX_SHEBANG_LEN: usize = 256;
+
+/// Program headers inspected before an ELF file is treated as malformed
+const MAX_PROGRAM_HEADERS: u16 = 512;
+
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+const ET_DYN: u16 = 3;
+
+/// What an executable is, decoded from its first bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileKind {
+    Elf(ElfInfo),
+    MachO(MachOInfo),
+    Script(ScriptInfo),
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+    /// Loaded through a dynamic linker named by `PT_INTERP`
+    Dynamic,
+    /// Self-relocating position-independent executable without an interpreter
+    StaticPie,
+    Static,
+}
+
+impl Linkage {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Linkage::Dynamic => "dynamic",
+            Linkage::StaticPie => "static-pie",
+            Linkage::Static => "static",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfInfo {
+    /// 32 or 64
+    pub bits: u8,
+    pub little_endian: bool,
+    pub machine: String,
+    pub linkage: Linkage,
+    pub interpreter: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachOInfo {
+    /// One architecture for thin binaries, every slice for universal binaries
+    pub architectures: Vec<String>,
+    pub universal: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptInfo {
+    /// The interpreter exactly as written after `#!`
+    pub interpreter: String,
+    /// Program looked up by `#!/usr/bin/env <program>`
+    pub env_program: Option<String>,
+    /// Where the interpreter resolves, or `None` if it cannot be run
+    pub resolved: Option<PathBuf>,
+}
+
+/// Classify `path` from its file header
+///
+/// `#!/usr/bin/env` programs are looked up in `path_dirs`, the same way `env`
+/// would search `PATH`.
+#[must_use]
+pub fn introspect(path: &Path, path_dirs: &[PathBuf]) -> Option<FileKind> {
+    let mut file = File::open(path).ok()?;
+    let mut head = [0u8; MAX_SHEBANG_LEN];
+    let len = read_up_to(&mut file, &mut head).ok()?;
+    let head = &head[..len];
+
+    let kind = if head.starts_with(b"\x7fELF") {
+        parse_elf(&mut file, head).map_or(FileKind::Other, FileKind::Elf)
+    } else if head.starts_with(b"#!") {
+        FileKind::Script(parse_shebang(head, path_dirs))
+    } else if let Some(info) = parse_macho(head) {
+        FileKind::MachO(info)
+    } else {
+        FileKind::Other
+    };
+
+    Some(kind)
+}
+
+fn read_up_to(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Fixed-width integer reads in the file's byte order
+struct Endian(bool);
+
+impl Endian {
+    fn u16(&self, bytes: &[u8], at: usize) -> Option<u16> {
+        let raw: [u8; 2] = bytes.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.0 {
+            u16::from_le_bytes(raw)
+        } else {
+            u16::from_be_bytes(raw)
+        })
+    }
+
+    fn u32(&self, bytes: &[u8], at: usize) -> Option<u32> {
+        let raw: [u8; 4] = bytes.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.0 {
+            u32::from_le_bytes(raw)
+        } else {
+            u32::from_be_bytes(raw)
+        })
+    }
+
+    fn u64(&self, bytes: &[u8], at: usize) -> Option<u64> {
+        let raw: [u8; 8] = bytes.get(at..at + 8)?.try_into().ok()?;
+        Some(if self.0 {
+            u64::from_le_bytes(raw)
+        } else {
+            u64::from_be_bytes(raw)
+        })
+    }
+
+    /// A 32-bit field in ELF32 files, 64-bit in ELF64
+    fn word(&self, bytes: &[u8], at32: usize, at64: usize, is_64: bool) -> Option<u64> {
+        if is_64 {
+            self.u64(bytes, at64)
+        } else {
+            self.u32(bytes, at32).map(u64::from)
+        }
+    }
+}
+
+fn parse_elf(file: &mut File, head: &[u8]) -> Option<ElfInfo> {
+    let is_64 = match head.get(4)? {
+        1 => false,
+        2 => true,
+        _ => return None,
+    };
+    let endian = match head.get(5)? {
+        1 => Endian(true),
+        2 => Endian(false),
+        _ => return None,
+    };
+
+    let elf_type = endian.u16(head, 16)?;
+    let machine = endian.u16(head, 18)?;
+    let phoff = endian.word(head, 28, 32, is_64)?;
+    let (entsize_at, num_at) = if is_64 { (54, 56) } else { (42, 44) };
+    let phentsize = usize::from(endian.u16(head, entsize_at)?);
+    let phnum = endian.u16(head, num_at)?.min(MAX_PROGRAM_HEADERS);
+
+    let min_entsize = if is_64 { 56 } else { 32 };
+    if phentsize < min_entsize {
+        return None;
+    }
+
+    let mut table = vec![0u8; phentsize * usize::from(phnum)];
+    file.seek(SeekFrom::Start(phoff)).ok()?;
+    file.read_exact(&mut table).ok()?;
+
+    let mut has_dynamic = false;
+    let mut interpreter = None;
+
+    for header in table.chunks_exact(phentsize) {
+        match endian.u32(header, 0)? {
+            PT_DYNAMIC => has_dynamic = true,
+            PT_INTERP => {
+                let offset = endian.word(header, 4, 8, is_64)?;
+                let size = endian.word(header, 16, 32, is_64)?.min(4096);
+                let mut raw = vec![0u8; usize::try_from(size).ok()?];
+                file.seek(SeekFrom::Start(offset)).ok()?;
+                file.read_exact(&mut raw).ok()?;
+                let raw = raw.split(|&b| b == 0).next().unwrap_or_default();
+                interpreter = Some(PathBuf::from(String::from_utf8_lossy(raw).into_owned()));
+            }
+            _ => {}
+        }
+    }
+
+    let linkage = if interpreter.is_some() {
+        Linkage::Dynamic
+    } else if has_dynamic && elf_type == ET_DYN {
+        Linkage::StaticPie
+    } else {
+        Linkage::Static
+    };
+
+    Some(ElfInfo {
+        bits: if is_64 { 64 } else { 32 },
+        little_endian: endian.0,
+        machine: elf_machine_name(machine),
+        linkage,
+        interpreter,
+    })
+}
+
+fn elf_machine_name(machine: u16) -> String {
+    let name = match machine {
+        2 => "sparc",
+        3 => "x86",
+        8 => "mips",
+        20 => "powerpc",
+        21 => "powerpc64",
+        22 => "s390",
+        40 => "arm",
+        43 => "sparc64",
+        62 => "x86-64",
+        183 => "aarch64",
+        243 => "riscv",
+        258 => "loongarch",
+        other => return format!("machine {other}"),
+    };
+    name.to_string()
+}
+
+fn parse_macho(head: &[u8]) -> Option<MachOInfo> {
+    let magic: [u8; 4] = head.get(..4)?.try_into().ok()?;
+
+    let thin_endian = match magic {
+        [0xce | 0xcf, 0xfa, 0xed, 0xfe] => Some(Endian(true)),
+        [0xfe, 0xed, 0xfa, 0xce | 0xcf] => Some(Endian(false)),
+        _ => None,
+    };
+    if let Some(endian) = thin_endian {
+        return Some(MachOInfo {
+            architectures: vec![macho_cpu_name(endian.u32(head, 4)?)],
+            universal: false,
+        });
+    }
+
+    // Universal headers are big-endian; Java class files share the magic but
+    // have a version number where the slice count would be
+    let entry_size = match magic {
+        [0xca, 0xfe, 0xba, 0xbe] => 20,
+        [0xca, 0xfe, 0xba, 0xbf] => 32,
+        _ => return None,
+    };
+    let endian = Endian(false);
+    let count = endian.u32(head, 4)?;
+    if count == 0 || count > 20 {
+        return None;
+    }
+
+    let architectures = (0..count as usize)
+        .map(|i| endian.u32(head, 8 + i * entry_size).map(macho_cpu_name))
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(MachOInfo {
+        architectures,
+        universal: true,
+    })
+}
+
+fn macho_cpu_name(cpu_type: u32) -> String {
+    let name = match cpu_type {
+        7 => "i386",
+        0x0100_0007 => "x86_64",
+        12 => "arm",
+        0x0100_000c => "arm64",
+        0x0200_000c => "arm64_32",
+        18 => "ppc",
+        0x0100_0012 => "ppc64",
+        other => return format!("cpu {other:#x}"),
+    };
+    name.to_string()
+}
+
+fn parse_shebang(head: &[u8], path_dirs: &[PathBuf]) -> ScriptInfo {
+    let line = head[2..].split(|&b| b == b'\n').next().unwrap_or_default();
+    let line = String::from_utf8_lossy(line);
+    let mut words = line.split_whitespace();
+    let interpreter = words.next().unwrap_or_default().to_string();
+
+    let is_env = Path::new(&interpreter)
+        .file_name()
+        .is_some_and(|name| name == "env");
+
+    // `env -S python3 -u` and `env VAR=1 python3` both name the program later
+    let env_program = is_env
+        .then(|| {
+            words
+                .flat_map(str::split_whitespace)
+                .find(|word| !word.starts_with('-') && !word.contains('='))
+                .map(str::to_string)
+        })
+        .flatten();
+
+    let resolved = match env_program {
+        Some(ref program) if !program.contains('/') => path_dirs
+            .iter()
+            .map(|dir| dir.join(program))
+            .find(|candidate| classify(candidate) == CandidateStatus::Executable),
+        Some(ref program) => executable(Path::new(program)),
+        None if interpreter.is_empty() => None,
+        None => executable(Path::new(&interpreter)),
+    };
+
+    ScriptInfo {
+        interpreter,
+        env_program,
+        resolved,
+    }
+}
+
+fn executable(path: &Path) -> Option<PathBuf> {
+    (classify(path) == CandidateStatus::Executable).then(|| path.to_path_buf())
+}
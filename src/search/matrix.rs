@@ -0,0 +1,150 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::search::index::ExecutableIndex;
+use crate::search::shadows::{ShadowEntry, mark_same_files};
+use crate::search::suggest::suggest;
+
+/// Which `PATH` entries provide one of the names asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixRow {
+    /// The name as given on the command line
+    pub requested: String,
+    /// The name looked up: `requested`, or the closest name on `PATH` when
+    /// `requested` was not found
+    pub name: String,
+    /// Every copy in `PATH` order, so the first one wins
+    pub entries: Vec<ShadowEntry>,
+}
+
+impl MatrixRow {
+    #[must_use]
+    pub fn winner(&self) -> Option<&ShadowEntry> {
+        self.entries.first()
+    }
+
+    /// The requested name was not found and a close one was used instead
+    #[must_use]
+    pub fn is_corrected(&self) -> bool {
+        self.name != self.requested
+    }
+
+    #[must_use]
+    pub fn entry_at(&self, path_index: usize) -> Option<&ShadowEntry> {
+        self.entries.iter().find(|e| e.path_index == path_index)
+    }
+}
+
+/// The names that resolve into one install prefix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixGroup {
+    pub prefix: PathBuf,
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    pub rows: Vec<MatrixRow>,
+    /// 1-based indices of the `PATH` entries that provide at least one name
+    pub columns: Vec<usize>,
+}
+
+impl Matrix {
+    /// Winning copies grouped by install prefix, in order of first appearance
+    #[must_use]
+    pub fn prefixes(&self) -> Vec<PrefixGroup> {
+        let mut groups: Vec<PrefixGroup> = Vec::new();
+
+        for row in &self.rows {
+            let Some(winner) = row.winner() else {
+                continue;
+            };
+            let prefix = winner
+                .path
+                .parent()
+                .map_or_else(PathBuf::new, install_prefix);
+            match groups.iter_mut().find(|g| g.prefix == prefix) {
+                Some(group) => group.names.push(row.name.clone()),
+                None => groups.push(PrefixGroup {
+                    prefix,
+                    names: vec![row.name.clone()],
+                }),
+            }
+        }
+
+        groups
+    }
+}
+
+/// Resolve every name against `dirs`, falling back to the closest name on
+/// `PATH` for names that are not found at all
+pub fn build_matrix(names: &[String], dirs: &[PathBuf], index: &mut ExecutableIndex) -> Matrix {
+    let listings: Vec<HashSet<String>> = dirs
+        .iter()
+        .map(|dir| index.executables(dir).into_iter().collect())
+        .collect();
+    let find = |name: &str| -> Vec<ShadowEntry> {
+        let mut entries: Vec<ShadowEntry> = dirs
+            .iter()
+            .zip(&listings)
+            .enumerate()
+            .filter(|(_, (_, listing))| listing.contains(name))
+            .map(|(idx, (dir, _))| ShadowEntry {
+                path_index: idx + 1,
+                path: dir.join(name),
+                same_file: false,
+            })
+            .collect();
+        if !entries.is_empty() {
+            mark_same_files(&mut entries);
+        }
+        entries
+    };
+
+    let mut rows = Vec::with_capacity(names.len());
+    for requested in names {
+        let mut entries = find(requested);
+        let mut name = requested.clone();
+
+        if entries.is_empty()
+            && let Some(close) = suggest(requested, dirs, &[], index, 1)
+                .near
+                .into_iter()
+                .next()
+        {
+            entries = find(&close.name);
+            name = close.name;
+        }
+
+        rows.push(MatrixRow {
+            requested: requested.clone(),
+            name,
+            entries,
+        });
+    }
+
+    let columns: BTreeSet<usize> = rows
+        .iter()
+        .flat_map(|row| row.entries.iter().map(|e| e.path_index))
+        .collect();
+
+    Matrix {
+        rows,
+        columns: columns.into_iter().collect(),
+    }
+}
+
+/// The install prefix a `bin` directory belongs to: `/opt/llvm/bin` gives
+/// `/opt/llvm`
+///
+/// Symlinks are resolved first so merged-`/usr` systems, where `/bin` points
+/// at `/usr/bin`, do not look like two prefixes.
+#[must_use]
+pub fn install_prefix(dir: &Path) -> PathBuf {
+    let dir = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    match dir.file_name().and_then(|name| name.to_str()) {
+        Some("bin" | "sbin") => dir.parent().map_or(dir.clone(), Path::to_path_buf),
+        _ => dir,
+    }
+}
@@ -1 +1,12 @@
+pub mod explain;
+pub mod index;
+pub mod introspect;
+pub mod matrix;
 pub mod result;
+pub mod semver;
+pub mod shadows;
+pub mod shims;
+pub mod suggest;
+pub mod symlink;
+pub mod version;
+pub mod winners;
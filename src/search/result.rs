@@ -2,6 +2,11 @@ use std::fs;
 use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::{Path, PathBuf};
 
+use crate::path::fuzzy::FuzzyScore;
+use crate::search::introspect::FileKind;
+use crate::search::shims::ShimResolution;
+use crate::search::symlink::SymlinkChain;
+
 pub struct ExecutableCheck<'a> {
     path: &'a Path,
     metadata: Option<fs::Metadata>,
@@ -62,6 +67,9 @@ impl<'a> ExecutableCheck<'a> {
             dev: metadata.dev(),
             ino: metadata.ino(),
             size: metadata.len(),
+            mode: metadata.mode(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
             mtime: metadata.modified().ok(),
             ctime: metadata.created().ok(),
         })
@@ -73,14 +81,28 @@ pub struct FileMetadata {
     pub dev: u64,
     pub ino: u64,
     pub size: u64,
+    /// Full `st_mode`, including the file type bits
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
     pub mtime: Option<std::time::SystemTime>,
     pub ctime: Option<std::time::SystemTime>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SearchResult {
     pub path: PathBuf,
     pub canonical_path: Option<PathBuf>,
     pub metadata: Option<FileMetadata>,
+    /// ELF, Mach-O or script details decoded from the file header, filled in by `--stat`
+    pub file_kind: Option<FileKind>,
     pub path_index: usize,
+    /// First line of the version probe output, filled in by `--versions`
+    pub version: Option<String>,
+    /// What a version-manager shim dispatches to, filled in by `--resolve-shims`
+    pub shim: Option<ShimResolution>,
+    /// Every symlink hop from `path`, filled in by `--chain`
+    pub symlink_chain: Option<SymlinkChain>,
+    /// How the name scored against a fuzzy query, shown by `--explain-score`
+    pub score: Option<FuzzyScore>,
 }
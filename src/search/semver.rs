@@ -0,0 +1,255 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A `major.minor.patch` version extracted from tool output
+///
+/// Missing components are treated as 0 and pre-release/build suffixes are
+/// ignored, which is enough to compare what `--version` prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// Find the first dotted version number in `text`, e.g. `3.11.7` in `Python 3.11.7`
+    ///
+    /// A leading `v` or a word prefix like `go1.22.1` is skipped. Bare integers are
+    /// only accepted when no dotted number exists.
+    #[must_use]
+    pub fn find_in(text: &str) -> Option<Version> {
+        let mut fallback = None;
+
+        for candidate in numeric_runs(text) {
+            let parts: Vec<&str> = candidate.split('.').filter(|p| !p.is_empty()).collect();
+            let Some(version) = Version::from_parts(&parts) else {
+                continue;
+            };
+
+            if parts.len() > 1 {
+                return Some(version);
+            }
+            fallback.get_or_insert(version);
+        }
+
+        fallback
+    }
+
+    fn from_parts(parts: &[&str]) -> Option<Version> {
+        let mut numbers = parts.iter().map(|p| p.parse::<u64>().ok());
+        Some(Version {
+            major: numbers.next()??,
+            minor: numbers.next().unwrap_or(Some(0))?,
+            patch: numbers.next().unwrap_or(Some(0))?,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Maximal runs of digits and dots in `text`, trimmed of stray dots
+fn numeric_runs(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|ch: char| !ch.is_ascii_digit() && ch != '.')
+        .map(|run| run.trim_matches('.'))
+        .filter(|run| !run.is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+/// One comparison like `>=3.11` or `^20`; omitted components act as wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl Comparator {
+    fn parse(input: &str) -> Result<Comparator, String> {
+        let input = input.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| input.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((Op::Exact, input));
+
+        let rest = rest.trim().trim_start_matches('v');
+        let mut parts = rest.split('.');
+        let invalid = || format!("Invalid version constraint: {input}");
+
+        let major = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        let minor = parts
+            .next()
+            .map(|p| p.parse().map_err(|_| invalid()))
+            .transpose()?;
+        let patch = parts
+            .next()
+            .map(|p| p.parse().map_err(|_| invalid()))
+            .transpose()?;
+
+        if parts.next().is_some() || (minor.is_none() && patch.is_some()) {
+            return Err(invalid());
+        }
+
+        Ok(Comparator {
+            op,
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// The lowest version this comparator's components describe
+    fn floor(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+        }
+    }
+
+    /// The first version past the range spanned by the given components
+    fn ceiling(&self) -> Version {
+        match (self.minor, self.patch) {
+            (None, _) => Version {
+                major: self.major + 1,
+                minor: 0,
+                patch: 0,
+            },
+            (Some(minor), None) => Version {
+                major: self.major,
+                minor: minor + 1,
+                patch: 0,
+            },
+            (Some(minor), Some(patch)) => Version {
+                major: self.major,
+                minor,
+                patch: patch + 1,
+            },
+        }
+    }
+
+    fn matches(&self, version: Version) -> bool {
+        let floor = self.floor();
+        let ceiling = self.ceiling();
+
+        match self.op {
+            Op::Exact => version >= floor && version < ceiling,
+            Op::Greater => version >= ceiling,
+            Op::GreaterEq => version >= floor,
+            Op::Less => version < floor,
+            Op::LessEq => version < ceiling,
+            Op::Tilde => {
+                // ~1.2.3 allows patch updates, ~1 allows minor updates
+                let upper = if self.minor.is_some() {
+                    Version {
+                        major: self.major,
+                        minor: self.minor.unwrap_or(0) + 1,
+                        patch: 0,
+                    }
+                } else {
+                    ceiling
+                };
+                version >= floor && version < upper
+            }
+            Op::Caret => version >= floor && version < self.caret_ceiling(),
+        }
+    }
+
+    /// Caret ranges allow changes that do not modify the left-most non-zero component
+    fn caret_ceiling(&self) -> Version {
+        match (self.major, self.minor, self.patch) {
+            (0, Some(0), Some(patch)) => Version {
+                major: 0,
+                minor: 0,
+                patch: patch + 1,
+            },
+            (0, Some(minor), _) if minor > 0 || self.patch.is_none() => Version {
+                major: 0,
+                minor: minor + 1,
+                patch: 0,
+            },
+            (major, _, _) => Version {
+                major: major + 1,
+                minor: 0,
+                patch: 0,
+            },
+        }
+    }
+}
+
+/// A comma-separated set of comparators that must all hold, e.g. `>=3.11,<3.13`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    raw: String,
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(input: &str) -> Result<VersionReq, String> {
+        let comparators = input
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(VersionReq {
+            raw: input.trim().to_string(),
+            comparators,
+        })
+    }
+
+    /// Whether `input` is meant as a constraint rather than an index or path
+    ///
+    /// Requires a leading operator so `whi prefer cargo 2` stays index-based and
+    /// `~/bin` stays a path.
+    #[must_use]
+    pub fn looks_like_constraint(input: &str) -> bool {
+        let input = input.trim_start();
+        input.starts_with(['>', '<', '=', '^'])
+            || input
+                .strip_prefix('~')
+                .is_some_and(|rest| rest.starts_with(|ch: char| ch.is_ascii_digit()))
+    }
+
+    #[must_use]
+    pub fn matches(&self, version: Version) -> bool {
+        self.comparators.iter().all(|c| c.matches(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// Order candidates best-first: highest version, then earliest `PATH` position
+#[must_use]
+pub fn compare_candidates(a: (Version, usize), b: (Version, usize)) -> Ordering {
+    b.0.cmp(&a.0).then(a.1.cmp(&b.1))
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::search::index::ExecutableIndex;
+
+/// A single copy of an executable found in a `PATH` directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowEntry {
+    pub path_index: usize,
+    pub path: PathBuf,
+    /// The entry resolves to the same file (device and inode) as the winner
+    pub same_file: bool,
+}
+
+/// An executable name provided by more than one `PATH` directory
+///
+/// `entries` is ordered by `PATH` position, so the first entry is the winner
+/// and every following entry is shadowed by it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowGroup {
+    pub name: String,
+    pub entries: Vec<ShadowEntry>,
+}
+
+impl ShadowGroup {
+    #[must_use]
+    pub fn winner(&self) -> &ShadowEntry {
+        &self.entries[0]
+    }
+
+    #[must_use]
+    pub fn shadowed(&self) -> &[ShadowEntry] {
+        &self.entries[1..]
+    }
+
+    /// True when at least one shadowed copy is a different file than the winner
+    #[must_use]
+    pub fn has_distinct_copies(&self) -> bool {
+        self.shadowed().iter().any(|entry| !entry.same_file)
+    }
+}
+
+/// Find every executable name that exists in more than one `PATH` directory
+///
+/// Directories are visited in order; duplicate `PATH` entries are visited
+/// again so that repeated directories show up as shadowing themselves.
+pub fn find_shadows(dirs: &[PathBuf], index: &mut ExecutableIndex) -> Vec<ShadowGroup> {
+    let mut by_name: BTreeMap<String, Vec<ShadowEntry>> = BTreeMap::new();
+
+    for (idx, dir) in dirs.iter().enumerate() {
+        for name in index.executables(dir) {
+            let path = dir.join(&name);
+            by_name.entry(name).or_default().push(ShadowEntry {
+                path_index: idx + 1,
+                path,
+                same_file: false,
+            });
+        }
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, entries)| entries.len() > 1)
+        .map(|(name, mut entries)| {
+            mark_same_files(&mut entries);
+            ShadowGroup { name, entries }
+        })
+        .collect()
+}
+
+pub(crate) fn mark_same_files(entries: &mut [ShadowEntry]) {
+    let file_id = |path: &Path| fs::metadata(path).ok().map(|m| (m.dev(), m.ino()));
+
+    let Some(winner_id) = file_id(&entries[0].path) else {
+        return;
+    };
+
+    for entry in &mut entries[1..] {
+        entry.same_file = file_id(&entry.path) == Some(winner_id);
+    }
+}
@@ -0,0 +1,522 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use crate::search::explain::{CandidateStatus, classify};
+use crate::search::semver::Version;
+
+/// Bytes of a candidate script read when looking for shim signatures
+const SIGNATURE_PROBE_LEN: u64 = 4096;
+
+/// Version managers whose shims whi knows how to see through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShimKind {
+    Rustup,
+    Pyenv,
+    Asdf,
+    Mise,
+    Nvm,
+}
+
+impl ShimKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShimKind::Rustup => "rustup",
+            ShimKind::Pyenv => "pyenv",
+            ShimKind::Asdf => "asdf",
+            ShimKind::Mise => "mise",
+            ShimKind::Nvm => "nvm",
+        }
+    }
+}
+
+/// What a shim dispatches to in the current directory and environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShimResolution {
+    pub kind: ShimKind,
+    /// The selected toolchain or version, e.g. `stable` or `3.12.1`
+    pub selector: Option<String>,
+    /// Where the selection came from: an environment variable or a file path
+    pub source: Option<String>,
+    /// The executable the shim will run, if it is installed
+    pub target: Option<PathBuf>,
+}
+
+impl ShimResolution {
+    fn unresolved(kind: ShimKind) -> Self {
+        ShimResolution {
+            kind,
+            selector: None,
+            source: None,
+            target: None,
+        }
+    }
+}
+
+/// Detects version-manager shims and resolves them relative to a working directory
+///
+/// Version selection mirrors each manager's own precedence: environment
+/// variables first, then the nearest version file walking up from `cwd`, then
+/// the global default.
+pub struct ShimResolver<'a> {
+    path_dirs: &'a [PathBuf],
+    cwd: PathBuf,
+    home: PathBuf,
+}
+
+impl<'a> ShimResolver<'a> {
+    #[must_use]
+    pub fn new(path_dirs: &'a [PathBuf], cwd: PathBuf) -> Self {
+        let home = env::var("HOME").map(PathBuf::from).unwrap_or_default();
+        ShimResolver {
+            path_dirs,
+            cwd,
+            home,
+        }
+    }
+
+    /// Resolve `path` if it is a known shim
+    #[must_use]
+    pub fn resolve(&self, path: &Path) -> Option<ShimResolution> {
+        let name = path.file_name()?.to_str()?;
+        let kind = self.detect(path, name)?;
+
+        Some(match kind {
+            ShimKind::Rustup => self.resolve_rustup(name),
+            ShimKind::Pyenv => self.resolve_pyenv(path, name),
+            ShimKind::Asdf | ShimKind::Mise => self.resolve_tool_manager(kind, path, name),
+            ShimKind::Nvm => self.resolve_nvm(name),
+        })
+    }
+
+    fn detect(&self, path: &Path, name: &str) -> Option<ShimKind> {
+        let dir = path.parent()?;
+
+        // rustup and mise shims are links to (or copies of) the manager binary itself
+        let real_name = fs::canonicalize(path)
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()));
+        if !name.starts_with("rustup")
+            && (real_name.as_deref() == Some("rustup") || same_file(path, &dir.join("rustup")))
+        {
+            return Some(ShimKind::Rustup);
+        }
+        if name != "mise" && real_name.as_deref() == Some("mise") {
+            return Some(ShimKind::Mise);
+        }
+
+        if dir == self.pyenv_root().join("shims") {
+            return Some(ShimKind::Pyenv);
+        }
+        if dir == self.asdf_dir().join("shims") {
+            return Some(ShimKind::Asdf);
+        }
+        if dir == self.mise_dir().join("shims") {
+            return Some(ShimKind::Mise);
+        }
+
+        let script = read_script_head(path)?;
+        if script.contains("asdf exec") || script.contains("# asdf-plugin:") {
+            Some(ShimKind::Asdf)
+        } else if script.contains("pyenv exec") || script.contains("PYENV_ROOT") {
+            Some(ShimKind::Pyenv)
+        } else if script.contains("mise x") || script.contains("mise exec") {
+            Some(ShimKind::Mise)
+        } else if script.contains("nvm exec") || script.contains("NVM_DIR") {
+            Some(ShimKind::Nvm)
+        } else {
+            None
+        }
+    }
+
+    fn resolve_rustup(&self, name: &str) -> ShimResolution {
+        let rustup_home = env_dir("RUSTUP_HOME").unwrap_or_else(|| self.home.join(".rustup"));
+
+        // rustup checks `rustup override set` before any rust-toolchain file
+        let settings = rustup_home.join("settings.toml");
+        let selection = env_selection("RUSTUP_TOOLCHAIN")
+            .or_else(|| directory_override(&settings, &self.cwd))
+            .or_else(|| self.rust_toolchain_file())
+            .or_else(|| default_toolchain(&settings));
+
+        let Some((toolchain, source)) = selection else {
+            return ShimResolution::unresolved(ShimKind::Rustup);
+        };
+
+        let toolchains = rustup_home.join("toolchains");
+        let toolchain_dir = if toolchains.join(&toolchain).is_dir() {
+            Some(toolchains.join(&toolchain))
+        } else {
+            // `stable` is installed as `stable-<host triple>`
+            sorted_entries(&toolchains)
+                .into_iter()
+                .find(|dir| dir_name(dir).starts_with(&format!("{toolchain}-")))
+        };
+
+        ShimResolution {
+            kind: ShimKind::Rustup,
+            target: toolchain_dir.and_then(|dir| executable_at(&dir.join("bin").join(name))),
+            selector: Some(toolchain),
+            source: Some(source),
+        }
+    }
+
+    fn rust_toolchain_file(&self) -> Option<(String, String)> {
+        for dir in self.cwd.ancestors() {
+            for file_name in ["rust-toolchain.toml", "rust-toolchain"] {
+                let file = dir.join(file_name);
+                let Ok(content) = fs::read_to_string(&file) else {
+                    continue;
+                };
+
+                let channel = toml_string_value(&content, "channel").or_else(|| {
+                    content
+                        .lines()
+                        .map(str::trim)
+                        .find(|line| !line.is_empty() && !line.starts_with('#'))
+                        .filter(|line| !line.contains('='))
+                        .map(str::to_string)
+                });
+
+                if let Some(channel) = channel {
+                    return Some((channel, file.display().to_string()));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn resolve_pyenv(&self, shim: &Path, name: &str) -> ShimResolution {
+        let root = self.pyenv_root();
+
+        let selection = env_selection("PYENV_VERSION")
+            .or_else(|| {
+                let file = self.nearest_file(&[".python-version"])?;
+                let versions = fs::read_to_string(&file).ok()?;
+                let versions: Vec<&str> = versions.split_whitespace().collect();
+                Some((versions.join(":"), file.display().to_string()))
+            })
+            .or_else(|| {
+                let file = root.join("version");
+                let versions = fs::read_to_string(&file).ok()?;
+                let versions: Vec<&str> = versions.split_whitespace().collect();
+                Some((versions.join(":"), file.display().to_string()))
+            })
+            .unwrap_or_else(|| ("system".to_string(), "default".to_string()));
+
+        // pyenv tries each selected version in order until one provides the command
+        let (selector, source) = selection;
+        let target = selector.split(':').find_map(|version| {
+            if version == "system" {
+                self.search_path_excluding(name, shim.parent(), &root)
+            } else {
+                executable_at(&root.join("versions").join(version).join("bin").join(name))
+            }
+        });
+
+        ShimResolution {
+            kind: ShimKind::Pyenv,
+            selector: Some(selector),
+            source: Some(source),
+            target,
+        }
+    }
+
+    fn resolve_tool_manager(&self, kind: ShimKind, shim: &Path, name: &str) -> ShimResolution {
+        let (data_dir, config_files, env_prefix): (PathBuf, &[&str], &str) = match kind {
+            ShimKind::Mise => (
+                self.mise_dir(),
+                &[".tool-versions", "mise.toml", ".mise.toml"],
+                "MISE",
+            ),
+            _ => (self.asdf_dir(), &[".tool-versions"], "ASDF"),
+        };
+        let installs = data_dir.join("installs");
+
+        // asdf shims name the plugins they dispatch to; otherwise try every configured tool
+        let plugins: Vec<String> = read_script_head(shim)
+            .map(|script| {
+                script
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("# asdf-plugin:"))
+                    .filter_map(|rest| rest.split_whitespace().next())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut tools = self.tool_versions(config_files);
+        for plugin in &plugins {
+            let key = format!("{env_prefix}_{}_VERSION", plugin.to_uppercase());
+            if let Some((version, source)) = env_selection(&key) {
+                tools.insert(0, (plugin.clone(), version, source));
+            }
+        }
+
+        for (tool, version, source) in tools {
+            if !plugins.is_empty() && !plugins.contains(&tool) {
+                continue;
+            }
+
+            let Some(install) = best_install(&installs.join(&tool), &version) else {
+                continue;
+            };
+
+            if let Some(target) = executable_at(&install.join("bin").join(name)) {
+                return ShimResolution {
+                    kind,
+                    selector: Some(format!("{tool} {version}")),
+                    source: Some(source),
+                    target: Some(target),
+                };
+            }
+        }
+
+        ShimResolution::unresolved(kind)
+    }
+
+    fn resolve_nvm(&self, name: &str) -> ShimResolution {
+        let nvm_dir = env_dir("NVM_DIR").unwrap_or_else(|| self.home.join(".nvm"));
+
+        let read_first_line = |file: &Path| {
+            fs::read_to_string(file)
+                .ok()?
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        };
+
+        let selection = self
+            .nearest_file(&[".nvmrc"])
+            .and_then(|file| Some((read_first_line(&file)?, file.display().to_string())))
+            .or_else(|| {
+                let file = nvm_dir.join("alias").join("default");
+                Some((read_first_line(&file)?, file.display().to_string()))
+            });
+
+        let Some((version, source)) = selection else {
+            return ShimResolution::unresolved(ShimKind::Nvm);
+        };
+
+        let wanted = match version.as_str() {
+            "node" | "stable" => String::new(),
+            other => other.trim_start_matches('v').to_string(),
+        };
+
+        ShimResolution {
+            kind: ShimKind::Nvm,
+            target: best_install(&nvm_dir.join("versions").join("node"), &wanted)
+                .and_then(|dir| executable_at(&dir.join("bin").join(name))),
+            selector: Some(version),
+            source: Some(source),
+        }
+    }
+
+    /// `(tool, version, source)` from the nearest tool-version files, nearest first
+    fn tool_versions(&self, file_names: &[&str]) -> Vec<(String, String, String)> {
+        let mut tools = Vec::new();
+        let global = self.home.join(".config").join("mise").join("config.toml");
+
+        let files = self
+            .cwd
+            .ancestors()
+            .flat_map(|dir| file_names.iter().map(move |name| dir.join(name)))
+            .chain([self.home.join(".tool-versions"), global]);
+
+        for file in files {
+            let Ok(content) = fs::read_to_string(&file) else {
+                continue;
+            };
+            let source = file.display().to_string();
+
+            let is_toml = file.extension().is_some_and(|ext| ext == "toml");
+            let entries = if is_toml {
+                toml_section_entries(&content, "tools")
+            } else {
+                content
+                    .lines()
+                    .map(|line| line.split('#').next().unwrap_or("").trim())
+                    .filter_map(|line| {
+                        let mut parts = line.split_whitespace();
+                        Some((parts.next()?.to_string(), parts.next()?.to_string()))
+                    })
+                    .collect()
+            };
+
+            for (tool, version) in entries {
+                if !tools.iter().any(|(t, _, _)| *t == tool) {
+                    tools.push((tool, version, source.clone()));
+                }
+            }
+        }
+
+        tools
+    }
+
+    fn nearest_file(&self, names: &[&str]) -> Option<PathBuf> {
+        self.cwd
+            .ancestors()
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|file| file.is_file())
+    }
+
+    /// First executable `name` on `PATH` outside the shim directory and manager root
+    fn search_path_excluding(
+        &self,
+        name: &str,
+        shim_dir: Option<&Path>,
+        root: &Path,
+    ) -> Option<PathBuf> {
+        self.path_dirs
+            .iter()
+            .filter(|dir| Some(dir.as_path()) != shim_dir && !dir.starts_with(root))
+            .find_map(|dir| executable_at(&dir.join(name)))
+    }
+
+    fn pyenv_root(&self) -> PathBuf {
+        env_dir("PYENV_ROOT").unwrap_or_else(|| self.home.join(".pyenv"))
+    }
+
+    fn asdf_dir(&self) -> PathBuf {
+        env_dir("ASDF_DATA_DIR").unwrap_or_else(|| self.home.join(".asdf"))
+    }
+
+    fn mise_dir(&self) -> PathBuf {
+        env_dir("MISE_DATA_DIR")
+            .unwrap_or_else(|| self.home.join(".local").join("share").join("mise"))
+    }
+}
+
+fn env_dir(key: &str) -> Option<PathBuf> {
+    env::var(key)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn env_selection(key: &str) -> Option<(String, String)> {
+    env::var(key)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(|value| (value, format!("${key}")))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+fn executable_at(path: &Path) -> Option<PathBuf> {
+    (classify(path) == CandidateStatus::Executable).then(|| path.to_path_buf())
+}
+
+fn read_script_head(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let mut head = Vec::new();
+    file.take(SIGNATURE_PROBE_LEN).read_to_end(&mut head).ok()?;
+
+    if !head.starts_with(b"#!") {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+/// The install directory for `version`, accepting prefixes like `20` for `20.11.0`
+///
+/// An exact directory wins; otherwise the highest installed version whose
+/// name (ignoring a leading `v`) starts with `version` is used. An empty
+/// `version` selects the highest installed version.
+fn best_install(dir: &Path, version: &str) -> Option<PathBuf> {
+    if !version.is_empty() {
+        for name in [version.to_string(), format!("v{version}")] {
+            if dir.join(&name).is_dir() {
+                return Some(dir.join(name));
+            }
+        }
+    }
+
+    sorted_entries(dir)
+        .into_iter()
+        .filter(|path| {
+            let name = dir_name(path);
+            let name = name.trim_start_matches('v');
+            version.is_empty() || name == version || name.starts_with(&format!("{version}."))
+        })
+        .filter_map(|path| Some((Version::find_in(&dir_name(&path))?, path)))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, path)| path)
+}
+
+fn toml_string_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
+    })
+}
+
+/// `key = "value"` pairs inside `[section]` of a simple TOML file
+fn toml_section_entries(content: &str, section: &str) -> Vec<(String, String)> {
+    let mut current = String::new();
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') && line.ends_with(']') {
+            current = line[1..line.len() - 1].trim().to_string();
+            continue;
+        }
+
+        if current == section
+            && let Some((key, value)) = line.split_once('=')
+        {
+            let value = value.trim().trim_matches('"');
+            // `node = ["20", "18"]` and `{ version = .. }` tables are not supported
+            if !value.starts_with(['[', '{']) {
+                entries.push((key.trim().trim_matches('"').to_string(), value.to_string()));
+            }
+        }
+    }
+
+    entries
+}
+
+/// The directory override in rustup's `settings.toml` covering `cwd`; the
+/// longest matching directory wins
+fn directory_override(settings: &Path, cwd: &Path) -> Option<(String, String)> {
+    let content = fs::read_to_string(settings).ok()?;
+
+    toml_section_entries(&content, "overrides")
+        .into_iter()
+        .filter(|(dir, _)| cwd.starts_with(dir))
+        .max_by_key(|(dir, _)| dir.len())
+        .map(|(_, toolchain)| (toolchain, settings.display().to_string()))
+}
+
+/// `default_toolchain` from rustup's `settings.toml`
+fn default_toolchain(settings: &Path) -> Option<(String, String)> {
+    let content = fs::read_to_string(settings).ok()?;
+
+    toml_string_value(&content, "default_toolchain")
+        .map(|toolchain| (toolchain, settings.display().to_string()))
+}
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::search::explain::{CandidateStatus, classify};
+use crate::search::index::ExecutableIndex;
+
+/// How many similar names are suggested for a missing one
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 3;
+
+/// An executable on `PATH` whose name is close to the one asked for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearMatch {
+    pub name: String,
+    pub distance: usize,
+    /// 1-based index of the `PATH` entry that provides it
+    pub path_index: usize,
+    pub path: PathBuf,
+}
+
+/// The exact name, found in an install directory that is not on `PATH`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffPathMatch {
+    pub dir: PathBuf,
+    pub path: PathBuf,
+}
+
+impl OffPathMatch {
+    /// The `whi add` command that would put this match on `PATH`
+    #[must_use]
+    pub fn add_command(&self) -> String {
+        add_command(&self.dir)
+    }
+}
+
+/// `whi add DIR`, with `$HOME` shortened to `~`
+#[must_use]
+pub fn add_command(dir: &Path) -> String {
+    let home = env::var("HOME").ok().filter(|home| !home.is_empty());
+    let dir = match home.as_deref().and_then(|home| dir.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => dir.display().to_string(),
+    };
+    format!("whi add {dir}")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Suggestions {
+    /// Closest names first
+    pub near: Vec<NearMatch>,
+    pub off_path: Vec<OffPathMatch>,
+}
+
+impl Suggestions {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.near.is_empty() && self.off_path.is_empty()
+    }
+}
+
+/// Suggest what the user may have meant by a `name` that is not on `PATH`
+///
+/// Names on `PATH` within a small edit distance are listed closest first,
+/// keeping only the winning copy of each. `install_dirs` that are not on
+/// `PATH` are checked for an executable called exactly `name`.
+pub fn suggest(
+    name: &str,
+    path_dirs: &[PathBuf],
+    install_dirs: &[PathBuf],
+    index: &mut ExecutableIndex,
+    limit: usize,
+) -> Suggestions {
+    let max_distance = (name.chars().count() / 3).clamp(1, 3);
+    let mut seen = HashSet::new();
+    let mut near = Vec::new();
+
+    for (idx, dir) in path_dirs.iter().enumerate() {
+        for candidate in index.executables(dir) {
+            if candidate == name || !seen.insert(candidate.clone()) {
+                continue;
+            }
+            if let Some(distance) = bounded_distance(name, &candidate, max_distance) {
+                near.push(NearMatch {
+                    path: dir.join(&candidate),
+                    name: candidate,
+                    distance,
+                    path_index: idx + 1,
+                });
+            }
+        }
+    }
+
+    near.sort_by(|a, b| {
+        (a.distance, a.path_index, &a.name).cmp(&(b.distance, b.path_index, &b.name))
+    });
+    near.truncate(limit);
+
+    let on_path: HashSet<PathBuf> = path_dirs.iter().flat_map(|dir| same_dirs(dir)).collect();
+    let off_path = install_dirs
+        .iter()
+        .filter(|dir| !same_dirs(dir).iter().any(|d| on_path.contains(d)))
+        .map(|dir| OffPathMatch {
+            dir: dir.clone(),
+            path: dir.join(name),
+        })
+        .filter(|found| classify(&found.path) == CandidateStatus::Executable)
+        .collect();
+
+    Suggestions { near, off_path }
+}
+
+/// A directory as written and, if different, as resolved on disk
+pub(crate) fn same_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.to_path_buf()];
+    if let Ok(canonical) = fs::canonicalize(dir)
+        && canonical != dir
+    {
+        dirs.push(canonical);
+    }
+    dirs
+}
+
+/// Directories tools commonly install into but that are often missing from `PATH`
+///
+/// `$CARGO_HOME/bin`, `$GOBIN` and `$GOPATH/bin` are used when set, in place
+/// of their usual defaults.
+#[must_use]
+pub fn well_known_install_dirs() -> Vec<PathBuf> {
+    let var = |key: &str| {
+        env::var_os(key)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let home = var("HOME");
+    let in_home = |rest: &str| home.as_ref().map(|home| home.join(rest));
+
+    let mut dirs: Vec<PathBuf> = [
+        var("CARGO_HOME")
+            .map(|cargo| cargo.join("bin"))
+            .or_else(|| in_home(".cargo/bin")),
+        in_home(".local/bin"),
+        var("GOBIN")
+            .or_else(|| var("GOPATH").map(|go| go.join("bin")))
+            .or_else(|| in_home("go/bin")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if let Ok(entries) = fs::read_dir("/opt") {
+        let mut opt: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path().join("bin"))
+            .filter(|bin| bin.is_dir())
+            .collect();
+        opt.sort();
+        dirs.extend(opt);
+    }
+
+    dirs
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and swaps of adjacent characters each cost one
+#[must_use]
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// The edit distance if it is at most `max`, skipping names whose length
+/// alone rules them out
+fn bounded_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    if a.chars().count().abs_diff(b.chars().count()) > max {
+        return None;
+    }
+    Some(edit_distance(a, b)).filter(|&distance| distance <= max)
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Hops followed before a chain is reported as a loop, matching the usual `ELOOP` limit
+const MAX_HOPS: usize = 40;
+
+/// One `read_link` step in a symlink chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkHop {
+    /// Where this hop lands, made absolute against the link's directory
+    pub path: PathBuf,
+    /// The link target exactly as stored on disk
+    pub target: PathBuf,
+    /// True when the stored target was relative to the link's directory
+    pub relative: bool,
+}
+
+/// How a symlink chain ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainStatus {
+    /// The last hop is not a symlink
+    Resolved,
+    /// A hop pointed back at an earlier link, or the chain was too long
+    Loop,
+    /// A hop points at something that does not exist
+    Broken,
+}
+
+impl ChainStatus {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChainStatus::Resolved => "resolved",
+            ChainStatus::Loop => "loop",
+            ChainStatus::Broken => "broken",
+        }
+    }
+}
+
+/// Every hop from a path to its final target
+///
+/// `hops` is empty when the path is not a symlink. Relative targets are joined
+/// to the link's directory and shown as written, except that each `..` is
+/// folded against the canonical directory it leaves, as the kernel does when
+/// that directory is reached through a symlink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymlinkChain {
+    pub hops: Vec<SymlinkHop>,
+    pub status: ChainStatus,
+}
+
+/// Follow `path` one `read_link` at a time
+#[must_use]
+pub fn resolve_chain(path: &Path) -> SymlinkChain {
+    let mut hops = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = path.to_path_buf();
+
+    loop {
+        let Ok(metadata) = fs::symlink_metadata(&current) else {
+            return SymlinkChain {
+                hops,
+                status: ChainStatus::Broken,
+            };
+        };
+
+        if !metadata.file_type().is_symlink() {
+            return SymlinkChain {
+                hops,
+                status: ChainStatus::Resolved,
+            };
+        }
+
+        let Ok(target) = fs::read_link(&current) else {
+            return SymlinkChain {
+                hops,
+                status: ChainStatus::Broken,
+            };
+        };
+
+        let relative = target.is_relative();
+        let base = current.parent().unwrap_or_else(|| Path::new("/"));
+        let next = hop_destination(base, &target);
+
+        visited.insert(current);
+        let looped = visited.contains(&next) || hops.len() >= MAX_HOPS;
+
+        hops.push(SymlinkHop {
+            path: next.clone(),
+            target,
+            relative,
+        });
+
+        if looped {
+            return SymlinkChain {
+                hops,
+                status: ChainStatus::Loop,
+            };
+        }
+
+        current = next;
+    }
+}
+
+/// Where a link in `dir` storing `target` lands
+fn hop_destination(dir: &Path, target: &Path) -> PathBuf {
+    let mut dest = dir.to_path_buf();
+
+    for component in target.components() {
+        match component {
+            Component::ParentDir => {
+                let left = if dest.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    dest.as_path()
+                };
+                match fs::canonicalize(left) {
+                    Ok(real) => {
+                        dest = real;
+                        dest.pop();
+                    }
+                    // Kept so the lookup fails the way the kernel's would
+                    Err(_) => dest.push(".."),
+                }
+            }
+            Component::CurDir => {}
+            other => dest.push(other),
+        }
+    }
+
+    dest
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::config::runtime::{VersionsConfig, get_cache_dir};
+use crate::io::atomic_file::AtomicFile;
+use crate::search::result::ExecutableCheck;
+
+const CACHE_HEADER: &str = "!whi.versions 1";
+
+/// Longest version line kept; anything after this is cut off
+const MAX_VERSION_LEN: usize = 200;
+
+/// Bytes read from each output stream before giving up on finding a newline
+const MAX_CAPTURE: u64 = 4096;
+
+/// Identity of an executable file: (dev, ino, mtime in nanoseconds, version argument)
+type CacheKey = (u64, u64, u128, String);
+
+/// Runs matched executables with a version argument and remembers the answer
+///
+/// Results are cached in `~/.whi/cache/versions`, keyed by the file's device,
+/// inode and mtime plus the argument used, so an upgraded binary is probed again.
+/// Probes that fail or print no version are cached as unknown to avoid
+/// re-running them; timed-out probes are not cached, so a slow run is retried.
+pub struct VersionProbe {
+    config: VersionsConfig,
+    file: Option<PathBuf>,
+    cache: HashMap<CacheKey, String>,
+    dirty: bool,
+}
+
+impl VersionProbe {
+    /// Create a probe, loading the on-disk cache unless `use_cache` is false
+    #[must_use]
+    pub fn new(config: VersionsConfig, use_cache: bool) -> Self {
+        let file = if use_cache {
+            version_cache_path().ok()
+        } else {
+            None
+        };
+
+        let cache = file
+            .as_ref()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .map(|content| parse_cache(&content))
+            .unwrap_or_default();
+
+        VersionProbe {
+            config,
+            file,
+            cache,
+            dirty: false,
+        }
+    }
+
+    /// First line printed by `path <version arg>`, or `None` if unknown
+    ///
+    /// Non-executable files are never run.
+    pub fn probe(&mut self, path: &Path) -> Option<String> {
+        let checker = ExecutableCheck::new(path);
+        if !checker.is_executable() {
+            return None;
+        }
+
+        let name = path.file_name()?.to_str()?;
+        let arg = self.config.arg_for(name).to_string();
+
+        let metadata = checker.get_file_metadata()?;
+        let mtime = metadata
+            .mtime
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        let key = (metadata.dev, metadata.ino, mtime, arg);
+
+        if let Some(version) = self.cache.get(&key) {
+            return (!version.is_empty()).then(|| version.clone());
+        }
+
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let version = match run_version_command(path, &key.3, timeout) {
+            ProbeOutcome::Version(version) => version,
+            ProbeOutcome::NoVersion => String::new(),
+            ProbeOutcome::Inconclusive => return None,
+        };
+
+        self.cache.insert(key, version.clone());
+        self.dirty = true;
+
+        (!version.is_empty()).then_some(version)
+    }
+
+    /// Write newly probed versions back to disk
+    pub fn save(&mut self) -> Result<(), String> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {e}"))?;
+        }
+
+        let mut atomic_file = AtomicFile::new(file)
+            .map_err(|e| format!("Failed to create version cache file: {e}"))?;
+
+        atomic_file
+            .write_all(format_cache(&self.cache).as_bytes())
+            .map_err(|e| format!("Failed to write version cache: {e}"))?;
+
+        atomic_file
+            .commit()
+            .map_err(|e| format!("Failed to commit version cache file: {e}"))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Remove the on-disk version cache
+    pub fn clear() -> Result<(), String> {
+        let file = version_cache_path()?;
+        if file.exists() {
+            fs::remove_file(&file).map_err(|e| format!("Failed to remove version cache: {e}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// What running a version probe found out
+enum ProbeOutcome {
+    Version(String),
+    /// Exited non-zero or printed no version line; remembered as unknown
+    NoVersion,
+    /// Could not be started or did not finish in time; tried again next run
+    Inconclusive,
+}
+
+fn version_cache_path() -> Result<PathBuf, String> {
+    Ok(get_cache_dir()?.join("versions"))
+}
+
+/// Run `path arg` with a hard timeout and return the first non-empty output line
+///
+/// stdout is preferred; stderr is used for tools like `java -version` that
+/// print their version there. The child is killed if it outlives `timeout`,
+/// and a non-zero exit status counts as no version.
+fn run_version_command(path: &Path, arg: &str, timeout: Duration) -> ProbeOutcome {
+    let Ok(mut child) = Command::new(path)
+        .args(arg.split_whitespace())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    else {
+        return ProbeOutcome::Inconclusive;
+    };
+
+    let deadline = Instant::now() + timeout;
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return ProbeOutcome::Inconclusive;
+    };
+    let (stdout, stderr) = (capture(stdout), capture(stderr));
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                child.kill().ok();
+                child.wait().ok();
+                return ProbeOutcome::Inconclusive;
+            }
+        }
+    };
+
+    // Tools that reject the argument usually exit non-zero with a usage message
+    if !status.success() {
+        return ProbeOutcome::NoVersion;
+    }
+
+    // A grandchild may keep the pipes open; never wait past the deadline for them
+    let remaining = || deadline.saturating_duration_since(Instant::now());
+    let out = stdout.recv_timeout(remaining());
+    let err = stderr.recv_timeout(remaining());
+    let timed_out = out.is_err() || err.is_err();
+
+    match first_line(&out.unwrap_or_default()).or_else(|| first_line(&err.unwrap_or_default())) {
+        Some(version) => ProbeOutcome::Version(version),
+        None if timed_out => ProbeOutcome::Inconclusive,
+        None => ProbeOutcome::NoVersion,
+    }
+}
+
+fn capture<R: Read + Send + 'static>(stream: R) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        stream.take(MAX_CAPTURE).read_to_end(&mut buf).ok();
+        tx.send(buf).ok();
+    });
+    rx
+}
+
+fn first_line(output: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(output);
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+
+    // Control characters would corrupt terminal output and the cache format
+    let line: String = line
+        .chars()
+        .filter(|ch| !ch.is_control())
+        .take(MAX_VERSION_LEN)
+        .collect();
+
+    (!line.is_empty()).then_some(line)
+}
+
+fn format_cache(cache: &HashMap<CacheKey, String>) -> String {
+    let mut entries: Vec<(&CacheKey, &String)> = cache.iter().collect();
+    entries.sort();
+
+    let mut output = String::from(CACHE_HEADER);
+    output.push('\n');
+
+    for ((dev, ino, mtime, arg), version) in entries {
+        if arg.contains(['\t', '\n']) {
+            continue;
+        }
+        writeln!(output, "{dev}\t{ino}\t{mtime}\t{arg}\t{version}").ok();
+    }
+
+    output
+}
+
+fn parse_cache(content: &str) -> HashMap<CacheKey, String> {
+    let mut lines = content.lines();
+    if lines.next() != Some(CACHE_HEADER) {
+        return HashMap::new();
+    }
+
+    lines
+        .filter_map(|line| {
+            let mut parts = line.splitn(5, '\t');
+            let dev = parts.next()?.parse().ok()?;
+            let ino = parts.next()?.parse().ok()?;
+            let mtime = parts.next()?.parse().ok()?;
+            let arg = parts.next()?.to_string();
+            let version = parts.next()?.to_string();
+            Some(((dev, ino, mtime, arg), version))
+        })
+        .collect()
+}
@@ -0,0 +1,47 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use crate::search::index::ExecutableIndex;
+
+/// A name that would resolve to a different copy, or stop or start
+/// resolving, after a `PATH` change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinnerChange {
+    pub name: String,
+    pub before: Option<PathBuf>,
+    pub after: Option<PathBuf>,
+}
+
+/// Every name whose winning copy differs between `before` and `after`,
+/// sorted by name
+pub fn winner_changes(
+    before: &[PathBuf],
+    after: &[PathBuf],
+    index: &mut ExecutableIndex,
+) -> Vec<WinnerChange> {
+    let old = winners(before, index);
+    let new = winners(after, index);
+    let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    names
+        .into_iter()
+        .filter(|name| old.get(*name) != new.get(*name))
+        .map(|name| WinnerChange {
+            name: name.clone(),
+            before: old.get(name).cloned(),
+            after: new.get(name).cloned(),
+        })
+        .collect()
+}
+
+fn winners(dirs: &[PathBuf], index: &mut ExecutableIndex) -> HashMap<String, PathBuf> {
+    let mut winners = HashMap::new();
+    for dir in dirs {
+        for name in index.executables(dir) {
+            winners
+                .entry(name)
+                .or_insert_with_key(|name| dir.join(name));
+        }
+    }
+    winners
+}
@@ -17,6 +17,14 @@ pub struct HistoryFiles {
     pub cursor_file: PathBuf,
 }
 
+/// A recorded `PATH` value and when it was written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub path: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct HistoryContext {
     files: HistoryFiles,
@@ -42,6 +50,10 @@ impl HistoryContext {
         read_snapshots(&self.files)
     }
 
+    pub fn read_timed_snapshots(&self) -> Result<Vec<Snapshot>, String> {
+        read_timed_snapshots(&self.files)
+    }
+
     pub fn initial_snapshot(&self) -> Result<Option<String>, String> {
         Ok(self.read_snapshots()?.into_iter().next())
     }
@@ -166,6 +178,13 @@ fn write_snapshot(
 }
 
 fn read_snapshots(files: &HistoryFiles) -> Result<Vec<String>, String> {
+    Ok(read_timed_snapshots(files)?
+        .into_iter()
+        .map(|snapshot| snapshot.path)
+        .collect())
+}
+
+fn read_timed_snapshots(files: &HistoryFiles) -> Result<Vec<Snapshot>, String> {
     if !files.history_file.exists() {
         return Ok(Vec::new());
     }
@@ -179,7 +198,10 @@ fn read_snapshots(files: &HistoryFiles) -> Result<Vec<String>, String> {
         if let Some(rest) = line.strip_prefix("SNAPSHOT:") {
             let parts: Vec<&str> = rest.splitn(2, ':').collect();
             if parts.len() >= 2 {
-                snapshots.push(parts[1].to_string());
+                snapshots.push(Snapshot {
+                    timestamp: parts[0].parse().unwrap_or(0),
+                    path: parts[1].to_string(),
+                });
             }
         }
     }
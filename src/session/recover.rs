@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::search::explain::{CandidateStatus, classify};
+use crate::search::suggest::{add_command, same_dirs};
+use crate::session::history::Snapshot;
+
+/// Where a command that is missing from `PATH` can still be found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// A `PATH` entry dropped earlier in this session, with the time of the
+    /// first snapshot without it if the removal was recorded
+    Removed { removed_at: Option<u64> },
+    /// An entry of a saved profile's `PATH`
+    Profile(String),
+    /// A well-known install directory that was never on `PATH`
+    InstallDir,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    pub origin: Origin,
+    pub dir: PathBuf,
+    pub path: PathBuf,
+}
+
+impl Recovery {
+    /// The `whi` command that puts `dir` back on `PATH`
+    #[must_use]
+    pub fn restore_command(&self) -> String {
+        add_command(&self.dir)
+    }
+}
+
+/// Look for an executable `name` in directories that are not on `current_path`
+///
+/// Directories removed this session come first, most recently present first,
+/// then saved profiles (`(name, PATH)` pairs) in order, then `install_dirs`.
+/// Each directory is reported once, under the first origin it is found in.
+#[must_use]
+pub fn find_recoveries(
+    name: &str,
+    current_path: &str,
+    snapshots: &[Snapshot],
+    profiles: &[(String, String)],
+    install_dirs: &[PathBuf],
+) -> Vec<Recovery> {
+    let mut seen: HashSet<PathBuf> = entries(current_path).flat_map(same_dirs).collect();
+    let mut found = Vec::new();
+    let mut consider = |dir: &Path, origin: Origin| {
+        let dirs = same_dirs(dir);
+        if dirs.iter().any(|d| seen.contains(d)) {
+            return;
+        }
+        let path = dir.join(name);
+        if classify(&path) == CandidateStatus::Executable {
+            seen.extend(dirs);
+            found.push(Recovery {
+                origin,
+                dir: dir.to_path_buf(),
+                path,
+            });
+        }
+    };
+
+    for (index, snapshot) in snapshots.iter().enumerate().rev() {
+        for dir in entries(&snapshot.path) {
+            // Walking backwards, the first sighting of a directory is its last
+            let removed_at = snapshots.get(index + 1).map(|next| next.timestamp);
+            consider(dir, Origin::Removed { removed_at });
+        }
+    }
+
+    for (profile, path) in profiles {
+        for dir in entries(path) {
+            consider(dir, Origin::Profile(profile.clone()));
+        }
+    }
+
+    for dir in install_dirs {
+        consider(dir, Origin::InstallDir);
+    }
+
+    found
+}
+
+fn entries(path: &str) -> impl Iterator<Item = &Path> {
+    path.split(':').filter(|e| !e.is_empty()).map(Path::new)
+}
@@ -1,11 +1,13 @@
 use std::env;
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Write};
 use std::path::PathBuf;
 
 #[cfg(unix)]
 use std::os::unix::fs::DirBuilderExt;
 
+use crate::io::atomic_file::AtomicFile;
+use crate::path::pins::{Pin, format_pins, parse_pins};
 use crate::platform;
 use crate::session::history::HistoryContext;
 
@@ -51,6 +53,43 @@ pub fn get_session_file(pid: u32) -> Result<PathBuf, String> {
     Ok(session_dir.join(format!("session_{pid}.log")))
 }
 
+/// Get path to the pins file for given `PID`
+pub fn get_pins_file(pid: u32) -> Result<PathBuf, String> {
+    Ok(get_session_file(pid)?.with_extension("pins"))
+}
+
+/// Read the pins set in this session, sorted by position
+pub fn read_session_pins(pid: u32) -> Result<Vec<Pin>, String> {
+    let pins_file = get_pins_file(pid)?;
+    if !pins_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(&pins_file).map_err(|e| format!("Failed to read pins file: {e}"))?;
+    parse_pins(&content)
+}
+
+/// Replace the pins set in this session
+pub fn write_session_pins(pid: u32, pins: &[Pin]) -> Result<(), String> {
+    let pins_file = get_pins_file(pid)?;
+    if pins.is_empty() {
+        if pins_file.exists() {
+            fs::remove_file(&pins_file).map_err(|e| format!("Failed to remove pins file: {e}"))?;
+        }
+        return Ok(());
+    }
+
+    let mut atomic_file =
+        AtomicFile::new(&pins_file).map_err(|e| format!("Failed to create pins file: {e}"))?;
+    atomic_file
+        .write_all(format_pins(pins).as_bytes())
+        .map_err(|e| format!("Failed to write pins file: {e}"))?;
+    atomic_file
+        .commit()
+        .map_err(|e| format!("Failed to commit pins file: {e}"))
+}
+
 /// Write `PATH` snapshot to session log
 pub fn write_path_snapshot(pid: u32, path_string: &str) -> Result<(), String> {
     HistoryContext::global(pid)?.write_snapshot(path_string)
@@ -145,6 +184,7 @@ pub fn cleanup_old_sessions() -> Result<usize, String> {
 
     for (path, _) in session_files.iter().take(files_to_delete) {
         if fs::remove_file(path).is_ok() {
+            fs::remove_file(path.with_extension("pins")).ok();
             deleted_count += 1;
         }
     }
@@ -6,9 +6,21 @@ function __whi_run
     $__WHI_BIN $argv
 end
 
+# Number of arguments, not counting --dry-run and --force
+function __whi_argc
+    count (string match -v -r -- '^--(dry-run|force)$' $argv)
+end
+
 function __whi_apply
     set -l subcmd $argv[1]
     set -l rest $argv[2..-1]
+
+    # A dry run prints a preview instead of a PATH to export
+    if contains -- --dry-run $rest
+        __whi_run __$subcmd $rest
+        return $status
+    end
+
     set -l new_path (__whi_run __$subcmd $rest)
     set -l exit_code $status
     if test $exit_code -ne 0
@@ -20,7 +32,7 @@ end
 
 function __whi_handle_move --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -ne 2
+    if test (__whi_argc $args) -ne 2
         echo "Usage: $display FROM TO" >&2
         return 2
     end
@@ -29,7 +41,7 @@ end
 
 function __whi_handle_switch --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -ne 2
+    if test (__whi_argc $args) -ne 2
         echo "Usage: $display IDX1 IDX2" >&2
         return 2
     end
@@ -37,17 +49,12 @@ function __whi_handle_switch --argument-names display
 end
 
 function __whi_handle_clean --argument-names display
-    set -l args $argv[2..-1]
-    if test (count $args) -ne 0
-        echo "Usage: $display" >&2
-        return 2
-    end
-    __whi_apply clean
+    __whi_apply clean $argv[2..-1]
 end
 
 function __whi_handle_delete --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -lt 1
+    if test (__whi_argc $args) -lt 1
         echo "Usage: $display TARGET [TARGET...]" >&2
         return 2
     end
@@ -56,8 +63,8 @@ end
 
 function __whi_handle_add --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -lt 1
-        echo "Usage: $display PATH..." >&2
+    if test (__whi_argc $args) -lt 1
+        echo "Usage: $display [--append | --at N | --before ANCHOR | --after ANCHOR] [--move-if-present] PATH..." >&2
         return 2
     end
     __whi_apply add $args
@@ -65,7 +72,7 @@ end
 
 function __whi_handle_prefer --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -lt 1
+    if test (__whi_argc $args) -lt 1
         echo "Usage: $display [NAME] TARGET [PATTERN...]" >&2
         return 2
     end
@@ -81,29 +88,29 @@ end
 
 function __whi_handle_redo --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -gt 1
+    if test (__whi_argc $args) -gt 1
         echo "Usage: $display [COUNT]" >&2
         return 2
     end
 
-    if test (count $args) -eq 0
-        __whi_apply redo 1
+    if test (__whi_argc $args) -eq 0
+        __whi_apply redo 1 $args
     else
-        __whi_apply redo $args[1]
+        __whi_apply redo $args
     end
 end
 
 function __whi_handle_undo --argument-names display
     set -l args $argv[2..-1]
-    if test (count $args) -gt 1
+    if test (__whi_argc $args) -gt 1
         echo "Usage: $display [COUNT]" >&2
         return 2
     end
 
-    if test (count $args) -eq 0
-        __whi_apply undo 1
+    if test (__whi_argc $args) -eq 0
+        __whi_apply undo 1 $args
     else
-        __whi_apply undo $args[1]
+        __whi_apply undo $args
     end
 end
 
@@ -114,6 +121,25 @@ if test -f ~/.whi/saved_path_fish
     end
 end
 
+# Keep any existing handler to fall back on, but not our own on re-source
+if functions -q fish_command_not_found
+    and not string match -q '*__not_found*' -- (functions fish_command_not_found)
+    functions -e __whi_prev_fish_command_not_found
+    functions -c fish_command_not_found __whi_prev_fish_command_not_found
+end
+
+function fish_command_not_found
+    # Succeeds after explaining where the command went
+    if __whi_run __not_found $argv[1]
+        return
+    end
+    if functions -q __whi_prev_fish_command_not_found
+        __whi_prev_fish_command_not_found $argv
+    else
+        __fish_default_command_not_found_handler $argv
+    end
+end
+
 function whim
     __whi_handle_move whim $argv
 end
@@ -155,11 +181,11 @@ function whiu
 end
 
 function whil
-    if test (count $argv) -ne 1
+    if test (__whi_argc $argv) -ne 1
         echo "Usage: whil NAME" >&2
         return 2
     end
-    __whi_apply load $argv[1]
+    __whi_apply load $argv
 end
 
 function whish
@@ -177,23 +203,38 @@ function whi
 
     switch $cmd
         case reset
-            if test (count $rest) -ne 0
+            if test (__whi_argc $rest) -ne 0
                 echo "Usage: whi reset" >&2
                 return 2
             end
-            __whi_apply reset
+            __whi_apply reset $rest
         case undo
             __whi_handle_undo "whi undo" $rest
         case redo
             __whi_handle_redo "whi redo" $rest
         case load
-            if test (count $rest) -ne 1
+            if test (__whi_argc $rest) -ne 1
                 echo "Usage: whi load NAME" >&2
                 return 2
             end
-            __whi_apply load $rest[1]
+            __whi_apply load $rest
         case add
             __whi_handle_add "whi add" $rest
+        case batch
+            __whi_apply batch $rest
+        case pin
+            if test (__whi_argc $rest) -lt 1
+                echo "Usage: whi pin INDEX|PATH [--position N] [--save]" >&2
+                return 2
+            end
+            __whi_apply pin $rest
+        case enforce
+            # Checking only reports, so there is no PATH to export
+            if contains -- --check $rest
+                __whi_run enforce $rest
+            else
+                __whi_apply enforce $rest
+            end
         case prefer
             __whi_handle_prefer "whi prefer" $rest
         case move
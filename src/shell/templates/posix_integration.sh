@@ -6,10 +6,30 @@ __whi_exec() {
     "$__WHI_BIN" "$@"
 }
 
+# Number of arguments, not counting --dry-run and --force
+__whi_argc() {
+    local count=0 arg
+    for arg in "$@"; do
+        case "$arg" in
+            --dry-run | --force) ;;
+            *) count=$((count + 1)) ;;
+        esac
+    done
+    echo "$count"
+}
+
 __whi_apply_path() {
     local subcmd="$1"
     shift
 
+    # A dry run prints a preview instead of a PATH to export
+    case " $* " in
+        *" --dry-run "*)
+            __whi_exec "__${subcmd}" "$@"
+            return $?
+            ;;
+    esac
+
     local new_path
     new_path=$(__whi_exec "__${subcmd}" "$@")
     local exit_code=$?
@@ -24,37 +44,32 @@ __whi_apply_path() {
 __whi_handle_move() {
     local display="$1"
     shift
-    [ "$#" -eq 2 ] || {
+    [ "$(__whi_argc "$@")" -eq 2 ] || {
         echo "Usage: $display FROM TO" >&2
         return 2
     }
-    __whi_apply_path move "$1" "$2"
+    __whi_apply_path move "$@"
 }
 
 __whi_handle_switch() {
     local display="$1"
     shift
-    [ "$#" -eq 2 ] || {
+    [ "$(__whi_argc "$@")" -eq 2 ] || {
         echo "Usage: $display IDX1 IDX2" >&2
         return 2
     }
-    __whi_apply_path switch "$1" "$2"
+    __whi_apply_path switch "$@"
 }
 
 __whi_handle_clean() {
-    local display="$1"
     shift
-    [ "$#" -eq 0 ] || {
-        echo "Usage: $display" >&2
-        return 2
-    }
-    __whi_apply_path clean
+    __whi_apply_path clean "$@"
 }
 
 __whi_handle_delete() {
     local display="$1"
     shift
-    [ "$#" -ge 1 ] || {
+    [ "$(__whi_argc "$@")" -ge 1 ] || {
         echo "Usage: $display TARGET [TARGET...]" >&2
         return 2
     }
@@ -64,8 +79,8 @@ __whi_handle_delete() {
 __whi_handle_add() {
     local display="$1"
     shift
-    [ "$#" -ge 1 ] || {
-        echo "Usage: $display PATH..." >&2
+    [ "$(__whi_argc "$@")" -ge 1 ] || {
+        echo "Usage: $display [--append | --at N | --before ANCHOR | --after ANCHOR] [--move-if-present] PATH..." >&2
         return 2
     }
     __whi_apply_path add "$@"
@@ -74,7 +89,7 @@ __whi_handle_add() {
 __whi_handle_prefer() {
     local display="$1"
     shift
-    [ "$#" -ge 1 ] || {
+    [ "$(__whi_argc "$@")" -ge 1 ] || {
         echo "Usage: $display [NAME] TARGET [PATTERN...]" >&2
         return 2
     }
@@ -91,41 +106,77 @@ __whi_handle_prefer() {
 __whi_handle_redo() {
     local display="$1"
     shift
-    [ "$#" -le 1 ] || {
+    [ "$(__whi_argc "$@")" -le 1 ] || {
         echo "Usage: $display [COUNT]" >&2
         return 2
     }
-    if [ "$#" -eq 0 ]; then
-        __whi_apply_path redo 1
+    if [ "$(__whi_argc "$@")" -eq 0 ]; then
+        __whi_apply_path redo 1 "$@"
     else
-        __whi_apply_path redo "$1"
+        __whi_apply_path redo "$@"
     fi
 }
 
 __whi_handle_undo() {
     local display="$1"
     shift
-    [ "$#" -le 1 ] || {
+    [ "$(__whi_argc "$@")" -le 1 ] || {
         echo "Usage: $display [COUNT]" >&2
         return 2
     }
-    if [ "$#" -eq 0 ]; then
-        __whi_apply_path undo 1
+    if [ "$(__whi_argc "$@")" -eq 0 ]; then
+        __whi_apply_path undo 1 "$@"
     else
-        __whi_apply_path undo "$1"
+        __whi_apply_path undo "$@"
     fi
 }
 
+# Succeeds after explaining where a missing command went
+__whi_not_found() {
+    __whi_exec __not_found "$1"
+}
+
 if [ -n "$BASH_VERSION" ]; then
     if [ -f ~/.whi/saved_path_bash ]; then
         NEW_PATH=$(__whi_exec __load_saved_path bash 2>/dev/null)
         [ -n "$NEW_PATH" ] && export PATH="$NEW_PATH"
     fi
+
+    # Keep any existing handler to fall back on, but not our own on re-source
+    case "$(declare -f command_not_found_handle 2>/dev/null)" in
+        '' | *__whi_not_found*) ;;
+        *) eval "__whi_prev_$(declare -f command_not_found_handle)" ;;
+    esac
+
+    command_not_found_handle() {
+        __whi_not_found "$1" && return 127
+        if declare -f __whi_prev_command_not_found_handle >/dev/null 2>&1; then
+            __whi_prev_command_not_found_handle "$@"
+            return $?
+        fi
+        printf 'bash: %s: command not found\n' "$1" >&2
+        return 127
+    }
 elif [ -n "$ZSH_VERSION" ]; then
     if [ -f ~/.whi/saved_path_zsh ]; then
         NEW_PATH=$(__whi_exec __load_saved_path zsh 2>/dev/null)
         [ -n "$NEW_PATH" ] && export PATH="$NEW_PATH"
     fi
+
+    case "$(typeset -f command_not_found_handler 2>/dev/null)" in
+        '' | *__whi_not_found*) ;;
+        *) eval "__whi_prev_$(typeset -f command_not_found_handler)" ;;
+    esac
+
+    command_not_found_handler() {
+        __whi_not_found "$1" && return 127
+        if typeset -f __whi_prev_command_not_found_handler >/dev/null 2>&1; then
+            __whi_prev_command_not_found_handler "$@"
+            return $?
+        fi
+        printf 'zsh: command not found: %s\n' "$1" >&2
+        return 127
+    }
 fi
 
 whim() { __whi_handle_move "whim" "$@"; }
@@ -139,11 +190,11 @@ whin() { __whi_exec -n "$@"; }
 whir() { __whi_handle_redo "whir" "$@"; }
 whiu() { __whi_handle_undo "whiu" "$@"; }
 whil() {
-    [ "$#" -eq 1 ] || {
+    [ "$(__whi_argc "$@")" -eq 1 ] || {
         echo "Usage: whil NAME" >&2
         return 2
     }
-    __whi_apply_path load "$1"
+    __whi_apply_path load "$@"
 }
 whish() { __whi_exec shorthands "$@"; }
 
@@ -158,11 +209,11 @@ whi() {
 
     case "$cmd" in
         reset)
-            [ "$#" -eq 0 ] || {
+            [ "$(__whi_argc "$@")" -eq 0 ] || {
                 echo "Usage: whi reset" >&2
                 return 2
             }
-            __whi_apply_path reset
+            __whi_apply_path reset "$@"
             ;;
         undo)
             __whi_handle_undo "whi undo" "$@"
@@ -171,15 +222,32 @@ whi() {
             __whi_handle_redo "whi redo" "$@"
             ;;
         load)
-            [ "$#" -eq 1 ] || {
+            [ "$(__whi_argc "$@")" -eq 1 ] || {
                 echo "Usage: whi load NAME" >&2
                 return 2
             }
-            __whi_apply_path load "$1"
+            __whi_apply_path load "$@"
             ;;
         add)
             __whi_handle_add "whi add" "$@"
             ;;
+        batch)
+            __whi_apply_path batch "$@"
+            ;;
+        pin)
+            [ "$(__whi_argc "$@")" -ge 1 ] || {
+                echo "Usage: whi pin INDEX|PATH [--position N] [--save]" >&2
+                return 2
+            }
+            __whi_apply_path pin "$@"
+            ;;
+        enforce)
+            # Checking only reports, so there is no PATH to export
+            case " $* " in
+                *" --check "*) __whi_exec enforce "$@" ;;
+                *) __whi_apply_path enforce "$@" ;;
+            esac
+            ;;
         prefer)
             __whi_handle_prefer "whi prefer" "$@"
             ;;
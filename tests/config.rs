@@ -1,10 +1,11 @@
 mod support;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use support::{env_lock, set_env_var};
 use tempfile::TempDir;
-use whi::config::{protected_paths, runtime, shell_paths};
+use whi::config::{order_rules, protected_paths, runtime, shell_paths};
+use whi::io::time::TimeFormat;
 use whi::path::file::parse_path_file;
 use whi::shell::detect::{Shell, get_config_file_path, get_saved_path_file, get_sourcing_line};
 
@@ -18,7 +19,101 @@ fn runtime_default_config_and_load() {
     let config = runtime::load_config().unwrap();
 
     assert!(!config.search.executable_search_fuzzy);
+    assert_eq!(config.versions.timeout_ms, 1000);
+    assert_eq!(config.versions.arg_for("python3"), "--version");
+    assert_eq!(config.display.time_format, TimeFormat::Local);
+    assert_eq!(config.environments.cron_path, "/usr/bin:/bin");
     assert!(home.path().join(".whi/config.toml").exists());
+
+    std::fs::write(
+        home.path().join(".whi/config.toml"),
+        "[versions]\ntimeout_ms = 250\n\n[versions.args]\njava = \"-version\"\n\n[display]\ntime_format = \"relative\"\n\n[environments]\ncron_path = \"/usr/local/bin:/usr/bin\"\n",
+    )
+    .unwrap();
+    let config = runtime::load_config().unwrap();
+    assert_eq!(config.versions.timeout_ms, 250);
+    assert_eq!(config.versions.arg_for("java"), "-version");
+    assert_eq!(config.versions.arg_for("python3"), "--version");
+    assert_eq!(config.display.time_format, TimeFormat::Relative);
+    assert_eq!(config.environments.cron_path, "/usr/local/bin:/usr/bin");
+
+    std::fs::write(
+        home.path().join(".whi/config.toml"),
+        "[display]\ntime_format = \"sometimes\"\n",
+    )
+    .unwrap();
+    assert!(runtime::load_config().is_err());
+}
+
+#[test]
+fn runtime_config_pins_parse_and_update() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+
+    runtime::ensure_config_exists().unwrap();
+    assert!(runtime::load_config().unwrap().pins.is_empty());
+
+    runtime::set_config_pin(Path::new("/opt/tools/bin"), Some(2)).unwrap();
+    runtime::set_config_pin(&home.path().join("bin"), Some(1)).unwrap();
+    runtime::set_config_pin(Path::new("/opt/tools/bin"), Some(3)).unwrap();
+    let pins = runtime::load_config().unwrap().pins;
+    assert_eq!(pins.len(), 2);
+    assert_eq!(pins[0].path, home.path().join("bin"));
+    assert_eq!(pins[0].position, 1);
+    assert_eq!(pins[1].path, PathBuf::from("/opt/tools/bin"));
+    assert_eq!(pins[1].position, 3);
+
+    runtime::set_config_pin(Path::new("/opt/tools/bin"), None).unwrap();
+    assert_eq!(runtime::load_config().unwrap().pins.len(), 1);
+    let content = std::fs::read_to_string(home.path().join(".whi/config.toml")).unwrap();
+    assert!(content.contains("[search]"));
+
+    std::fs::write(
+        home.path().join(".whi/config.toml"),
+        "[pins]\n\"~/bin\" = 1\n",
+    )
+    .unwrap();
+    assert_eq!(
+        runtime::load_config().unwrap().pins[0].path,
+        home.path().join("bin")
+    );
+
+    std::fs::write(home.path().join(".whi/config.toml"), "[pins]\n\"/x\" = 0\n").unwrap();
+    assert!(runtime::load_config().is_err());
+}
+
+#[test]
+fn order_rules_load_and_auto_enforce_setting() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+
+    runtime::ensure_config_exists().unwrap();
+    assert!(!runtime::load_config().unwrap().order.auto_enforce);
+    assert!(order_rules::load_order_rules().unwrap().is_empty());
+
+    std::fs::write(
+        home.path().join(".whi/order_rules"),
+        "!order.first\n~/bin\n",
+    )
+    .unwrap();
+    let rules = order_rules::load_order_rules().unwrap();
+    assert!(rules[0].subject.matches(&home.path().join("bin")));
+
+    std::fs::write(home.path().join(".whi/order_rules"), "~/bin\n").unwrap();
+    let err = order_rules::load_order_rules().unwrap_err();
+    assert!(
+        err.contains("order_rules") && err.contains("line 1"),
+        "{err}"
+    );
+
+    std::fs::write(
+        home.path().join(".whi/config.toml"),
+        "[order]\nauto_enforce = true\n",
+    )
+    .unwrap();
+    assert!(runtime::load_config().unwrap().order.auto_enforce);
 }
 
 #[test]
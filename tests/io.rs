@@ -1,10 +1,16 @@
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 
 use tempfile::TempDir;
 use whi::io::atomic_file::AtomicFile;
+use whi::io::json::JsonValue;
 use whi::io::line_utils::{ContentLines, strip_inline_comment};
+use whi::io::output::{OutputTemplate, TemplateField};
+use whi::io::time::{DateTime, TimeFormat};
+use whi::io::tz::TimeZone;
+use whi::search::result::{FileMetadata, SearchResult};
 
 #[test]
 fn line_utils_filters_and_strips_comments() {
@@ -62,3 +68,181 @@ fn atomic_file_overwrite_existing() {
 
     assert_eq!(fs::read_to_string(&path).unwrap(), "new content");
 }
+
+#[test]
+fn json_value_escapes_and_orders_fields() {
+    let record = JsonValue::object([
+        ("name", JsonValue::from("tab\there \"quoted\"")),
+        ("index", JsonValue::from(3_usize)),
+        ("missing", JsonValue::from(None::<u64>)),
+        ("flags", JsonValue::from(vec![true, false])),
+        ("ctl", JsonValue::from("\u{1}")),
+    ]);
+
+    assert_eq!(
+        record.to_string(),
+        r#"{"name":"tab\there \"quoted\"","index":3,"missing":null,"flags":[true,false],"ctl":"\u0001"}"#
+    );
+}
+
+#[test]
+fn date_time_formats_with_offsets() {
+    // 2024-02-29 23:30:05 UTC, a Thursday
+    let utc = DateTime::utc(1_709_249_405);
+    assert_eq!(
+        utc.format("%F %T %a %j %z %Z"),
+        "2024-02-29 23:30:05 Thu 060 +0000 UTC"
+    );
+    assert_eq!(utc.format("%I:%M %p %%"), "11:30 PM %");
+
+    let ahead = DateTime::with_offset(1_709_249_405, 5400, "X");
+    assert_eq!(
+        ahead.format("%Y-%m-%d %H:%M %z %B"),
+        "2024-03-01 01:00 +0130 March"
+    );
+
+    let before_epoch = DateTime::utc(-86_400);
+    assert_eq!(before_epoch.format("%F %A"), "1969-12-31 Wednesday");
+}
+
+#[test]
+fn output_template_renders_fields_and_markers() {
+    let result = SearchResult {
+        path: PathBuf::from("/usr/local/bin/cargo"),
+        canonical_path: None,
+        metadata: Some(FileMetadata {
+            dev: 1,
+            ino: 42,
+            size: 1024,
+            mode: 0o100_755,
+            uid: 0,
+            gid: 20,
+            mtime: Some(UNIX_EPOCH + Duration::from_secs(1_000_000)),
+            ctime: None,
+        }),
+        file_kind: None,
+        path_index: 3,
+        version: Some("cargo 1.80.0".to_string()),
+        shim: None,
+        symlink_chain: None,
+        score: None,
+    };
+
+    let template =
+        OutputTemplate::parse(r"{winner:*| }{index:>3}\t{executable}\t{size}\t{perms} {mode}")
+            .unwrap();
+    assert_eq!(
+        template.render("cargo", &result, true),
+        "*  3\tcargo\t1024\t-rwxr-xr-x 0755"
+    );
+    assert!(template.render("cargo", &result, false).starts_with("   3"));
+    assert!(template.needs_stat());
+    assert!(!template.uses(&[TemplateField::Version]));
+
+    let template =
+        OutputTemplate::parse("{{{name}}} {version} {dir} [{canonical}] {ctime}").unwrap();
+    assert_eq!(
+        template.render("cargo", &result, false),
+        "{cargo} cargo 1.80.0 /usr/local/bin [] "
+    );
+    assert!(template.uses(&[TemplateField::Version]));
+
+    assert!(OutputTemplate::parse("{nope}").is_err());
+    assert!(OutputTemplate::parse("{path").is_err());
+    assert!(OutputTemplate::parse("stray }").is_err());
+    assert!(OutputTemplate::parse("{size:wide}").is_err());
+}
+
+/// `TZif` v2 data with one transition and a POSIX footer for later instants
+fn tzif_v2(transition: i64, footer: &str) -> Vec<u8> {
+    let header = |time_size: usize| {
+        let mut bytes = b"TZif2".to_vec();
+        bytes.extend([0u8; 15]);
+        // isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+        for count in [0u32, 0, 0, 1, 2, 8] {
+            bytes.extend(count.to_be_bytes());
+        }
+        if time_size == 8 {
+            bytes.extend(transition.to_be_bytes());
+        } else {
+            bytes.extend(i32::try_from(transition).unwrap().to_be_bytes());
+        }
+        bytes.push(1);
+        // LMT +0:53:28, then CET +1:00
+        bytes.extend(3208i32.to_be_bytes());
+        bytes.extend([0, 0]);
+        bytes.extend(3600i32.to_be_bytes());
+        bytes.extend([0, 4]);
+        bytes.extend(b"LMT\0CET\0");
+        bytes
+    };
+
+    let mut data = header(4);
+    data.extend(header(8));
+    data.extend(format!("\n{footer}\n").as_bytes());
+    data
+}
+
+#[test]
+fn time_zones_follow_tzif_transitions_and_posix_rules() {
+    let zone = TimeZone::from_tzif(&tzif_v2(-2_000_000_000, "CET-1CEST,M3.5.0,M10.5.0/3")).unwrap();
+    let at = |ts| DateTime::in_zone(ts, &zone).format("%F %T %Z");
+
+    assert_eq!(at(-2_100_000_000), "1903-06-16 11:33:28 LMT");
+    assert_eq!(at(0), "1970-01-01 01:00:00 CET");
+    // Past the last transition the footer rule applies, including DST switches
+    assert_eq!(at(1_711_846_799), "2024-03-31 01:59:59 CET");
+    assert_eq!(at(1_711_846_800), "2024-03-31 03:00:00 CEST");
+    assert_eq!(at(1_729_990_800), "2024-10-27 02:00:00 CET");
+
+    let new_york = TimeZone::from_posix("EST5EDT,M3.2.0,M11.1.0").unwrap();
+    let summer = DateTime::in_zone(1_720_000_000, &new_york);
+    assert_eq!(
+        summer.format("%F %T %Z %z"),
+        "2024-07-03 05:46:40 EDT -0400"
+    );
+
+    // Southern hemisphere: daylight time spans the new year
+    let auckland = TimeZone::from_posix("NZST-12NZDT,M9.5.0,M4.1.0/3").unwrap();
+    assert_eq!(DateTime::in_zone(1_704_067_200, &auckland).zone, "NZDT");
+    assert_eq!(DateTime::in_zone(1_720_000_000, &auckland).zone, "NZST");
+
+    let quoted = TimeZone::from_tz_var("<+0530>-5:30");
+    assert_eq!(
+        DateTime::in_zone(0, &quoted).format("%T %Z %:z"),
+        "05:30:00 +0530 +05:30"
+    );
+
+    assert_eq!(DateTime::in_zone(0, &TimeZone::from_tz_var("")).zone, "UTC");
+    assert!(TimeZone::from_posix("X5").is_err());
+    assert!(TimeZone::from_tzif(b"not a zone").is_err());
+}
+
+#[test]
+fn time_formats_render_named_and_relative_styles() {
+    let zone = TimeZone::from_posix("CET-1").unwrap();
+    let time = DateTime::in_zone(1_709_249_405, &zone);
+    let now = 1_709_249_405;
+
+    assert_eq!(
+        TimeFormat::parse("ISO8601").unwrap().render_at(&time, now),
+        "2024-03-01T00:30:05+01:00"
+    );
+    assert_eq!(
+        TimeFormat::parse("rfc3339").unwrap().render_at(&time, now),
+        "2024-03-01 00:30:05+01:00"
+    );
+    assert_eq!(TimeFormat::Epoch.render_at(&time, now), "1709249405");
+    assert_eq!(
+        TimeFormat::parse("%d.%m.%Y").unwrap().render_at(&time, now),
+        "01.03.2024"
+    );
+
+    let relative = TimeFormat::parse("relative").unwrap();
+    assert_eq!(relative.render_at(&time, now + 3), "just now");
+    assert_eq!(relative.render_at(&time, now + 90), "1 minute ago");
+    assert_eq!(relative.render_at(&time, now + 3 * 86_400), "3 days ago");
+    assert_eq!(relative.render_at(&time, now - 7200), "in 2 hours");
+
+    assert!(TimeFormat::parse("yesterday").is_err());
+}
@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
 
+use whi::cli::{
+    AddPosition, PathAnchor, parse_add_position, split_batch_operations, split_operation_words,
+};
 use whi::platform::{get_parent_pid, get_user_id, is_tty};
 
 #[test]
@@ -42,3 +45,59 @@ fn commands_runner_normalization() {
 
     assert!(missing.is_empty());
 }
+
+#[test]
+fn add_positions_parse_anchors() {
+    assert_eq!(
+        parse_add_position(false, None, None, None).unwrap(),
+        AddPosition::Prepend
+    );
+    assert_eq!(
+        parse_add_position(true, None, None, None).unwrap(),
+        AddPosition::Append
+    );
+    assert_eq!(
+        parse_add_position(false, Some(4), None, None).unwrap(),
+        AddPosition::At(4)
+    );
+    assert_eq!(
+        parse_add_position(false, None, Some("3"), None).unwrap(),
+        AddPosition::Before(PathAnchor::Index(3))
+    );
+    assert_eq!(
+        parse_add_position(false, None, Some("/usr/bin"), None).unwrap(),
+        AddPosition::Before(PathAnchor::Path("/usr/bin".to_string()))
+    );
+    assert_eq!(
+        parse_add_position(false, None, None, Some("cargo")).unwrap(),
+        AddPosition::After(PathAnchor::Pattern("cargo".to_string()))
+    );
+
+    assert!(parse_add_position(false, Some(0), None, None).is_err());
+    assert!(parse_add_position(true, Some(2), None, None).is_err());
+    assert!(parse_add_position(false, None, Some("a"), Some("b")).is_err());
+}
+
+#[test]
+fn batch_operations_split_with_shell_quoting() {
+    let words = |operation: &str| split_operation_words(operation).unwrap();
+    assert_eq!(
+        words("delete --glob '/opt/*'"),
+        vec!["delete", "--glob", "/opt/*"]
+    );
+    assert_eq!(
+        words(r#"add "/opt/My Tools/bin" /a\ b ''"#),
+        vec!["add", "/opt/My Tools/bin", "/a b", ""]
+    );
+    assert_eq!(
+        words(r#"add "say \"hi\" \n""#),
+        vec!["add", r#"say "hi" \n"#]
+    );
+    assert!(split_operation_words("add '/opt/bin").is_err());
+    assert!(split_operation_words("add \"/opt/bin").is_err());
+
+    assert_eq!(
+        split_batch_operations("move 5 2; add '/a;b'\ndelete \"c;d\""),
+        vec!["move 5 2", " add '/a;b'", "delete \"c;d\""]
+    );
+}
@@ -1,14 +1,24 @@
 mod support;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use support::{env_lock, set_env_var};
 use whi::path::PathSearcher;
 use whi::path::diff::{DiffEntry, compute_diff};
+use whi::path::environment::{compare_resolution, sudo_secure_path};
 use whi::path::file::{apply_path_sections, expand_shell_vars, format_path_file, parse_path_file};
-use whi::path::fuzzy::FuzzyMatcher;
+use whi::path::fuzzy::{FuzzyMatcher, score_subsequence};
 use whi::path::guard::PathGuard;
+use whi::path::health::{Severity, check_path_entries};
+use whi::path::order::{enforce_order, find_violations, parse_order_rules};
+use whi::path::pattern::{CaseMode, Pattern, PatternKind, PatternOptions};
+use whi::path::pins::{Pin, format_pins, keep_pinned, merge_pins, parse_pins};
 use whi::path::resolve::expand_tilde;
+use whi::path::searcher::{CleanOptions, CleanReason};
+use whi::path::source::PathSource;
+use whi::search::index::ExecutableIndex;
+use whi::session::history::HistoryContext;
+use whi::shell::detect::Shell;
 
 #[test]
 fn expand_tilde_and_fuzzy_matcher() {
@@ -56,6 +66,12 @@ fn path_searcher_mutations() {
     let (cleaned, removed) = searcher.clean_duplicates();
     assert_eq!(cleaned, "/a:/b:/c:/d:/e");
     assert!(removed.is_empty());
+
+    let mut searcher = PathSearcher::new("/a:/b:/c");
+    assert_eq!(searcher.remove_at(2).unwrap(), Path::new("/b"));
+    assert_eq!(searcher.to_path_string(), "/a:/c");
+    assert_eq!(searcher.find_path_index(Path::new("/c")), Some(2));
+    assert!(searcher.remove_at(3).is_err());
 }
 
 #[test]
@@ -96,3 +112,427 @@ fn path_guard_preserves_entries() {
 
     assert_eq!(result, "/usr/local/bin");
 }
+
+#[test]
+fn health_check_reports_entries_with_fixes() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let temp = dir.path();
+    let empty = temp.join("empty");
+    let file = temp.join("file");
+    std::fs::create_dir_all(&empty).unwrap();
+    std::fs::write(&file, "").unwrap();
+
+    let path_var = format!(
+        "{}::{}:{}/missing:relative:{}",
+        empty.display(),
+        file.display(),
+        temp.display(),
+        empty.display()
+    );
+    let mut index = ExecutableIndex::disabled();
+    let findings = check_path_entries(&path_var, &mut index);
+
+    let codes: Vec<(&str, Option<usize>)> = findings.iter().map(|f| (f.code, f.index)).collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("empty-component", None),
+            ("no-executables", Some(1)),
+            ("not-a-directory", Some(2)),
+            ("missing-dir", Some(3)),
+            ("relative-entry", Some(4)),
+            ("missing-dir", Some(4)),
+            ("duplicate-entry", Some(5)),
+        ]
+    );
+    assert_eq!(findings[2].severity, Severity::Error);
+    assert_eq!(findings[3].fix.as_deref(), Some("whi delete 3"));
+    assert_eq!(findings[6].fix.as_deref(), Some("whi clean"));
+}
+
+#[test]
+fn clean_modes_report_kept_index() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let temp = dir.path();
+    let real = temp.join("real");
+    let link = temp.join("link");
+    std::fs::create_dir_all(&real).unwrap();
+    std::os::unix::fs::symlink(&real, &link).unwrap();
+
+    let path_var = format!(
+        "{r}:{r}/:{l}:{t}/missing:{r}",
+        r = real.display(),
+        l = link.display(),
+        t = temp.display()
+    );
+    let searcher = PathSearcher::new(&path_var);
+
+    let report = searcher.clean(CleanOptions::default());
+    let removed: Vec<usize> = report.removed.iter().map(|r| r.index).collect();
+    assert_eq!(removed, vec![5]);
+
+    let report = searcher.clean(CleanOptions {
+        normalize: true,
+        ..CleanOptions::default()
+    });
+    assert_eq!(
+        report.removed[0].reason,
+        CleanReason::Normalized { kept: 1 }
+    );
+    assert_eq!(report.removed.len(), 2);
+
+    let report = searcher.clean(CleanOptions {
+        canonical: true,
+        normalize: true,
+        missing: true,
+    });
+    let reasons: Vec<(usize, &CleanReason)> = report
+        .removed
+        .iter()
+        .map(|r| (r.index, &r.reason))
+        .collect();
+    assert_eq!(reasons[0], (2, &CleanReason::Normalized { kept: 1 }));
+    assert!(matches!(
+        reasons[1],
+        (3, CleanReason::Canonical { kept: 1, .. })
+    ));
+    assert_eq!(reasons[2], (4, &CleanReason::Missing));
+    assert_eq!(reasons[3], (5, &CleanReason::Duplicate { kept: 1 }));
+    assert_eq!(report.path, real.display().to_string());
+}
+
+#[test]
+fn regex_and_glob_patterns_match_names_and_entries() {
+    let regex = |pattern: &str, case| {
+        let options = PatternOptions {
+            kind: PatternKind::Regex,
+            case,
+        };
+        Pattern::new(pattern, options).unwrap()
+    };
+    let glob = |pattern: &str, case| {
+        let options = PatternOptions {
+            kind: PatternKind::Glob,
+            case,
+        };
+        Pattern::new(pattern, options).unwrap()
+    };
+
+    let clang = regex(r"^clang(-\d+)?$", CaseMode::Smart);
+    assert!(clang.matches("clang"));
+    assert!(clang.matches("clang-17"));
+    assert!(!clang.matches("clang-format"));
+    assert!(!clang.matches("clang-"));
+
+    let lazy = regex(r"^a.*?b{2,3}(?:x|yz)+$", CaseMode::Smart);
+    assert!(lazy.matches("a--bbbyzx"));
+    assert!(!lazy.matches("a--b-x"));
+    assert!(regex(r"\bpip[^a-z\s]", CaseMode::Smart).matches("x pip3"));
+    assert!(regex("python", CaseMode::Smart).matches("/usr/bin/Python3"));
+    assert!(!regex("Python", CaseMode::Smart).matches("python3"));
+    assert!(!regex("python", CaseMode::Sensitive).matches("Python3"));
+    assert!(regex("PYTHON", CaseMode::Insensitive).matches("python3"));
+    // Catastrophic patterns give up instead of hanging
+    assert!(!regex("^(a*)*b$", CaseMode::Smart).matches(&"a".repeat(64)));
+    // Empty iterations fill a large minimum without recursing for each one
+    assert!(regex("^(?:(?:a?){1000}){1000}$", CaseMode::Smart).matches("aa"));
+
+    for invalid in [
+        "(abc",
+        "abc)",
+        "*a",
+        "[a-",
+        "x{3,1}",
+        r"\q",
+        "(?=a)",
+        "(?:a?){100000}",
+    ] {
+        let options = PatternOptions {
+            kind: PatternKind::Regex,
+            case: CaseMode::Smart,
+        };
+        assert!(Pattern::new(invalid, options).is_err(), "{invalid}");
+    }
+
+    let python = glob("python3.*", CaseMode::Smart);
+    assert!(python.matches("python3.12"));
+    assert!(!python.matches("python3"));
+    assert!(!python.matches("ipython3.12"));
+    assert!(glob("{pip,python}3.1[!0-1]", CaseMode::Smart).matches("pip3.12"));
+    assert!(!glob("{pip,python}3.1[!0-1]", CaseMode::Smart).matches("pip3.11"));
+    assert!(glob("*/.cargo/*", CaseMode::Smart).matches_path(Path::new("/home/u/.cargo/bin")));
+    assert!(glob("file\\*[", CaseMode::Smart).matches("file*["));
+    assert!(!glob("Cargo", CaseMode::Smart).matches("cargo"));
+
+    let fuzzy = PatternOptions {
+        kind: PatternKind::Fuzzy,
+        case: CaseMode::Smart,
+    };
+    assert!(
+        Pattern::new("Cargo Bin", fuzzy)
+            .unwrap()
+            .matches("/x/.cargo/bin")
+    );
+
+    let searcher = PathSearcher::new("/opt/clang-17/bin:/usr/local/bin:/usr/bin");
+    let found =
+        searcher.find_pattern_indices(&regex(r"^/usr/(local/)?bin$", CaseMode::Smart), None);
+    let indices: Vec<usize> = found.iter().map(|entry| entry.index).collect();
+    assert_eq!(indices, vec![3, 2]);
+    let found = searcher.find_pattern_indices(&glob("/opt/*", CaseMode::Smart), None);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].index, 1);
+}
+
+#[test]
+fn fuzzy_scores_rank_contiguous_boundary_and_exact_matches() {
+    let exact = score_subsequence("cargo", "cargo", false).unwrap();
+    let prefix = score_subsequence("cargo", "cargo-clippy", false).unwrap();
+    let scattered = score_subsequence("cargo", "crate-argo", false).unwrap();
+    assert!(exact.total > prefix.total);
+    assert!(prefix.total > scattered.total);
+    assert_eq!(exact.exact, 100);
+    assert_eq!(prefix.positions, vec![0, 1, 2, 3, 4]);
+    assert!(scattered.gaps > 0);
+    assert!(score_subsequence("cargo", "cgo", false).is_none());
+
+    // The best alignment prefers word starts over the first occurrence
+    let boundary = score_subsequence("fb", "foo-bar-fb", false).unwrap();
+    let camel = score_subsequence("gc", "goCompile", false).unwrap();
+    assert_eq!(camel.positions, vec![0, 2]);
+    assert!(boundary.total > 0);
+    assert!(score_subsequence("Go", "go", true).is_none());
+
+    for score in [&exact, &prefix, &scattered, &boundary, &camel] {
+        let parts =
+            score.matched + score.contiguous + score.boundary + score.basename + score.exact;
+        assert_eq!(score.total, parts - score.gaps);
+        assert!(score.describe().starts_with(&format!("{} = ", score.total)));
+    }
+
+    let matcher = FuzzyMatcher::new("cargo bin");
+    assert!(matcher.score(Path::new("/usr/local/bin")).is_none());
+    let home = matcher.score(Path::new("/home/u/.cargo/bin")).unwrap();
+    let nested = matcher.score(Path::new("/opt/cargo/bin/extra")).unwrap();
+    assert!(home.total > nested.total);
+
+    // Ties on score fall back to shorter paths, then PATH order
+    let searcher = PathSearcher::new("/home/u/.cargo/bin:/x/.cargo/bin:/y/.cargo/bin");
+    let ranked: Vec<usize> = searcher
+        .find_fuzzy_indices("cargo bin", None)
+        .iter()
+        .map(|(idx, _)| *idx)
+        .collect();
+    assert_eq!(ranked, vec![2, 3, 1]);
+}
+
+#[test]
+fn path_sources_parse_and_read_files_and_snapshots() {
+    let _lock = env_lock();
+    let temp = tempfile::TempDir::new().unwrap();
+    let _runtime = set_env_var("XDG_RUNTIME_DIR", temp.path());
+    let _home = set_env_var("HOME", "/home/testuser");
+
+    assert_eq!(
+        "profile:work".parse::<PathSource>(),
+        Ok(PathSource::Profile("work".to_string()))
+    );
+    assert_eq!(
+        "saved:fish".parse::<PathSource>(),
+        Ok(PathSource::Saved(Shell::Fish))
+    );
+    assert_eq!(
+        "snapshot:-3".parse::<PathSource>(),
+        Ok(PathSource::Snapshot(-3))
+    );
+    assert_eq!("pid:1234".parse::<PathSource>(), Ok(PathSource::Pid(1234)));
+    assert!("pid:abc".parse::<PathSource>().is_err());
+    assert!("profile:".parse::<PathSource>().is_err());
+    assert!("env:PATH".parse::<PathSource>().is_err());
+
+    let no_history = || -> Result<HistoryContext, String> { panic!("history not needed") };
+
+    // Plain PATH strings replace, profile-style files apply to the current PATH
+    let plain = temp.path().join("plain");
+    std::fs::write(&plain, "/opt/a/bin:/usr/bin\n").unwrap();
+    let source = PathSource::File(plain);
+    assert_eq!(
+        source.read("/bin", no_history).unwrap(),
+        "/opt/a/bin:/usr/bin"
+    );
+    let sections = temp.path().join("sections");
+    std::fs::write(&sections, "!path.prepend\n~/bin\n").unwrap();
+    let source = PathSource::File(sections);
+    assert_eq!(
+        source.read("/bin", no_history).unwrap(),
+        "/home/testuser/bin:/bin"
+    );
+
+    let history = HistoryContext::global(4321).unwrap();
+    for path in ["/a", "/a:/b", "/a:/b:/c", "/b:/c"] {
+        history.write_snapshot(path).unwrap();
+    }
+    let read = |index| PathSource::Snapshot(index).read("", || HistoryContext::global(4321));
+    assert_eq!(read(1).unwrap(), "/a:/b");
+    assert_eq!(read(-3).unwrap(), "/a");
+    assert!(read(4).is_err());
+    assert!(read(-4).is_err());
+
+    // Counting back starts from the cursor after an undo
+    history.set_cursor(2).unwrap();
+    assert_eq!(read(-1).unwrap(), "/a:/b");
+}
+
+#[test]
+fn sudo_secure_path_follows_includes_and_env_resolution_compares() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let sudoers = temp.path().join("sudoers");
+    let sudoers_d = temp.path().join("sudoers.d");
+    std::fs::create_dir(&sudoers_d).unwrap();
+
+    std::fs::write(
+        &sudoers,
+        "Defaults\tenv_reset, secure_path=\"/usr/sbin:/usr/bin\" # comment\n\
+         Defaults:alice secure_path=/home/alice/bin\n\
+         @include missing.conf\n\
+         @includedir sudoers.d\n",
+    )
+    .unwrap();
+    // Includes that cannot be read are skipped, as sudo does
+    std::fs::write(sudoers_d.join("05-binary"), [0xff, 0xfe]).unwrap();
+    assert_eq!(
+        sudo_secure_path(&sudoers).unwrap().as_deref(),
+        Some("/usr/sbin:/usr/bin")
+    );
+
+    // Later files override earlier ones; files with a dot are skipped
+    std::fs::write(
+        sudoers_d.join("10-path"),
+        "Defaults secure_path = /opt/bin:\\\n/bin\n",
+    )
+    .unwrap();
+    std::fs::write(sudoers_d.join("20.bak"), "Defaults !secure_path\n").unwrap();
+    assert_eq!(
+        sudo_secure_path(&sudoers).unwrap().as_deref(),
+        Some("/opt/bin:/bin")
+    );
+    std::fs::write(sudoers_d.join("30-off"), "Defaults !secure_path\n").unwrap();
+    assert_eq!(sudo_secure_path(&sudoers).unwrap(), None);
+    assert!(sudo_secure_path(&temp.path().join("missing")).is_err());
+
+    let system = temp.path().join("system");
+    let user = temp.path().join("user");
+    for dir in [&system, &user] {
+        std::fs::create_dir(dir).unwrap();
+        let tool = dir.join("tool");
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&tool, std::os::unix::fs::PermissionsExt::from_mode(0o755))
+            .unwrap();
+    }
+    let env_path = system.display().to_string();
+    let current_path = format!("{}:{}", user.display(), system.display());
+
+    let resolution = compare_resolution("tool", &env_path, &current_path);
+    assert_eq!(resolution.path, Some(system.join("tool")));
+    assert_eq!(resolution.current, Some(user.join("tool")));
+    assert!(resolution.differs());
+    let resolution = compare_resolution("tool", &env_path, &env_path);
+    assert!(!resolution.differs());
+    assert_eq!(
+        compare_resolution("missing", &env_path, &current_path).path,
+        None
+    );
+}
+
+#[test]
+fn pinned_entries_survive_shifts_and_refuse_moves() {
+    let entries = |list: &[&str]| list.iter().map(PathBuf::from).collect::<Vec<_>>();
+    let pin = |path: &str, position| Pin {
+        path: PathBuf::from(path),
+        position,
+    };
+    let pins = vec![pin("/b", 2)];
+    let before = entries(&["/a", "/b", "/c"]);
+
+    // Inserting in front only shifts the pin, so it is moved back
+    assert_eq!(
+        keep_pinned(&before, &entries(&["/new", "/a", "/b", "/c"]), &pins).unwrap(),
+        entries(&["/new", "/b", "/a", "/c"])
+    );
+    assert_eq!(
+        keep_pinned(&before, &entries(&["/c", "/a", "/b"]), &pins).unwrap(),
+        entries(&["/c", "/b", "/a"])
+    );
+    assert_eq!(
+        keep_pinned(&before, &entries(&["/a", "/b"]), &pins).unwrap(),
+        entries(&["/a", "/b"])
+    );
+
+    let removed = keep_pinned(&before, &entries(&["/a", "/c"]), &pins).unwrap_err();
+    assert!(removed.contains("would be removed"), "{removed}");
+    let moved = keep_pinned(&before, &entries(&["/b", "/a", "/c"]), &pins).unwrap_err();
+    assert!(moved.contains("pinned at position 2"), "{moved}");
+    assert!(keep_pinned(&before, &entries(&["/a", "/c", "/b"]), &pins).is_err());
+
+    let stored = parse_pins(&format_pins(&[pin("/b", 2), pin("/x y", 1)])).unwrap();
+    assert_eq!(stored, vec![pin("/b", 2), pin("/x y", 1)]);
+    assert!(parse_pins("0\t/b\n").is_err());
+    assert_eq!(
+        merge_pins(&[pin("/b", 5), pin("/c", 3)], &[pin("/b", 1)]),
+        vec![pin("/b", 1), pin("/c", 3)]
+    );
+}
+
+#[test]
+fn order_rules_reorder_minimally_and_report_contradictions() {
+    let entries = |list: &[&str]| list.iter().map(PathBuf::from).collect::<Vec<_>>();
+    let rules = parse_order_rules(
+        "# toolchains first\n!order.before\n/opt/cargo/bin /usr/bin\n!order.last\n/nix/store/*\n",
+    )
+    .unwrap();
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].to_string(), "/opt/cargo/bin before /usr/bin");
+    assert_eq!(rules[1].line, 5);
+
+    // The entry a rule moves up goes just before what it has to precede
+    let current = entries(&[
+        "/nix/store/a",
+        "/home/u/bin",
+        "/usr/bin",
+        "/bin",
+        "/opt/cargo/bin",
+    ]);
+    let violations = find_violations(&rules, &current);
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].entry, violations[0].other), (4, 2));
+    assert_eq!((violations[1].entry, violations[1].other), (0, 4));
+    let ordered = enforce_order(&rules, &current).unwrap();
+    assert_eq!(
+        ordered,
+        entries(&[
+            "/home/u/bin",
+            "/opt/cargo/bin",
+            "/usr/bin",
+            "/bin",
+            "/nix/store/a"
+        ])
+    );
+    assert!(find_violations(&rules, &ordered).is_empty());
+    assert_eq!(enforce_order(&rules, &ordered).unwrap(), ordered);
+
+    let contradicting = parse_order_rules(
+        "!order.before\n/opt/cargo/bin /usr/bin\n!order.after\n/opt/cargo/bin /usr/bin\n",
+    )
+    .unwrap();
+    let err = enforce_order(&contradicting, &current).unwrap_err();
+    assert!(
+        err.contains("(line 2)") && err.contains("(line 4)"),
+        "{err}"
+    );
+    // Rules about entries not on PATH cannot contradict anything
+    assert!(enforce_order(&contradicting, &entries(&["/bin"])).is_ok());
+
+    assert!(parse_order_rules("/usr/bin /bin\n").is_err());
+    assert!(parse_order_rules("!order.sideways\n").is_err());
+    assert!(parse_order_rules("!order.before\n/usr/bin\n").is_err());
+}
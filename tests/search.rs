@@ -0,0 +1,627 @@
+mod support;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use support::{env_lock, set_env_var};
+use tempfile::TempDir;
+use whi::config::runtime::VersionsConfig;
+use whi::search::explain::{CandidateStatus, explain_name};
+use whi::search::index::{EntryKind, ExecutableIndex};
+use whi::search::introspect::{FileKind, Linkage, introspect};
+use whi::search::matrix::{build_matrix, install_prefix};
+use whi::search::semver::{Version, VersionReq};
+use whi::search::shadows::find_shadows;
+use whi::search::shims::{ShimKind, ShimResolver};
+use whi::search::suggest::{edit_distance, suggest};
+use whi::search::symlink::{ChainStatus, resolve_chain};
+use whi::search::version::VersionProbe;
+use whi::search::winners::{WinnerChange, winner_changes};
+
+fn write_executable(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[test]
+fn shadows_report_winner_and_shadowed_copies() {
+    let root = TempDir::new().unwrap();
+    let first = root.path().join("first");
+    let second = root.path().join("second");
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+
+    write_executable(&first, "tool");
+    write_executable(&second, "tool");
+    write_executable(&second, "only-here");
+    fs::write(first.join("data"), "not executable").unwrap();
+    fs::write(second.join("data"), "not executable").unwrap();
+
+    let dirs = vec![first.clone(), second.clone(), first.clone()];
+    let groups = find_shadows(&dirs, &mut ExecutableIndex::disabled());
+
+    assert_eq!(groups.len(), 1);
+    let group = &groups[0];
+    assert_eq!(group.name, "tool");
+    assert_eq!(group.winner().path, first.join("tool"));
+    assert_eq!(group.shadowed().len(), 2);
+    assert_eq!(group.shadowed()[0].path_index, 2);
+    assert!(!group.shadowed()[0].same_file);
+    assert!(group.shadowed()[1].same_file);
+    assert!(group.has_distinct_copies());
+}
+
+#[test]
+fn index_persists_and_invalidates_on_directory_change() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+
+    let bin = home.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    write_executable(&bin, "alpha");
+    fs::write(bin.join("notes"), "plain").unwrap();
+
+    let mut index = ExecutableIndex::load();
+    assert_eq!(index.executables(&bin), vec!["alpha".to_string()]);
+    index.save().unwrap();
+    assert!(home.path().join(".whi/cache/exec_index").exists());
+
+    let mut reloaded = ExecutableIndex::load();
+    let listing = reloaded.listing(&bin).to_vec();
+    assert_eq!(listing.len(), 2);
+    assert!(
+        listing
+            .iter()
+            .any(|e| e.name == "notes" && e.kind == EntryKind::File)
+    );
+
+    // Sleep past coarse mtime granularity so the directory stamp changes
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    write_executable(&bin, "beta");
+    assert_eq!(
+        reloaded.executables(&bin),
+        vec!["alpha".to_string(), "beta".to_string()]
+    );
+
+    ExecutableIndex::clear().unwrap();
+    assert!(!home.path().join(".whi/cache/exec_index").exists());
+}
+
+#[test]
+fn explain_classifies_every_path_entry() {
+    let root = TempDir::new().unwrap();
+    let dirs: Vec<PathBuf> = ["broken", "dir", "plain", "empty", "exec", "later"]
+        .iter()
+        .map(|name| root.path().join(name))
+        .collect();
+    for dir in &dirs {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    std::os::unix::fs::symlink(root.path().join("nowhere"), dirs[0].join("tool")).unwrap();
+    fs::create_dir_all(dirs[1].join("tool")).unwrap();
+    fs::write(dirs[2].join("tool"), "not executable").unwrap();
+    write_executable(&dirs[4], "tool");
+    write_executable(&dirs[5], "tool");
+
+    let mut path_dirs = dirs.clone();
+    path_dirs.insert(0, root.path().join("missing"));
+
+    let steps = explain_name(&path_dirs, "tool");
+    let statuses: Vec<CandidateStatus> = steps.iter().map(|step| step.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            CandidateStatus::MissingDir,
+            CandidateStatus::BrokenSymlink,
+            CandidateStatus::Directory,
+            CandidateStatus::NotExecutable,
+            CandidateStatus::NoSuchFile,
+            CandidateStatus::Executable,
+            CandidateStatus::Executable,
+        ]
+    );
+
+    let winners: Vec<usize> = steps
+        .iter()
+        .filter(|step| step.winner)
+        .map(|step| step.path_index)
+        .collect();
+    assert_eq!(winners, vec![6]);
+}
+
+#[test]
+fn version_probe_runs_only_executables_and_caches() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+
+    let bin = home.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let counter = home.path().join("runs");
+
+    let tool = bin.join("tool");
+    fs::write(
+        &tool,
+        format!(
+            "#!/bin/sh\necho run >> {}\necho \"tool 1.2.3 $1\"\n",
+            counter.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(bin.join("plain"), "#!/bin/sh\necho should-not-run\n").unwrap();
+
+    // Too slow on the first run only, like a cold JVM
+    let slow = bin.join("slow");
+    fs::write(
+        &slow,
+        format!(
+            "#!/bin/sh\nif [ -e {m} ]; then echo 'slow 2.0'; else touch {m}; sleep 5; fi\n",
+            m = home.path().join("warm").display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&slow, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut config = VersionsConfig {
+        timeout_ms: 200,
+        ..VersionsConfig::default()
+    };
+    config.args.insert("tool".to_string(), "-V".to_string());
+
+    let mut probe = VersionProbe::new(config.clone(), true);
+    assert_eq!(probe.probe(&tool).as_deref(), Some("tool 1.2.3 -V"));
+    assert_eq!(probe.probe(&bin.join("plain")), None);
+    assert_eq!(probe.probe(&slow), None);
+    probe.save().unwrap();
+
+    let mut cached = VersionProbe::new(config, true);
+    assert_eq!(cached.probe(&tool).as_deref(), Some("tool 1.2.3 -V"));
+    assert_eq!(fs::read_to_string(&counter).unwrap().lines().count(), 1);
+    // A timeout is not remembered, so the next run probes again
+    assert_eq!(cached.probe(&slow).as_deref(), Some("slow 2.0"));
+}
+
+#[test]
+fn version_constraints_match_extracted_versions() {
+    let v = |text: &str| Version::find_in(text).unwrap();
+    assert_eq!(v("Python 3.11.7").to_string(), "3.11.7");
+    assert_eq!(v("go version go1.22.1 linux/amd64").to_string(), "1.22.1");
+    assert_eq!(v("v20.11.0").to_string(), "20.11.0");
+    assert_eq!(v("shasum 6").to_string(), "6.0.0");
+    assert!(Version::find_in("no digits here").is_none());
+
+    let req = VersionReq::parse(">=3.11, <3.13").unwrap();
+    assert!(req.matches(v("3.11.0")));
+    assert!(req.matches(v("3.12.9")));
+    assert!(!req.matches(v("3.13.0")));
+    assert!(!req.matches(v("3.10.14")));
+
+    let caret = VersionReq::parse("^20").unwrap();
+    assert!(caret.matches(v("20.11.0")));
+    assert!(!caret.matches(v("21.0.0")));
+    assert!(VersionReq::parse("^0.2").unwrap().matches(v("0.2.5")));
+    assert!(!VersionReq::parse("^0.2").unwrap().matches(v("0.3.0")));
+    assert!(VersionReq::parse("~1.2").unwrap().matches(v("1.2.9")));
+    assert!(!VersionReq::parse("~1.2").unwrap().matches(v("1.3.0")));
+    assert!(VersionReq::parse(">3.11").unwrap().matches(v("3.12.0")));
+    assert!(!VersionReq::parse(">3.11").unwrap().matches(v("3.11.5")));
+
+    assert!(VersionReq::looks_like_constraint(">=3.11"));
+    assert!(VersionReq::looks_like_constraint("~1.2"));
+    assert!(!VersionReq::looks_like_constraint("~/bin"));
+    assert!(!VersionReq::looks_like_constraint("2"));
+    assert!(VersionReq::parse(">=x").is_err());
+}
+
+#[test]
+fn symlink_chain_records_every_hop_and_loops() {
+    let root = TempDir::new().unwrap();
+    let bin = root.path().join("bin");
+    let alt = root.path().join("alt");
+    fs::create_dir_all(&bin).unwrap();
+    fs::create_dir_all(&alt).unwrap();
+
+    let real = write_executable(&alt, "real");
+    std::os::unix::fs::symlink(&real, alt.join("tool")).unwrap();
+    std::os::unix::fs::symlink("../alt/tool", bin.join("tool")).unwrap();
+
+    let chain = resolve_chain(&bin.join("tool"));
+    assert_eq!(chain.status, ChainStatus::Resolved);
+    assert_eq!(chain.hops.len(), 2);
+    assert!(chain.hops[0].relative);
+    assert_eq!(chain.hops[0].path, alt.join("tool"));
+    assert!(!chain.hops[1].relative);
+    assert_eq!(chain.hops[1].path, real);
+
+    assert!(resolve_chain(&real).hops.is_empty());
+
+    std::os::unix::fs::symlink("loop-b", bin.join("loop-a")).unwrap();
+    std::os::unix::fs::symlink("loop-a", bin.join("loop-b")).unwrap();
+    assert_eq!(resolve_chain(&bin.join("loop-a")).status, ChainStatus::Loop);
+
+    std::os::unix::fs::symlink("missing", bin.join("dangling")).unwrap();
+    let dangling = resolve_chain(&bin.join("dangling"));
+    assert_eq!(dangling.status, ChainStatus::Broken);
+    assert_eq!(dangling.hops.len(), 1);
+
+    // `..` leaves the real directory behind a symlinked one, not the link's parent
+    let nested = root.path().join("opt/pkg/bin");
+    fs::create_dir_all(&nested).unwrap();
+    std::os::unix::fs::symlink("../../../alt/real", nested.join("tool")).unwrap();
+    std::os::unix::fs::symlink(&nested, root.path().join("linked")).unwrap();
+    let chain = resolve_chain(&root.path().join("linked/tool"));
+    assert_eq!(chain.status, ChainStatus::Resolved);
+    assert_eq!(chain.hops[0].path, fs::canonicalize(&real).unwrap());
+}
+
+#[test]
+fn shims_resolve_through_version_files_and_env() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+    let root = home.path();
+    let _pyenv_root = set_env_var("PYENV_ROOT", root.join("pyenv"));
+    let _pyenv_version = set_env_var("PYENV_VERSION", "");
+    let _asdf_dir = set_env_var("ASDF_DATA_DIR", root.join("asdf"));
+    let _asdf_node = set_env_var("ASDF_NODEJS_VERSION", "");
+
+    let pyenv_shims = root.join("pyenv/shims");
+    let asdf_shims = root.join("asdf/shims");
+    let system = root.join("system");
+    for dir in [&pyenv_shims, &asdf_shims, &system] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    let python_shim = write_executable(&pyenv_shims, "python");
+    let system_python = write_executable(&system, "python");
+
+    let node_shim = asdf_shims.join("node");
+    fs::write(
+        &node_shim,
+        "#!/usr/bin/env bash\n# asdf-plugin: nodejs 18.19.0\n# asdf-plugin: nodejs 20.11.0\nexec asdf exec \"node\" \"$@\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&node_shim, fs::Permissions::from_mode(0o755)).unwrap();
+    let mut node_installs = Vec::new();
+    for version in ["18.19.0", "20.9.0", "20.11.0"] {
+        let bin = root.join("asdf/installs/nodejs").join(version).join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        node_installs.push(write_executable(&bin, "node"));
+    }
+    let py_bin = root.join("pyenv/versions/3.12.1/bin");
+    fs::create_dir_all(&py_bin).unwrap();
+    let pyenv_python = write_executable(&py_bin, "python");
+
+    let project = root.join("project");
+    let nested = project.join("src/deep");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(project.join(".python-version"), "3.12.1\n").unwrap();
+    fs::write(project.join(".tool-versions"), "# pinned\nnodejs 20\n").unwrap();
+
+    let dirs = vec![pyenv_shims, asdf_shims, system];
+    let resolver = ShimResolver::new(&dirs, nested.clone());
+
+    let python = resolver.resolve(&python_shim).unwrap();
+    assert_eq!(python.kind, ShimKind::Pyenv);
+    assert_eq!(python.selector.as_deref(), Some("3.12.1"));
+    let version_file = project.join(".python-version");
+    assert_eq!(python.source, Some(version_file.display().to_string()));
+    assert_eq!(python.target, Some(pyenv_python));
+
+    // A version prefix picks the highest matching install
+    let node = resolver.resolve(&node_shim).unwrap();
+    assert_eq!(node.kind, ShimKind::Asdf);
+    assert_eq!(node.selector.as_deref(), Some("nodejs 20"));
+    assert_eq!(node.target.as_ref(), Some(&node_installs[2]));
+
+    // Environment variables take precedence over version files
+    let _system = set_env_var("PYENV_VERSION", "system");
+    let _node = set_env_var("ASDF_NODEJS_VERSION", "18.19.0");
+    let python = resolver.resolve(&python_shim).unwrap();
+    assert_eq!(python.source.as_deref(), Some("$PYENV_VERSION"));
+    assert_eq!(python.target, Some(system_python));
+    let node = resolver.resolve(&node_shim).unwrap();
+    assert_eq!(node.target.as_ref(), Some(&node_installs[0]));
+
+    // Ordinary executables are not shims
+    assert!(resolver.resolve(&dirs[2].join("python")).is_none());
+}
+
+#[test]
+fn rustup_directory_override_wins_over_toolchain_file() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+    let root = home.path();
+    let rustup_home = root.join("rustup");
+    let _rustup_home = set_env_var("RUSTUP_HOME", &rustup_home);
+    let _toolchain = set_env_var("RUSTUP_TOOLCHAIN", "");
+
+    let cargo_bin = root.join("cargo/bin");
+    fs::create_dir_all(&cargo_bin).unwrap();
+    let rustup = write_executable(&cargo_bin, "rustup");
+    std::os::unix::fs::symlink(&rustup, cargo_bin.join("cargo")).unwrap();
+
+    let mut installed = Vec::new();
+    for toolchain in [
+        "stable-x86_64-unknown-linux-gnu",
+        "nightly-x86_64-unknown-linux-gnu",
+    ] {
+        let bin = rustup_home.join("toolchains").join(toolchain).join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        installed.push(write_executable(&bin, "cargo"));
+    }
+
+    let project = root.join("project");
+    let other = root.join("other");
+    fs::create_dir_all(&project).unwrap();
+    fs::create_dir_all(&other).unwrap();
+    fs::write(project.join("rust-toolchain"), "stable\n").unwrap();
+    fs::write(other.join("rust-toolchain"), "stable\n").unwrap();
+    let settings = rustup_home.join("settings.toml");
+    fs::write(
+        &settings,
+        format!(
+            "default_toolchain = \"stable\"\n\n[overrides]\n\"{}\" = \"nightly\"\n",
+            project.display()
+        ),
+    )
+    .unwrap();
+
+    let dirs = vec![cargo_bin.clone()];
+    let cargo = ShimResolver::new(&dirs, project)
+        .resolve(&cargo_bin.join("cargo"))
+        .unwrap();
+    assert_eq!(cargo.kind, ShimKind::Rustup);
+    assert_eq!(cargo.selector.as_deref(), Some("nightly"));
+    assert_eq!(cargo.source, Some(settings.display().to_string()));
+    assert_eq!(cargo.target.as_ref(), Some(&installed[1]));
+
+    // Without an override the toolchain file still beats the default
+    let cargo = ShimResolver::new(&dirs, other.clone())
+        .resolve(&cargo_bin.join("cargo"))
+        .unwrap();
+    assert_eq!(cargo.selector.as_deref(), Some("stable"));
+    assert_eq!(
+        cargo.source,
+        Some(other.join("rust-toolchain").display().to_string())
+    );
+    assert_eq!(cargo.target.as_ref(), Some(&installed[0]));
+}
+
+/// A minimal little-endian ELF64 executable with an optional `PT_INTERP` segment
+fn elf64(machine: u16, interpreter: Option<&str>) -> Vec<u8> {
+    let mut bytes = vec![0u8; 64];
+    bytes[..4].copy_from_slice(b"\x7fELF");
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[16..18].copy_from_slice(&2u16.to_le_bytes());
+    bytes[18..20].copy_from_slice(&machine.to_le_bytes());
+    bytes[32..40].copy_from_slice(&64u64.to_le_bytes());
+    bytes[54..56].copy_from_slice(&56u16.to_le_bytes());
+
+    let Some(interpreter) = interpreter else {
+        return bytes;
+    };
+    bytes[56..58].copy_from_slice(&1u16.to_le_bytes());
+
+    let mut header = vec![0u8; 56];
+    header[..4].copy_from_slice(&3u32.to_le_bytes());
+    header[8..16].copy_from_slice(&120u64.to_le_bytes());
+    let size = interpreter.len() as u64 + 1;
+    header[32..40].copy_from_slice(&size.to_le_bytes());
+    bytes.extend(header);
+    bytes.extend(interpreter.as_bytes());
+    bytes.push(0);
+    bytes
+}
+
+#[test]
+fn introspect_decodes_elf_macho_and_scripts() {
+    let dir = TempDir::new().unwrap();
+    let bin = dir.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let python = write_executable(&bin, "python3");
+    let dirs = vec![bin.clone()];
+
+    let dynamic = dir.path().join("dynamic");
+    fs::write(&dynamic, elf64(183, Some("/lib/ld-linux-aarch64.so.1"))).unwrap();
+    let Some(FileKind::Elf(elf)) = introspect(&dynamic, &dirs) else {
+        panic!("expected ELF");
+    };
+    assert_eq!((elf.bits, elf.little_endian), (64, true));
+    assert_eq!(elf.machine, "aarch64");
+    assert_eq!(elf.linkage, Linkage::Dynamic);
+    assert_eq!(
+        elf.interpreter,
+        Some(PathBuf::from("/lib/ld-linux-aarch64.so.1"))
+    );
+
+    let static_elf = dir.path().join("static");
+    fs::write(&static_elf, elf64(62, None)).unwrap();
+    let Some(FileKind::Elf(elf)) = introspect(&static_elf, &dirs) else {
+        panic!("expected ELF");
+    };
+    assert_eq!(elf.machine, "x86-64");
+    assert_eq!(elf.linkage, Linkage::Static);
+
+    // Universal binary with x86_64 and arm64 slices
+    let mut fat = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2];
+    for cpu in [0x0100_0007u32, 0x0100_000c] {
+        let mut arch = vec![0u8; 20];
+        arch[..4].copy_from_slice(&cpu.to_be_bytes());
+        fat.extend(arch);
+    }
+    let universal = dir.path().join("universal");
+    fs::write(&universal, fat).unwrap();
+    let Some(FileKind::MachO(macho)) = introspect(&universal, &dirs) else {
+        panic!("expected Mach-O");
+    };
+    assert!(macho.universal);
+    assert_eq!(macho.architectures, ["x86_64", "arm64"]);
+
+    let env_script = dir.path().join("env-script");
+    fs::write(&env_script, "#!/usr/bin/env -S python3 -u\n").unwrap();
+    let Some(FileKind::Script(script)) = introspect(&env_script, &dirs) else {
+        panic!("expected script");
+    };
+    assert_eq!(script.interpreter, "/usr/bin/env");
+    assert_eq!(script.env_program.as_deref(), Some("python3"));
+    assert_eq!(script.resolved, Some(python));
+
+    let missing = dir.path().join("missing-interpreter");
+    fs::write(&missing, "#!/nonexistent/perl -w\n").unwrap();
+    let Some(FileKind::Script(script)) = introspect(&missing, &dirs) else {
+        panic!("expected script");
+    };
+    assert_eq!(script.interpreter, "/nonexistent/perl");
+    assert_eq!(script.resolved, None);
+
+    let text = dir.path().join("notes");
+    fs::write(&text, "plain text").unwrap();
+    assert_eq!(introspect(&text, &dirs), Some(FileKind::Other));
+}
+
+#[test]
+fn suggestions_offer_close_names_and_install_dirs_off_path() {
+    let _lock = env_lock();
+    let temp = TempDir::new().unwrap();
+    let on_path = temp.path().join("bin");
+    let other = temp.path().join("usr-bin");
+    let go_bin = temp.path().join("go/bin");
+    let cargo_bin = temp.path().join(".cargo/bin");
+    for (dir, names) in [
+        (&on_path, &["cargo", "rustc"][..]),
+        (&other, &["cargo", "carton", "crago"][..]),
+        (&go_bin, &["gopls"][..]),
+        (&cargo_bin, &["gopls"][..]),
+    ] {
+        fs::create_dir_all(dir).unwrap();
+        for name in names {
+            let path = dir.join(name);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+    let _home = set_env_var("HOME", temp.path().to_str().unwrap());
+
+    assert_eq!(edit_distance("carg", "cargo"), 1);
+    assert_eq!(edit_distance("crago", "cargo"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+
+    let path_dirs = vec![on_path.clone(), other.clone(), cargo_bin.clone()];
+    let install_dirs = vec![cargo_bin.clone(), go_bin.clone()];
+    let mut index = ExecutableIndex::disabled();
+
+    let found = suggest("crgo", &path_dirs, &install_dirs, &mut index, 2);
+    let names: Vec<(&str, usize)> = found
+        .near
+        .iter()
+        .map(|near| (near.name.as_str(), near.path_index))
+        .collect();
+    // The winning copy of cargo is listed once, closest names first
+    assert_eq!(names, vec![("cargo", 1), ("crago", 2)]);
+    assert_eq!(found.near[0].path, on_path.join("cargo"));
+
+    // ~/.cargo/bin is on PATH already, so only ~/go/bin is offered
+    let found = suggest("gopls", &path_dirs[..2], &install_dirs, &mut index, 3);
+    assert!(found.near.is_empty());
+    assert_eq!(found.off_path.len(), 2);
+    let found = suggest("gopls", &path_dirs, &install_dirs, &mut index, 3);
+    assert_eq!(found.off_path.len(), 1);
+    assert_eq!(found.off_path[0].path, go_bin.join("gopls"));
+    assert_eq!(found.off_path[0].add_command(), "whi add ~/go/bin");
+
+    assert!(suggest("zzzzzz", &path_dirs, &install_dirs, &mut index, 3).is_empty());
+}
+
+#[test]
+fn matrix_marks_winners_and_detects_split_toolchains() {
+    let temp = TempDir::new().unwrap();
+    let usr_bin = temp.path().join("usr/bin");
+    let llvm_bin = temp.path().join("opt/llvm/bin");
+    let local = temp.path().join("local");
+    for dir in [&usr_bin, &llvm_bin, &local] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    for name in ["cc", "ar", "nm"] {
+        write_executable(&usr_bin, name);
+    }
+    write_executable(&llvm_bin, "ld");
+    write_executable(&llvm_bin, "nm");
+    std::os::unix::fs::symlink(usr_bin.join("ar"), local.join("ar")).unwrap();
+
+    let dirs = vec![usr_bin.clone(), llvm_bin.clone(), local];
+    let names: Vec<String> = ["cc", "ld", "ar", "nmm", "zzz"]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let mut index = ExecutableIndex::disabled();
+    let matrix = build_matrix(&names, &dirs, &mut index);
+
+    assert_eq!(matrix.columns, vec![1, 2, 3]);
+    let ar = &matrix.rows[2];
+    assert_eq!(ar.winner().unwrap().path_index, 1);
+    assert!(ar.entry_at(3).unwrap().same_file);
+    assert!(ar.entry_at(2).is_none());
+
+    // A typo resolves to the closest name, an unknown name to nothing
+    let nm = &matrix.rows[3];
+    assert!(nm.is_corrected());
+    assert_eq!(nm.name, "nm");
+    assert_eq!(nm.entries.len(), 2);
+    assert!(matrix.rows[4].winner().is_none());
+
+    let prefixes = matrix.prefixes();
+    let usr = install_prefix(&usr_bin);
+    let llvm = install_prefix(&llvm_bin);
+    assert_eq!(usr, fs::canonicalize(temp.path().join("usr")).unwrap());
+    assert_eq!(
+        prefixes
+            .iter()
+            .map(|group| (&group.prefix, group.names.join(" ")))
+            .collect::<Vec<_>>(),
+        vec![(&usr, "cc ar nm".to_string()), (&llvm, "ld".to_string())]
+    );
+}
+
+#[test]
+fn winner_changes_list_names_that_resolve_differently() {
+    let temp = TempDir::new().unwrap();
+    let first = temp.path().join("first");
+    let second = temp.path().join("second");
+    fs::create_dir_all(&first).unwrap();
+    fs::create_dir_all(&second).unwrap();
+    write_executable(&first, "python");
+    write_executable(&first, "only-first");
+    write_executable(&second, "python");
+    write_executable(&second, "cargo");
+
+    let mut index = ExecutableIndex::disabled();
+    let before = vec![first.clone(), second.clone()];
+    let after = vec![second.clone()];
+    let changes = winner_changes(&before, &after, &mut index);
+
+    assert_eq!(
+        changes,
+        vec![
+            WinnerChange {
+                name: "only-first".to_string(),
+                before: Some(first.join("only-first")),
+                after: None,
+            },
+            WinnerChange {
+                name: "python".to_string(),
+                before: Some(first.join("python")),
+                after: Some(second.join("python")),
+            },
+        ]
+    );
+    assert!(winner_changes(&before, &before, &mut index).is_empty());
+}
@@ -1,10 +1,14 @@
 mod support;
 
 use std::fs;
+use std::os::unix::fs::PermissionsExt;
 
 use support::{env_lock, set_env_var};
 use tempfile::TempDir;
-use whi::session::history::HistoryContext;
+use whi::cli::{AddPosition, Args, BatchStep, PathAnchor, PathEdit};
+use whi::commands;
+use whi::session::history::{HistoryContext, Snapshot};
+use whi::session::recover::{Origin, find_recoveries};
 use whi::session::store::{
     clear_cursor, clear_session, get_current_snapshot, get_initial_path, get_session_file,
     read_path_snapshots, set_cursor, truncate_snapshots, write_path_snapshot,
@@ -102,3 +106,161 @@ fn store_truncate_and_initial_path() {
 
     clear_session(pid).unwrap();
 }
+
+#[test]
+fn recoveries_cover_removed_dirs_profiles_and_install_dirs() {
+    let root = TempDir::new().unwrap();
+    let dirs: Vec<_> = ["kept", "removed", "profile", "install", "empty"]
+        .iter()
+        .map(|name| {
+            let dir = root.path().join(name);
+            fs::create_dir(&dir).unwrap();
+            dir
+        })
+        .collect();
+    for dir in &dirs[..4] {
+        let tool = dir.join("tool");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let [kept, removed, profile, install, empty] = dirs.as_slice() else {
+        unreachable!()
+    };
+    let join = |entries: &[&std::path::PathBuf]| {
+        entries
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect::<Vec<_>>()
+            .join(":")
+    };
+
+    let snapshots = vec![
+        Snapshot {
+            timestamp: 100,
+            path: join(&[removed, empty]),
+        },
+        Snapshot {
+            timestamp: 200,
+            path: join(&[empty]),
+        },
+    ];
+    // The profile repeats the removed dir, which is only reported once
+    let profiles = vec![("work".to_string(), join(&[removed, profile]))];
+    let install_dirs = vec![install.clone(), profile.clone()];
+
+    let found = find_recoveries(
+        "tool",
+        &join(&[empty]),
+        &snapshots,
+        &profiles,
+        &install_dirs,
+    );
+    let origins: Vec<_> = found.iter().map(|r| (&r.origin, &r.dir)).collect();
+    assert_eq!(
+        origins,
+        vec![
+            (
+                &Origin::Removed {
+                    removed_at: Some(200)
+                },
+                removed
+            ),
+            (&Origin::Profile("work".to_string()), profile),
+            (&Origin::InstallDir, install),
+        ]
+    );
+    assert_eq!(found[0].path, removed.join("tool"));
+    assert_eq!(
+        found[2].restore_command(),
+        format!("whi add {}", install.display())
+    );
+
+    // Directories already on PATH are never offered
+    let on_path = find_recoveries("tool", &join(&[kept, removed]), &snapshots, &profiles, &[]);
+    assert_eq!(on_path.len(), 1);
+    assert_eq!(on_path[0].dir, *profile);
+}
+
+#[test]
+fn batch_aborts_on_first_error_and_writes_one_snapshot() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let runtime_dir = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+    let _runtime = set_env_var("XDG_RUNTIME_DIR", runtime_dir.path());
+    let _session = set_env_var("WHI_SESSION_PID", "12");
+    let _path = set_env_var("PATH", "/a:/b:/c");
+
+    let move_step = |from, to| BatchStep {
+        operation: format!("move {from} {to}"),
+        args: Args {
+            path_edit: Some(PathEdit::Move { from, to }),
+            ..Default::default()
+        },
+    };
+    let history = HistoryContext::global(12).unwrap();
+
+    let failing = Args {
+        batch: vec![move_step(1, 3), move_step(9, 1)],
+        ..Default::default()
+    };
+    assert_eq!(commands::run(&failing), 2);
+    assert!(history.read_snapshots().unwrap().is_empty());
+
+    let append = BatchStep {
+        operation: "add --append /d".to_string(),
+        args: Args {
+            add_paths: vec!["/d".to_string()],
+            add_position: AddPosition::Append,
+            ..Default::default()
+        },
+    };
+    let batch = Args {
+        batch: vec![move_step(1, 3), append],
+        ..Default::default()
+    };
+    assert_eq!(commands::run(&batch), 0);
+    assert_eq!(history.read_snapshots().unwrap(), vec!["/b:/c:/a:/d"]);
+}
+
+#[test]
+fn add_anchors_name_entries_of_the_path_before_moving() {
+    let _lock = env_lock();
+    let home = TempDir::new().unwrap();
+    let runtime_dir = TempDir::new().unwrap();
+    let _home = set_env_var("HOME", home.path());
+    let _runtime = set_env_var("XDG_RUNTIME_DIR", runtime_dir.path());
+    let _session = set_env_var("WHI_SESSION_PID", "13");
+    let _path = set_env_var("PATH", "/a:/b:/c:/usr/bin");
+    let history = HistoryContext::global(13).unwrap();
+
+    let add = |path: &str, position| Args {
+        add_paths: vec![path.to_string()],
+        add_position: position,
+        move_if_present: true,
+        quiet: true,
+        ..Default::default()
+    };
+    let before = |anchor| AddPosition::Before(PathAnchor::Index(anchor));
+
+    assert_eq!(commands::run(&add("/a", before(3))), 0);
+    assert_eq!(commands::run(&add("/a", before(4))), 0);
+    assert_eq!(
+        commands::run(&add("/a", AddPosition::After(PathAnchor::Index(3)))),
+        0
+    );
+    assert_eq!(commands::run(&add("/c", before(1))), 0);
+    assert_eq!(
+        history.read_snapshots().unwrap(),
+        vec![
+            "/b:/a:/c:/usr/bin",
+            "/b:/c:/a:/usr/bin",
+            "/b:/c:/a:/usr/bin",
+            "/c:/a:/b:/usr/bin",
+        ]
+    );
+
+    // The anchor is the entry being moved
+    assert_eq!(commands::run(&add("/b", before(2))), 2);
+    assert_eq!(history.read_snapshots().unwrap().len(), 4);
+}